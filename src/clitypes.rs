@@ -250,6 +250,20 @@ impl From<flexi_logger::FlexiLoggerError> for CliError {
     }
 }
 
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for CliError {
+    fn from(err: zip::result::ZipError) -> CliError {
+        CliError::Other(format!("Zip error: {err}"))
+    }
+}
+
+#[cfg(feature = "excel_xml")]
+impl From<quick_xml::Error> for CliError {
+    fn from(err: quick_xml::Error) -> CliError {
+        CliError::Other(format!("XML error: {err}"))
+    }
+}
+
 impl From<chrono_tz::ParseError> for CliError {
     fn from(err: chrono_tz::ParseError) -> CliError {
         CliError::Other(format!("ChronoTZ error: {err:?}"))
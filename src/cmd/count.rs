@@ -151,9 +151,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     (idx.count(), empty_record_stats)
                 },
                 None => {
-                    // if --no-polars or its a snappy compressed file, use the regular CSV reader
+                    // if --no-polars, or its a compressed or non-CSV file,
+                    // use the regular CSV reader
                     #[cfg(feature = "polars")]
-                    if args.flag_no_polars || conf.is_snappy() {
+                    if args.flag_no_polars
+                        || conf.compression().is_compressed()
+                        || conf.data_format().is_some()
+                    {
                         count_input(&conf, count_delims_mode)?
                     } else {
                         let count = polars_count_input(&conf, args.flag_low_memory)?;
@@ -428,10 +432,8 @@ pub fn polars_count_input(conf: &Config, low_memory: bool) -> CliResult<u64> {
 
     let filepath = if is_stdin {
         let mut temp_file = tempfile::Builder::new().suffix(".csv").tempfile()?;
-        let stdin = std::io::stdin();
-        let mut stdin_handle = stdin.lock();
-        std::io::copy(&mut stdin_handle, &mut temp_file)?;
-        drop(stdin_handle);
+        // use the config's reader so compressed stdin is decoded before polars sees it
+        std::io::copy(&mut conf.io_reader()?, &mut temp_file)?;
 
         let (_, tempfile_pb) =
             temp_file.keep().or(Err(
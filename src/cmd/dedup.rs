@@ -12,6 +12,12 @@ Either way, the output will not only be deduplicated, it will also be sorted.
 
 A duplicate count will also be sent to <stderr>.
 
+When --keep is set, dedup groups the duplicates of each key and picks the surviving
+record using the given policy. In this mode, --dupes-output lists every removed record
+prefixed with the columns dupe_rowno, dupe_group, dupe_group_count and kept_rowno,
+so each removed row can be traced back to the row that replaced it.
+Row numbers are 1-based and do not count the header row.
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_dedup.rs.
 
 Usage:
@@ -29,6 +35,18 @@ dedup options:
                                memory to sort it first. Meant to be used in tandem and
                                after an extsort.
     -D, --dupes-output <file>  Write duplicates to <file>.
+    --keep <policy>            Which record of a group of duplicates to keep.
+                               Valid policies:
+                                 first         - the first record in input order
+                                 last          - the last record in input order
+                                 max:<column>  - the record with the largest value
+                                                 in <column>
+                                 min:<column>  - the record with the smallest value
+                                                 in <column>
+                                 most-complete - the record with the fewest empty fields
+                               max/min compare numbers numerically and dates
+                               chronologically. Other values rank below any number
+                               or date. Ties are resolved in favor of the earlier record.
     -H, --human-readable       Comma separate duplicate count.
     -j, --jobs <arg>           The number of jobs to run in parallel when sorting
                                an unsorted CSV, before deduping.
@@ -51,7 +69,7 @@ Common options:
                                CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::cmp;
+use std::{cmp, str::FromStr};
 
 use csv::ByteRecord;
 use rayon::slice::ParallelSliceMut;
@@ -64,6 +82,7 @@ use crate::{
     select::SelectColumns,
     util, CliResult,
 };
+
 #[derive(Deserialize)]
 struct Args {
     arg_input:           Option<String>,
@@ -72,6 +91,7 @@ struct Args {
     flag_ignore_case:    bool,
     flag_sorted:         bool,
     flag_dupes_output:   Option<String>,
+    flag_keep:           Option<String>,
     flag_output:         Option<String>,
     flag_no_headers:     bool,
     flag_delimiter:      Option<Delimiter>,
@@ -87,6 +107,123 @@ enum ComparisonMode {
     Normal,
 }
 
+/// The policy used to pick the surviving record of a group of duplicates.
+/// Also used by `extdedup`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeepPolicy {
+    First,
+    Last,
+    Max(String),
+    Min(String),
+    MostComplete,
+}
+
+impl FromStr for KeepPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        match lower.as_str() {
+            "first" => return Ok(KeepPolicy::First),
+            "last" => return Ok(KeepPolicy::Last),
+            "most-complete" => return Ok(KeepPolicy::MostComplete),
+            _ => {},
+        }
+        match s.split_once(':') {
+            Some((policy, column)) if !column.is_empty() => {
+                match policy.to_ascii_lowercase().as_str() {
+                    "max" => Ok(KeepPolicy::Max(column.to_string())),
+                    "min" => Ok(KeepPolicy::Min(column.to_string())),
+                    _ => Err(format!("Invalid keep policy: {s}")),
+                }
+            },
+            _ => Err(format!(
+                "Invalid keep policy: {s}. Valid policies are: first, last, max:<column>, \
+                 min:<column>, most-complete."
+            )),
+        }
+    }
+}
+
+/// Ranks the records of a group of duplicates according to a `KeepPolicy`.
+pub struct KeepScorer {
+    policy:     KeepPolicy,
+    column:     usize,
+    prefer_dmy: bool,
+}
+
+impl KeepScorer {
+    pub fn new(policy: KeepPolicy, headers: &ByteRecord, no_headers: bool) -> Result<Self, String> {
+        let column = match &policy {
+            KeepPolicy::Max(col) | KeepPolicy::Min(col) => {
+                let sel = SelectColumns::parse(col)?.selection(headers, !no_headers)?;
+                if sel.len() != 1 {
+                    return Err(format!(
+                        "--keep column selector \"{col}\" must select exactly one column."
+                    ));
+                }
+                sel[0]
+            },
+            _ => 0,
+        };
+        Ok(Self {
+            policy,
+            column,
+            prefer_dmy: util::get_envvar_flag("QSV_PREFER_DMY"),
+        })
+    }
+
+    /// returns the score of a record. Within a group, the record with the
+    /// highest score is kept.
+    #[inline]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn score(&self, record: &ByteRecord) -> f64 {
+        match self.policy {
+            KeepPolicy::Max(_) => self.value_score(record.get(self.column).unwrap_or_default()),
+            KeepPolicy::Min(_) => {
+                let score = self.value_score(record.get(self.column).unwrap_or_default());
+                if score == f64::NEG_INFINITY {
+                    score
+                } else {
+                    -score
+                }
+            },
+            KeepPolicy::MostComplete => record
+                .iter()
+                .filter(|field| !field.trim_ascii().is_empty())
+                .count() as f64,
+            KeepPolicy::First | KeepPolicy::Last => 0.0,
+        }
+    }
+
+    /// returns true if a later record with `candidate` score replaces
+    /// the current survivor with `survivor` score
+    #[inline]
+    pub fn replaces(&self, survivor: f64, candidate: f64) -> bool {
+        match self.policy {
+            KeepPolicy::First => false,
+            KeepPolicy::Last => true,
+            _ => candidate > survivor,
+        }
+    }
+
+    /// numbers score as themselves, dates as their unix timestamp in milliseconds.
+    /// Anything else ranks lowest.
+    #[allow(clippy::cast_precision_loss)]
+    fn value_score(&self, value: &[u8]) -> f64 {
+        let value = value.trim_ascii();
+        if let Ok(num) = fast_float2::parse::<f64, &[u8]>(value) {
+            return num;
+        }
+        if let Ok(s) = from_utf8(value) {
+            if let Ok(date) = qsv_dateparser::parse_with_preference(s, self.prefer_dmy) {
+                return date.timestamp_millis() as f64;
+            }
+        }
+        f64::NEG_INFINITY
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -98,6 +235,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ComparisonMode::Normal
     };
 
+    let keep_policy = match args.flag_keep {
+        Some(ref keep) => match KeepPolicy::from_str(keep) {
+            Ok(policy) => Some(policy),
+            Err(e) => return fail_incorrectusage_clierror!("{e}"),
+        },
+        None => None,
+    };
+
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -109,15 +254,78 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut dupewtr = Config::new(args.flag_dupes_output.as_ref()).writer()?;
 
     let headers = rdr.byte_headers()?;
+    let scorer = match keep_policy {
+        Some(policy) => Some(KeepScorer::new(policy, headers, args.flag_no_headers)?),
+        None => None,
+    };
     if dupes_output {
-        dupewtr.write_byte_record(headers)?;
+        if scorer.is_some() {
+            let mut dupe_headers = ByteRecord::new();
+            for col in DUPE_GROUP_COLUMNS {
+                dupe_headers.push_field(col.as_bytes());
+            }
+            dupe_headers.extend(headers.iter());
+            dupewtr.write_byte_record(&dupe_headers)?;
+        } else {
+            dupewtr.write_byte_record(headers)?;
+        }
     }
     let sel = rconfig.selection(headers)?;
 
     rconfig.write_headers(&mut rdr, &mut wtr)?;
     let mut dupe_count = 0_usize;
 
-    if args.flag_sorted {
+    if let Some(scorer) = scorer {
+        let mut group_writer = DupeGroupWriter::new(&scorer, dupes_output);
+
+        if args.flag_sorted {
+            // stream the groups of an already sorted CSV
+            let mut group: Vec<(u64, ByteRecord)> = Vec::new();
+            let mut record = ByteRecord::new();
+            let mut rowno = 0_u64;
+            while rdr.read_byte_record(&mut record)? {
+                rowno += 1;
+                if let Some((_, last)) = group.last() {
+                    match compare_keys(&compare_mode, sel.select(last), sel.select(&record)) {
+                        cmp::Ordering::Equal => {},
+                        cmp::Ordering::Less => {
+                            dupe_count += group_writer.write(&group, &mut wtr, &mut dupewtr)?;
+                            group.clear();
+                        },
+                        cmp::Ordering::Greater => {
+                            return fail_clierror!(
+                                "Aborting! Input not sorted! {last:?} is greater than {record:?}"
+                            );
+                        },
+                    }
+                }
+                group.push((rowno, record.clone()));
+            }
+            dupe_count += group_writer.write(&group, &mut wtr, &mut dupewtr)?;
+        } else {
+            if let Some(path) = rconfig.path.clone() {
+                util::mem_file_check(&path, false, args.flag_memcheck)?;
+            }
+
+            util::njobs(args.flag_jobs);
+
+            let mut all = rdr
+                .byte_records()
+                .enumerate()
+                .map(|(idx, r)| r.map(|record| (idx as u64 + 1, record)))
+                .collect::<Result<Vec<_>, _>>()?;
+            // par_sort_by is stable, so each group stays in input order
+            all.par_sort_by(|(_, r1), (_, r2)| {
+                compare_keys(&compare_mode, sel.select(r1), sel.select(r2))
+            });
+
+            for group in all.chunk_by(|(_, r1), (_, r2)| {
+                compare_keys(&compare_mode, sel.select(r1), sel.select(r2)) == cmp::Ordering::Equal
+            }) {
+                dupe_count += group_writer.write(group, &mut wtr, &mut dupewtr)?;
+            }
+        }
+    } else if args.flag_sorted {
         let mut record = ByteRecord::new();
         let mut next_record = ByteRecord::new();
 
@@ -246,6 +454,93 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// the columns prepended to --dupes-output when --keep is set
+pub const DUPE_GROUP_COLUMNS: [&str; 4] =
+    ["dupe_rowno", "dupe_group", "dupe_group_count", "kept_rowno"];
+
+#[inline]
+fn compare_keys<'a, L, R>(compare_mode: &ComparisonMode, a: L, b: R) -> cmp::Ordering
+where
+    L: Iterator<Item = &'a [u8]>,
+    R: Iterator<Item = &'a [u8]>,
+{
+    match compare_mode {
+        ComparisonMode::Normal => iter_cmp(a, b),
+        ComparisonMode::Numeric => iter_cmp_num(a, b),
+        ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+    }
+}
+
+/// Writes the survivor of each group of duplicates to the output and the
+/// rest of the group to the dupes output, numbering the groups with duplicates.
+struct DupeGroupWriter<'a> {
+    scorer:        &'a KeepScorer,
+    dupes_output:  bool,
+    next_group_id: u64,
+    dupe_row:      ByteRecord,
+}
+
+impl<'a> DupeGroupWriter<'a> {
+    fn new(scorer: &'a KeepScorer, dupes_output: bool) -> Self {
+        Self {
+            scorer,
+            dupes_output,
+            next_group_id: 1,
+            dupe_row: ByteRecord::new(),
+        }
+    }
+
+    /// returns the number of duplicates in the group
+    fn write<W: std::io::Write>(
+        &mut self,
+        group: &[(u64, ByteRecord)],
+        wtr: &mut csv::Writer<W>,
+        dupewtr: &mut csv::Writer<W>,
+    ) -> CliResult<usize> {
+        if group.is_empty() {
+            return Ok(0);
+        }
+
+        let mut kept_idx = 0;
+        let mut kept_score = self.scorer.score(&group[0].1);
+        for (idx, (_, record)) in group.iter().enumerate().skip(1) {
+            let score = self.scorer.score(record);
+            if self.scorer.replaces(kept_score, score) {
+                kept_idx = idx;
+                kept_score = score;
+            }
+        }
+        wtr.write_byte_record(&group[kept_idx].1)?;
+
+        let dupe_count = group.len() - 1;
+        if dupe_count == 0 {
+            return Ok(0);
+        }
+
+        if self.dupes_output {
+            let mut buffer = itoa::Buffer::new();
+            let group_id = buffer.format(self.next_group_id).as_bytes().to_vec();
+            let group_count = buffer.format(group.len()).as_bytes().to_vec();
+            let kept_rowno = buffer.format(group[kept_idx].0).as_bytes().to_vec();
+            for (idx, (rowno, record)) in group.iter().enumerate() {
+                if idx == kept_idx {
+                    continue;
+                }
+                self.dupe_row.clear();
+                self.dupe_row.push_field(buffer.format(*rowno).as_bytes());
+                self.dupe_row.push_field(&group_id);
+                self.dupe_row.push_field(&group_count);
+                self.dupe_row.push_field(&kept_rowno);
+                self.dupe_row.extend(record.iter());
+                dupewtr.write_byte_record(&self.dupe_row)?;
+            }
+        }
+        self.next_group_id += 1;
+
+        Ok(dupe_count)
+    }
+}
+
 /// Try comparing `a` and `b` ignoring the case
 #[inline]
 pub fn iter_cmp_ignore_case<'a, L, R>(mut a: L, mut b: R) -> cmp::Ordering
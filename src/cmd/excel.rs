@@ -67,6 +67,28 @@ Export metadata for all sheets in JSON format:
     qsv excel --metadata Short input.xlsx
     qsv excel --metadata S input.xlsx
 
+Export a sheet whose two-row header has merged cells, filling the merged cells &
+combining the header rows into single column names (e.g. Population_2020):
+    qsv excel --merged-cells --header-rows 2 input.xlsx
+
+Export the first sheet with numbers & dates formatted as they are displayed in Excel,
+and write the number format of every column to formats.csv:
+    qsv excel --as-displayed --format-sidecar formats.csv input.xlsx
+
+Export every worksheet to a CSV file in the "exported" directory, reading the workbook once.
+A manifest.csv listing the exported files is also written:
+    qsv excel --all-sheets exported input.xlsx
+
+Also export every table in an XLSX file to its own CSV file:
+    qsv excel --all-sheets exported --include-tables input.xlsx
+
+Write a CSV into the "Budget" sheet of a template workbook, from cell B3 below the
+template's header row. Other sheets, styles & formulas in the template are kept:
+    qsv excel --write data.csv --sheet Budget --range B3 --data-only template.xlsx -o filled.xlsx
+
+Append the data rows of a CSV after the last row of the first sheet of a workbook, in place:
+    qsv excel --write new_rows.csv --append input.xlsx
+
 Prompt for spreadsheets to export and then prompt where to save the CSV:
     qsv prompt -d ~/Documents -m 'Select a spreadsheet to export to CSV' -F xlsx,xls,ods | \
      qsv excel - | qsv prompt -m 'Save exported CSV to...' --fd-output
@@ -75,6 +97,7 @@ For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_exc
 
 Usage:
     qsv excel [options] [<input>]
+    qsv excel --write <csv> [options] <input>
     qsv excel --help
 
 Excel argument:
@@ -87,6 +110,15 @@ Excel options:
                                If the sheet cannot be found, qsv will read the first sheet.
                                [default: 0]
     --header-row <row>         The header row. Set if other than the first non-empty row of the sheet.
+    --header-rows <n>          The number of header rows, starting from the header row, to combine
+                               into single column names. e.g. with 3 header rows, "Population",
+                               "2020" & "Male" become "Population_2020_Male". Empty parts and
+                               parts repeated from the row above are skipped. Use --merged-cells
+                               if the header rows have merged cells. [default: 1]
+    --header-join <sep>        The separator used to combine the --header-rows. [default: _]
+    --merged-cells             Fill every cell of a merged region with the value of its top-left
+                               cell, instead of leaving the other cells empty.
+                               Only supported for xlsx and xls files.
     --metadata <c|s|j|J|S>     Outputs workbook metadata in CSV or JSON format:
                                  index, sheet_name, headers, type, visible, column_count, row_count,
                                  safe_headers, safe_headers_count, unsafe_headers, unsafe_headers_count
@@ -128,6 +160,17 @@ Excel options:
                                the --sheet option is ignored.
                                If the range is not found, qsv will exit with an error.
 
+    --all-sheets <outdir>      Export every worksheet of the workbook in one pass, each to a
+                               <outdir>/<sheet>.csv file. The sheet names are converted to
+                               filenames the same way `safenames` converts headers
+                               (e.g. "Sales 2024" -> sales_2024.csv). Chart sheets & other
+                               non-worksheets are skipped. --sheet, --range & --table are ignored.
+                               A <outdir>/manifest.csv with the sheet_name, table_name, file,
+                               row_count (excluding the header) & column_count of every
+                               exported file is also written.
+    --include-tables           With --all-sheets, also export every table of an XLSX workbook
+                               to <outdir>/<table>.csv.
+
     --error-format <format>    The format to use when formatting error cells.
                                There are 3 formats:
                                  - "code": return the error code.
@@ -147,25 +190,74 @@ Excel options:
                                return the date as if no date-format was specified.
      --keep-zero-time          Keep the time part of a date-time field if it is 00:00:00.
                                By default, qsv will remove the time part if it is 00:00:00.
+    --as-displayed             Format numbers & dates as they are displayed in the spreadsheet,
+                               applying each cell's number format - percentages, currencies,
+                               decimal places, thousands separators, leading zeros and custom
+                               date/time formats (e.g. 0.1234 formatted as 0.0% is 12.3%).
+                               Overrides --date-format for cells with a date format.
+                               Scientific notation, fractions and conditional formats are
+                               not supported and are exported as usual.
+                               Built-in formats are displayed as in the en-US locale.
+                               Only supported for xlsx and xlsm files.
+    --format-sidecar <file>    Write the detected number format of every column to <file>,
+                               a CSV with the column, number_format & format_type
+                               (general, number, percent, currency, date, time, datetime,
+                               scientific, fraction or text) columns. The number format of a
+                               column is the format of most of its non-empty cells.
+                               Only supported for xlsx and xlsm files. Ignored with --all-sheets.
      -j, --jobs <arg>          The number of jobs to run in parallel.
                                When not set, the number of jobs is set to the number of CPUs detected.
 
+Write options:
+    --write <csv>              Write <csv> into a sheet of an existing xlsx/xlsm workbook instead of
+                               exporting a sheet, keeping the rest of the workbook - other sheets,
+                               styles, formulas, charts, etc. The workbook is updated in place,
+                               unless --output is set.
+                               The CSV is written from the top-left cell of --range, which can be
+                               a cell (e.g. B3), a range (e.g. B3:F20), either of them in a named
+                               sheet (e.g. 'Sheet 2'!B3) or a defined name. Otherwise, it's written
+                               from cell A1 of --sheet. If --range is a range, the CSV must fit in it.
+                               Numbers are written as numbers, and other values as text.
+                               The styles of the overwritten cells are kept, and formulas are
+                               recalculated when the workbook is opened in Excel.
+                               Note that Excel tables are not resized, and that the first cell of
+                               a shared formula can't be overwritten, as the other cells of the
+                               formula would lose it.
+    --append                   With --write, write the data rows of the CSV after the last row of
+                               the sheet with values, from the column of --range (default: A).
+                               The new cells get the styles of the cells of the last row.
+    --data-only                With --write, don't write the header row of the CSV.
+
 Common options:
     -h, --help                 Display this message
     -o, --output <file>        Write output to <file> instead of stdout.
-    -d, --delimiter <arg>      The delimiter to use when writing CSV data.
+                               With --write, write the updated workbook to <file>.
+    -d, --delimiter <arg>      The delimiter to use when writing CSV data,
+                               or reading the --write CSV.
                                Must be a single character. [default: ,]
     -Q, --quiet                Do not display export summary message.
 "#;
 
-use std::{cmp, fmt::Write, io::Read, path::PathBuf};
+#[cfg(feature = "excel_xml")]
+use std::{borrow::Cow, collections::BTreeMap, io::Write as _};
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt::Write,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
 use calamine::{
-    open_workbook, open_workbook_auto, Data, Error, HeaderRow, Range, Reader, SheetType, Sheets,
+    open_workbook, open_workbook_auto, Data, Dimensions, Error, HeaderRow, Range, Reader,
+    SheetType, Sheets,
 };
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use file_format::FileFormat;
 use indicatif::HumanCount;
 use log::info;
+#[cfg(feature = "excel_xml")]
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
 use rayon::prelude::{IndexedParallelIterator, ParallelIterator, ParallelSlice};
 use serde::{Deserialize, Serialize};
 
@@ -175,6 +267,7 @@ use crate::{
 };
 
 #[derive(Deserialize)]
+#[cfg_attr(not(feature = "excel_xml"), allow(dead_code))]
 struct Args {
     arg_input:           String,
     flag_sheet:          String,
@@ -191,6 +284,16 @@ struct Args {
     flag_date_format:    Option<String>,
     flag_keep_zero_time: bool,
     flag_jobs:           Option<usize>,
+    flag_all_sheets:     Option<String>,
+    flag_include_tables: bool,
+    flag_merged_cells:   bool,
+    flag_header_rows:    usize,
+    flag_header_join:    String,
+    flag_as_displayed:   bool,
+    flag_format_sidecar: Option<String>,
+    flag_write:          Option<String>,
+    flag_append:         bool,
+    flag_data_only:      bool,
 }
 
 #[derive(PartialEq)]
@@ -203,7 +306,7 @@ enum MetadataMode {
     None,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 enum ErrorFormat {
     Code,
     Formula,
@@ -423,7 +526,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         std::fs::write(&tmpfile, &buffer)?;
         tmpfile.to_string_lossy().to_string()
     } else {
-        args.arg_input
+        args.arg_input.clone()
     };
     let path = &path_string;
 
@@ -440,6 +543,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .unwrap_or_default()
         .to_ascii_lowercase();
 
+    if args.flag_write.is_some() {
+        return write_into_workbook(&sce, &format, &args);
+    }
+
     let mut ods_flag = false;
     let mut sheets = match (format).as_str() {
         "xls" | "xla" => Sheets::Xls(open_workbook(path).map_err(Error::Xls)?),
@@ -721,6 +828,65 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // --------------------------------------------------------------------
     // we're not exporting metadata, we're exporting the spreadsheet to CSV
 
+    let header_row: HeaderRow = if let Some(hr) = args.flag_header_row {
+        info!("processing row {hr} as header...");
+        HeaderRow::Row(hr)
+    } else {
+        info!("processing first non-empty row as header...");
+        HeaderRow::FirstNonEmptyRow
+    };
+    sheets.with_header_row(header_row);
+
+    let cell_options = CellOptions {
+        error_format:   match args.flag_error_format.to_lowercase().as_str() {
+            "formula" => ErrorFormat::Formula,
+            "both" => ErrorFormat::Both,
+            _ => ErrorFormat::Code,
+        },
+        trim:           args.flag_trim,
+        date_format:    args.flag_date_format.clone(),
+        keep_zero_time: args.flag_keep_zero_time,
+        njobs:          util::njobs(args.flag_jobs),
+        merged_cells:   args.flag_merged_cells,
+        header_rows:    args.flag_header_rows,
+        header_join:    args.flag_header_join.clone(),
+        as_displayed:   args.flag_as_displayed,
+    };
+    if cell_options.header_rows == 0 {
+        return fail_incorrectusage_clierror!("--header-rows must be at least 1.");
+    }
+
+    // calamine doesn't expose the cell number formats, so we read them from the workbook
+    let mut xlsx_formats = if cell_options.as_displayed || args.flag_format_sidecar.is_some() {
+        if format == "xlsx" || format == "xlsm" {
+            Some(XlsxFormats::open(&sce)?)
+        } else {
+            wwarn!(
+                "--as-displayed and --format-sidecar are only supported for xlsx and xlsm files. \
+                 Ignoring them."
+            );
+            None
+        }
+    } else {
+        None
+    };
+
+    if let Some(ref outdir) = args.flag_all_sheets {
+        return export_all_sheets(
+            &mut sheets,
+            outdir,
+            args.flag_include_tables,
+            &cell_options,
+            &mut xlsx_formats,
+            args.flag_delimiter,
+            args.flag_flexible,
+            args.flag_quiet,
+        );
+    }
+    if args.flag_include_tables {
+        return fail_incorrectusage_clierror!("--include-tables requires --all-sheets.");
+    }
+
     // check if a table is being requested
     let table = if let Some(ref requested_table) = args.flag_table {
         if format == "xlsx" {
@@ -812,18 +978,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         return fail_clierror!("Cannot get sheet index for {sheet}");
     };
 
-    let header_row: HeaderRow = if let Some(hr) = args.flag_header_row {
-        info!("processing row {hr} as header...");
-        HeaderRow::Row(hr)
-    } else {
-        info!("processing first non-empty row as header...");
-        HeaderRow::FirstNonEmptyRow
-    };
-    sheets.with_header_row(header_row);
-
+    let table_sheet = table.as_ref().map(|t| t.sheet_name().to_string());
     let export_mode: ExportMode;
     let table_headers;
-    let range: Range<Data> = if let Some(table) = table {
+    let mut range: Range<Data> = if let Some(table) = table {
         export_mode = ExportMode::Table;
         table_headers = table.columns().to_vec();
         table.data().to_owned()
@@ -906,14 +1064,30 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     };
 
-    let (row_count, col_count) = range.get_size();
+    // the actual name of the exported sheet, as --range may have changed the sheet, and
+    // the merged regions & cell formats are by case-sensitive sheet name
+    let lower_sheet = sheet.to_lowercase();
+    let sheet_name = table_sheet
+        .or_else(|| {
+            sheet_names
+                .iter()
+                .find(|s| s.to_lowercase() == lower_sheet)
+                .cloned()
+        })
+        .unwrap_or_default();
+
+    if cell_options.merged_cells && export_mode != ExportMode::Table && !sheet_name.is_empty() {
+        load_merged_regions(&mut sheets)?;
+        fill_merged_cells(&mut range, &sheet_merged_regions(&sheets, &sheet_name));
+    }
 
-    let error_format = match args.flag_error_format.to_lowercase().as_str() {
-        "formula" => ErrorFormat::Formula,
-        "both" => ErrorFormat::Both,
-        _ => ErrorFormat::Code,
+    let cell_formats = match xlsx_formats {
+        Some(ref mut formats) => formats.sheet_formats(&sheet_name)?,
+        None => CellFormats::default(),
     };
 
+    let (row_count, col_count) = range.get_size();
+
     if row_count == 0 {
         let msg = match export_mode {
             ExportMode::Table => format!("Table: {:?} ", args.flag_table),
@@ -923,36 +1097,105 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         };
         return fail_clierror!("\"{msg}\"is empty.");
     }
-    // there are rows to export
-    let mut rows_iter = range.rows();
-
-    let range_start = range.start().unwrap_or((0, 0));
-
     // get the sheet formulas only if we need them
     // as this is an expensive operation
-    let sheet_formulas = if error_format == ErrorFormat::Code {
+    let sheet_formulas = if cell_options.error_format == ErrorFormat::Code {
         Range::empty()
     } else {
         sheets.worksheet_formula(&sheet)?
     };
 
+    info!("exporting sheet ({sheet})...");
+    let (headers, header_rows) = if export_mode == ExportMode::Table {
+        (table_headers, 1)
+    } else {
+        (
+            range_headers(&range, &cell_options),
+            cell_options.header_rows,
+        )
+    };
+    if let Some(ref sidecar) = args.flag_format_sidecar {
+        write_format_sidecar(sidecar, &range, &headers, header_rows, &cell_formats)?;
+    }
+    write_range(
+        &range,
+        headers,
+        header_rows,
+        &sheet_formulas,
+        &cell_formats,
+        &cell_options,
+        &mut wtr,
+    )?;
+
+    if !args.flag_quiet {
+        let msg = match export_mode {
+            ExportMode::Table => format!("Table: \"{}\"", args.flag_table.unwrap()),
+            ExportMode::Range => {
+                format!("Sheet: \"{sheet}\" Range:\"{}\"", args.flag_range.unwrap())
+            },
+            ExportMode::Sheet => format!("\"{sheet}\" sheet"),
+            ExportMode::NothingToExport => String::new(),
+        };
+        winfo!(
+            "{}",
+            format!(
+                "{} {}-column rows exported from {msg}",
+                HumanCount(row_count.saturating_sub(header_rows) as u64),
+                HumanCount(col_count as u64),
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// how cells are converted to CSV fields
+struct CellOptions {
+    error_format:   ErrorFormat,
+    trim:           bool,
+    date_format:    Option<String>,
+    keep_zero_time: bool,
+    njobs:          usize,
+    merged_cells:   bool,
+    header_rows:    usize,
+    header_join:    String,
+    as_displayed:   bool,
+}
+
+/// Writes a range to CSV - the headers, and the rows of the range after its first
+/// `header_rows` rows. The cells are converted to CSV fields in parallel.
+/// Returns the number of data rows written.
+fn write_range<W: std::io::Write>(
+    range: &Range<Data>,
+    headers: Vec<String>,
+    header_rows: usize,
+    sheet_formulas: &Range<String>,
+    cell_formats: &CellFormats,
+    options: &CellOptions,
+    wtr: &mut csv::Writer<W>,
+) -> CliResult<usize> {
+    let (row_count, col_count) = range.get_size();
+    if row_count == 0 {
+        return Ok(0);
+    }
+    let mut rows_iter = range.rows();
+
+    let range_start = range.start().unwrap_or((0, 0));
+
     // amortize allocations
     let mut record = csv::StringRecord::with_capacity(500, col_count);
     let mut trimmed_record = csv::StringRecord::with_capacity(500, col_count);
 
     // get headers
-    info!("exporting sheet ({sheet})...");
-    let headers = if export_mode == ExportMode::Table {
-        table_headers
-    } else {
-        range.headers().unwrap_or_default()
-    };
     for header in headers {
         record.push_field(&header);
     }
-    rows_iter.next(); // we processed the header row
+    // we processed the header rows
+    for _ in 0..header_rows {
+        rows_iter.next();
+    }
 
-    let trim = args.flag_trim;
+    let trim = options.trim;
 
     if trim {
         record.trim();
@@ -969,9 +1212,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     wtr.write_record(&record)?;
 
     let no_date_format: bool;
-    let date_format = if let Some(df) = args.flag_date_format {
+    let date_format = if let Some(ref df) = options.date_format {
         no_date_format = false;
-        df
+        df.clone()
     } else {
         no_date_format = true;
         String::new()
@@ -979,20 +1222,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let mut rows: Vec<(u32, &[Data])> = Vec::with_capacity(row_count);
 
-    // we add 1 as we already processed the header row
-    let mut row_idx = range_start.0 + 1;
+    // we add header_rows as we already processed the header rows
+    let mut row_idx = range_start.0 + header_rows as u32;
     // queue rest of the rows for processing as data rows
     for row in rows_iter {
         rows.push((row_idx, row));
         row_idx += 1;
     }
 
-    let ncpus = util::njobs(args.flag_jobs);
+    let ncpus = options.njobs;
 
     // set chunk_size to number of rows per core/thread
     let chunk_size = row_count.div_ceil(ncpus);
 
-    let keep_zero_time = args.flag_keep_zero_time;
+    let keep_zero_time = options.keep_zero_time;
+    let as_displayed = options.as_displayed;
+    let error_format = options.error_format;
     let formula_get_value_error = "cannot get formula".to_string();
 
     let mut processed_rows: Vec<Vec<csv::StringRecord>> = Vec::with_capacity(row_count);
@@ -1017,7 +1262,17 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             let mut cell_formula;
 
             for (row_idx, row) in chunk {
-                for cell in *row {
+                for (col, cell) in row.iter().enumerate() {
+                    if as_displayed {
+                        if let Some(displayed) = cell_formats
+                            .get((*row_idx, range_start.1 + col as u32))
+                            .and_then(|code| format_cell_value(cell, code))
+                        {
+                            record.push_field(&displayed);
+                            col_idx += 1;
+                            continue;
+                        }
+                    }
                     match *cell {
                         Data::Empty => record.push_field(""),
                         Data::String(ref s) => record.push_field(s),
@@ -1142,25 +1397,1654 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
 
     wtr.flush()?;
+    Ok(row_count.saturating_sub(header_rows))
+}
 
-    if !args.flag_quiet {
-        let msg = match export_mode {
-            ExportMode::Table => format!("Table: \"{}\"", args.flag_table.unwrap()),
-            ExportMode::Range => {
-                format!("Sheet: \"{sheet}\" Range:\"{}\"", args.flag_range.unwrap())
+/// Exports every worksheet (and with --include-tables, every table) of the workbook to
+/// <outdir>, reading the workbook only once. The file names are the safe names of the
+/// sheets & tables. A manifest.csv lists the exported files.
+fn export_all_sheets(
+    sheets: &mut Sheets<std::io::BufReader<std::fs::File>>,
+    outdir: &str,
+    include_tables: bool,
+    options: &CellOptions,
+    xlsx_formats: &mut Option<XlsxFormats>,
+    delimiter: Option<Delimiter>,
+    flexible: bool,
+    quiet: bool,
+) -> CliResult<()> {
+    let sheet_names = sheets.sheet_names();
+    if options.merged_cells {
+        load_merged_regions(sheets)?;
+    }
+    let sheet_types: Vec<SheetType> = sheets.sheets_metadata().iter().map(|s| s.typ).collect();
+
+    let table_names: Vec<String> = if include_tables {
+        if let Sheets::Xlsx(xlsx_wb) = sheets {
+            xlsx_wb.load_tables().map_err(Error::Xlsx)?;
+            xlsx_wb.table_names().into_iter().cloned().collect()
+        } else {
+            return fail_incorrectusage_clierror!("--include-tables is only valid for XLSX files");
+        }
+    } else {
+        vec![]
+    };
+
+    // the manifest comes first, so a sheet named "manifest" doesn't overwrite it
+    let mut export_names = csv::StringRecord::from(vec!["manifest"]);
+    for name in sheet_names.iter().chain(table_names.iter()) {
+        export_names.push_field(name);
+    }
+    let (safe_names, _) = util::safe_header_names(&export_names, false, false, None, "", false);
+
+    std::fs::create_dir_all(outdir)?;
+    let outdir = std::path::Path::new(outdir);
+    let mut manifest_wtr = Config::new(Some(
+        &outdir
+            .join(format!("{}.csv", safe_names[0]))
+            .to_string_lossy()
+            .to_string(),
+    ))
+    .delimiter(delimiter)
+    .writer()?;
+    manifest_wtr.write_record([
+        "sheet_name",
+        "table_name",
+        "file",
+        "row_count",
+        "column_count",
+    ])?;
+
+    let mut sheet_count = 0_usize;
+    for (i, sheet_name) in sheet_names.iter().enumerate() {
+        if sheet_types[i] != SheetType::WorkSheet {
+            info!("skipping \"{sheet_name}\", a {:?}", sheet_types[i]);
+            continue;
+        }
+        let mut range = match sheets.worksheet_range_at(i) {
+            Some(result) => result?,
+            None => Range::empty(),
+        };
+        if options.merged_cells {
+            fill_merged_cells(&mut range, &sheet_merged_regions(sheets, sheet_name));
+        }
+        let sheet_formulas = if options.error_format == ErrorFormat::Code || range.is_empty() {
+            Range::empty()
+        } else {
+            sheets.worksheet_formula(sheet_name)?
+        };
+
+        let file_name = format!("{}.csv", safe_names[i + 1]);
+        info!("exporting sheet ({sheet_name}) to {file_name}...");
+        let mut wtr = Config::new(Some(&outdir.join(&file_name).to_string_lossy().to_string()))
+            .flexible(flexible)
+            .delimiter(delimiter)
+            .writer()?;
+        let headers = range_headers(&range, options);
+        let cell_formats = match xlsx_formats {
+            Some(formats) if options.as_displayed => formats.sheet_formats(sheet_name)?,
+            _ => CellFormats::default(),
+        };
+        let row_count = write_range(
+            &range,
+            headers,
+            options.header_rows,
+            &sheet_formulas,
+            &cell_formats,
+            options,
+            &mut wtr,
+        )?;
+        manifest_wtr.write_record([
+            sheet_name.as_str(),
+            "",
+            file_name.as_str(),
+            row_count.to_string().as_str(),
+            range.width().to_string().as_str(),
+        ])?;
+        sheet_count += 1;
+    }
+
+    if let Sheets::Xlsx(xlsx_wb) = sheets {
+        for (i, table_name) in table_names.iter().enumerate() {
+            let table = xlsx_wb.table_by_name(table_name).map_err(Error::Xlsx)?;
+            let sheet_formulas = if options.error_format == ErrorFormat::Code {
+                Range::empty()
+            } else {
+                xlsx_wb
+                    .worksheet_formula(table.sheet_name())
+                    .map_err(Error::Xlsx)?
+            };
+
+            let file_name = format!("{}.csv", safe_names[sheet_names.len() + i + 1]);
+            info!("exporting table ({table_name}) to {file_name}...");
+            let mut wtr = Config::new(Some(&outdir.join(&file_name).to_string_lossy().to_string()))
+                .flexible(flexible)
+                .delimiter(delimiter)
+                .writer()?;
+            let headers = table.columns().to_vec();
+            let cell_formats = match xlsx_formats {
+                Some(formats) if options.as_displayed => {
+                    formats.sheet_formats(table.sheet_name())?
+                },
+                _ => CellFormats::default(),
+            };
+            let row_count = write_range(
+                table.data(),
+                headers,
+                1,
+                &sheet_formulas,
+                &cell_formats,
+                options,
+                &mut wtr,
+            )?;
+            manifest_wtr.write_record([
+                table.sheet_name(),
+                table_name.as_str(),
+                file_name.as_str(),
+                row_count.to_string().as_str(),
+                table.data().width().to_string().as_str(),
+            ])?;
+        }
+    }
+    manifest_wtr.flush()?;
+
+    if !quiet {
+        winfo!(
+            "{} sheet(s) and {} table(s) exported to {}",
+            HumanCount(sheet_count as u64),
+            HumanCount(table_names.len() as u64),
+            outdir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Loads the merged regions of the workbook. Only xlsx & xls workbooks have them -
+/// xls workbooks load them with the sheets.
+fn load_merged_regions(sheets: &mut Sheets<std::io::BufReader<std::fs::File>>) -> CliResult<()> {
+    match sheets {
+        Sheets::Xlsx(xlsx_wb) => xlsx_wb.load_merged_regions().map_err(Error::Xlsx)?,
+        Sheets::Xls(_) => {},
+        _ => wwarn!("--merged-cells is only supported for xlsx and xls files. Ignoring it."),
+    }
+    Ok(())
+}
+
+/// the merged regions of a sheet, after load_merged_regions
+fn sheet_merged_regions(
+    sheets: &Sheets<std::io::BufReader<std::fs::File>>,
+    sheet_name: &str,
+) -> Vec<Dimensions> {
+    match sheets {
+        Sheets::Xlsx(xlsx_wb) => xlsx_wb
+            .merged_regions_by_sheet(sheet_name)
+            .into_iter()
+            .map(|(_, _, dimensions)| *dimensions)
+            .collect(),
+        Sheets::Xls(xls_wb) => xls_wb.worksheet_merge_cells(sheet_name).unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+/// copies the value of the top-left cell of every merged region into the other cells
+/// of the region that are within the range
+fn fill_merged_cells(range: &mut Range<Data>, merged_regions: &[Dimensions]) {
+    let (Some(start), Some(end)) = (range.start(), range.end()) else {
+        return;
+    };
+    for region in merged_regions {
+        let Some(value) = range.get_value(region.start).cloned() else {
+            continue;
+        };
+        if value == Data::Empty {
+            continue;
+        }
+        for row in region.start.0.max(start.0)..=region.end.0.min(end.0) {
+            for col in region.start.1.max(start.1)..=region.end.1.min(end.1) {
+                if (row, col) != region.start {
+                    range.set_value((row, col), value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// The column names of a range - its first row, or with --header-rows, its first rows
+/// joined with --header-join. Empty parts & parts repeated from the row above
+/// (e.g. vertically merged cells) are skipped.
+fn range_headers(range: &Range<Data>, options: &CellOptions) -> Vec<String> {
+    if options.header_rows <= 1 {
+        return range.headers().unwrap_or_default();
+    }
+    let header_rows: Vec<&[Data]> = range.rows().take(options.header_rows).collect();
+    (0..range.width())
+        .map(|col| {
+            let mut parts: Vec<String> = Vec::with_capacity(header_rows.len());
+            for row in &header_rows {
+                let part = row.get(col).map(ToString::to_string).unwrap_or_default();
+                let part = part.trim();
+                if !part.is_empty() && parts.last().map(String::as_str) != Some(part) {
+                    parts.push(part.to_string());
+                }
+            }
+            parts.join(&options.header_join)
+        })
+        .collect()
+}
+
+/// The number format codes of the cells of an xlsx/xlsm workbook, for --as-displayed &
+/// --format-sidecar. calamine doesn't expose the cell styles, so they're read from the
+/// workbook's xml parts.
+#[cfg(feature = "excel_xml")]
+struct XlsxFormats {
+    archive:       zip::ZipArchive<std::io::BufReader<std::fs::File>>,
+    /// the number format code of every cell style (cellXfs), None for General
+    style_formats: Vec<Option<String>>,
+    /// the xml part of every sheet, by sheet name
+    sheet_parts:   HashMap<String, String>,
+}
+
+/// The number format codes of the cells of a sheet, by (row, column)
+#[derive(Default)]
+struct CellFormats {
+    codes: Vec<Option<String>>,
+    cells: HashMap<(u32, u32), usize>,
+}
+
+impl CellFormats {
+    fn get(&self, pos: (u32, u32)) -> Option<&str> {
+        self.cells
+            .get(&pos)
+            .and_then(|style| self.codes[*style].as_deref())
+    }
+}
+
+#[cfg(feature = "excel_xml")]
+impl XlsxFormats {
+    fn open(path: &Path) -> CliResult<Self> {
+        let mut archive =
+            zip::ZipArchive::new(std::io::BufReader::new(std::fs::File::open(path)?))?;
+
+        let sheet_parts = workbook_sheet_parts(&mut archive)?.into_iter().collect();
+
+        let mut num_fmts: HashMap<u32, String> = HashMap::new();
+        let mut xf_num_fmt_ids: Vec<u32> = Vec::new();
+        let mut in_cell_xfs = false;
+        read_xml_part(&mut archive, "xl/styles.xml", |event| match event {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"numFmt" => {
+                    if let (Some(id), Some(code)) = (
+                        xml_attr(e, b"numFmtId").and_then(|id| id.parse().ok()),
+                        xml_attr(e, b"formatCode"),
+                    ) {
+                        num_fmts.insert(id, code);
+                    }
+                },
+                b"cellXfs" => in_cell_xfs = matches!(event, Event::Start(_)),
+                b"xf" if in_cell_xfs => xf_num_fmt_ids.push(
+                    xml_attr(e, b"numFmtId")
+                        .and_then(|id| id.parse().ok())
+                        .unwrap_or_default(),
+                ),
+                _ => {},
             },
-            ExportMode::Sheet => format!("\"{sheet}\" sheet"),
-            ExportMode::NothingToExport => String::new(),
+            Event::End(e) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            _ => {},
+        })?;
+        let style_formats = xf_num_fmt_ids
+            .iter()
+            .map(|id| {
+                num_fmts
+                    .get(id)
+                    .cloned()
+                    .or_else(|| builtin_number_format(*id).map(str::to_string))
+                    .filter(|code| !code.eq_ignore_ascii_case("general"))
+            })
+            .collect();
+
+        Ok(Self {
+            archive,
+            style_formats,
+            sheet_parts,
+        })
+    }
+
+    /// the number formats of the cells of a sheet. Cells with the General format are skipped.
+    fn sheet_formats(&mut self, sheet_name: &str) -> CliResult<CellFormats> {
+        let Some(part) = self.sheet_parts.get(sheet_name).cloned() else {
+            return Ok(CellFormats::default());
         };
+        let style_formats = &self.style_formats;
+        let mut cells = HashMap::new();
+        read_xml_part(&mut self.archive, &part, |event| {
+            if let Event::Start(e) | Event::Empty(e) = event {
+                if e.local_name().as_ref() == b"c" {
+                    if let (Some(pos), Some(style)) = (
+                        xml_attr(e, b"r").and_then(|r| cell_position(&r)),
+                        xml_attr(e, b"s").and_then(|s| s.parse::<usize>().ok()),
+                    ) {
+                        if style_formats.get(style).is_some_and(Option::is_some) {
+                            cells.insert(pos, style);
+                        }
+                    }
+                }
+            }
+        })?;
+        Ok(CellFormats {
+            codes: self.style_formats.clone(),
+            cells,
+        })
+    }
+}
+
+/// Without the excel_xml feature, the number formats of the cells can't be read.
+#[cfg(not(feature = "excel_xml"))]
+struct XlsxFormats;
+
+#[cfg(not(feature = "excel_xml"))]
+impl XlsxFormats {
+    fn open(_path: &Path) -> CliResult<Self> {
+        fail_incorrectusage_clierror!(
+            "--as-displayed and --format-sidecar require qsv to be built with the \"excel_xml\" \
+             feature."
+        )
+    }
+
+    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    fn sheet_formats(&mut self, _sheet_name: &str) -> CliResult<CellFormats> {
+        Ok(CellFormats::default())
+    }
+}
+
+/// the name & xml part of every sheet of an xlsx workbook, in workbook order
+#[cfg(feature = "excel_xml")]
+fn workbook_sheet_parts(
+    archive: &mut zip::ZipArchive<std::io::BufReader<std::fs::File>>,
+) -> CliResult<Vec<(String, String)>> {
+    let mut sheet_rids: Vec<(String, String)> = Vec::new();
+    read_xml_part(archive, "xl/workbook.xml", |event| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if e.local_name().as_ref() == b"sheet" {
+                // the relationship id is the r:id attribute
+                if let (Some(name), Some(rid)) = (xml_attr(e, b"name"), xml_attr(e, b"id")) {
+                    sheet_rids.push((name, rid));
+                }
+            }
+        }
+    })?;
+
+    let mut targets: HashMap<String, String> = HashMap::new();
+    read_xml_part(archive, "xl/_rels/workbook.xml.rels", |event| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if e.local_name().as_ref() == b"Relationship" {
+                if let (Some(id), Some(target)) = (xml_attr(e, b"Id"), xml_attr(e, b"Target")) {
+                    // targets are relative to xl/, unless they're absolute
+                    let part = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{target}"),
+                    };
+                    targets.insert(id, part);
+                }
+            }
+        }
+    })?;
+    Ok(sheet_rids
+        .into_iter()
+        .filter_map(|(name, rid)| targets.remove(&rid).map(|part| (name, part)))
+        .collect())
+}
+
+/// calls `f` with every event of an xml part of the workbook. Missing parts are skipped.
+#[cfg(feature = "excel_xml")]
+fn read_xml_part(
+    archive: &mut zip::ZipArchive<std::io::BufReader<std::fs::File>>,
+    name: &str,
+    mut f: impl FnMut(&Event),
+) -> CliResult<()> {
+    let Ok(part) = archive.by_name(name) else {
+        return Ok(());
+    };
+    let mut reader = quick_xml::Reader::from_reader(std::io::BufReader::new(part));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            event => f(&event),
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// the value of the attribute of an xml element with the local name `name`
+#[cfg(feature = "excel_xml")]
+fn xml_attr(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.to_string()))
+}
+
+/// the zero-based (row, column) of an A1-style cell reference
+#[cfg(feature = "excel_xml")]
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let digits_start = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(digits_start);
+    if letters.is_empty() {
+        return None;
+    }
+    let col = letters.bytes().try_fold(0_u32, |col, b| {
+        b.is_ascii_alphabetic()
+            .then(|| col * 26 + u32::from(b.to_ascii_uppercase() - b'A' + 1))
+    })?;
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+/// the format codes of the built-in number formats, as displayed in the en-US locale
+#[cfg(feature = "excel_xml")]
+const fn builtin_number_format(id: u32) -> Option<&'static str> {
+    Some(match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        5 => "\"$\"#,##0_);(\"$\"#,##0)",
+        6 => "\"$\"#,##0_);[Red](\"$\"#,##0)",
+        7 => "\"$\"#,##0.00_);(\"$\"#,##0.00)",
+        8 => "\"$\"#,##0.00_);[Red](\"$\"#,##0.00)",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "m/d/yyyy",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yyyy h:mm",
+        37 => "#,##0_);(#,##0)",
+        38 => "#,##0_);[Red](#,##0)",
+        39 => "#,##0.00_);(#,##0.00)",
+        40 => "#,##0.00_);[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mm:ss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    })
+}
+
+/// a token of a section of an Excel number format code
+#[derive(Debug, PartialEq)]
+enum FormatToken {
+    Literal(String),
+    /// a digit placeholder - 0, # or ?
+    Digit(char),
+    Point,
+    Comma,
+    Percent,
+    /// a date/time part - y, m, d, h or s, and how many times it's repeated
+    DatePart(char, usize),
+    /// elapsed time - [h], [m] or [s]
+    Elapsed(char),
+    /// AM/PM, or A/P if short
+    AmPm {
+        short: bool,
+    },
+    General,
+    Text,
+    Exponent,
+    Fraction,
+    Condition,
+}
+
+/// splits a number format code into its ; separated sections -
+/// positive;negative;zero;text
+fn split_format_sections(code: &str) -> Vec<&str> {
+    let mut sections = Vec::with_capacity(4);
+    let (mut in_quotes, mut in_brackets, mut escaped) = (false, false, false);
+    let mut start = 0;
+    for (i, c) in code.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if !in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => in_brackets = true,
+            ']' if !in_quotes => in_brackets = false,
+            ';' if !in_quotes && !in_brackets => {
+                sections.push(&code[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    sections.push(&code[start..]);
+    sections
+}
+
+fn tokenize_format_section(section: &str) -> Vec<FormatToken> {
+    use FormatToken::{
+        AmPm, Comma, Condition, DatePart, Digit, Elapsed, Exponent, Fraction, General, Literal,
+        Percent, Point, Text,
+    };
+
+    let chars: Vec<char> = section.chars().collect();
+    let starts_with = |i: usize, s: &str| {
+        chars.len() >= i + s.len()
+            && chars[i..i + s.len()]
+                .iter()
+                .zip(s.chars())
+                .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+    };
+    let closing = |i: usize, delimiter: char| {
+        chars[i + 1..]
+            .iter()
+            .position(|&c| c == delimiter)
+            .map_or(chars.len(), |p| i + 1 + p)
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let mut next = i + 1;
+        match c {
+            '"' => {
+                let end = closing(i, '"');
+                tokens.push(Literal(chars[i + 1..end].iter().collect()));
+                next = end + 1;
+            },
+            '\\' => {
+                if let Some(escaped) = chars.get(i + 1) {
+                    tokens.push(Literal(escaped.to_string()));
+                }
+                next = i + 2;
+            },
+            // padding (_) & repeated fill (*) characters are not shown
+            '_' | '*' => next = i + 2,
+            '[' => {
+                let end = closing(i, ']');
+                let content: String = chars[i + 1..end].iter().collect();
+                let lower = content.to_ascii_lowercase();
+                if let Some(currency) = content.strip_prefix('$') {
+                    // a currency symbol with an optional locale, e.g. [$€-407]
+                    tokens.push(Literal(
+                        currency.split('-').next().unwrap_or_default().to_string(),
+                    ));
+                } else if let Some(unit @ ('h' | 'm' | 's')) = lower.chars().next() {
+                    if lower.chars().all(|c| c == unit) {
+                        tokens.push(Elapsed(unit));
+                    }
+                } else if content.starts_with(['<', '>', '=']) {
+                    tokens.push(Condition);
+                }
+                // colors, e.g. [Red], are not shown
+                next = end + 1;
+            },
+            '0' | '#' | '?' => tokens.push(Digit(c)),
+            '.' => tokens.push(Point),
+            ',' => tokens.push(Comma),
+            '%' => tokens.push(Percent),
+            '@' => tokens.push(Text),
+            'E' | 'e' if matches!(chars.get(i + 1), Some('+' | '-')) => {
+                tokens.push(Exponent);
+                next = i + 2;
+            },
+            '/' if tokens.iter().any(|t| matches!(t, Digit(_))) => tokens.push(Fraction),
+            'G' | 'g' if starts_with(i, "general") => {
+                tokens.push(General);
+                next = i + 7;
+            },
+            'A' | 'a' if starts_with(i, "am/pm") => {
+                tokens.push(AmPm { short: false });
+                next = i + 5;
+            },
+            'A' | 'a' if starts_with(i, "a/p") => {
+                tokens.push(AmPm { short: true });
+                next = i + 3;
+            },
+            'y' | 'Y' | 'm' | 'M' | 'd' | 'D' | 'h' | 'H' | 's' | 'S' => {
+                let part = c.to_ascii_lowercase();
+                while chars
+                    .get(next)
+                    .is_some_and(|c| c.to_ascii_lowercase() == part)
+                {
+                    next += 1;
+                }
+                tokens.push(DatePart(part, next - i));
+            },
+            _ => tokens.push(Literal(c.to_string())),
+        }
+        i = next;
+    }
+    tokens
+}
+
+/// Formats a cell value as Excel displays it with the number format `code`.
+/// Returns None for cells that aren't numbers or dates, and for unsupported formats
+/// (scientific notation, fractions & conditional formats), so they're formatted as usual.
+fn format_cell_value(cell: &Data, code: &str) -> Option<String> {
+    #[allow(clippy::cast_precision_loss)]
+    let (value, datetime) = match cell {
+        Data::Float(f) => (*f, None),
+        Data::Int(i) => (*i as f64, None),
+        Data::DateTime(edt) => (edt.as_f64(), edt.as_datetime()),
+        _ => return None,
+    };
+
+    let sections = split_format_sections(code);
+    let (section, value, negative) = if value < 0.0 && sections.len() > 1 {
+        (sections[1], -value, false)
+    } else if value.abs() < f64::EPSILON && sections.len() > 2 {
+        (sections[2], value, false)
+    } else {
+        (sections[0], value.abs(), value < 0.0)
+    };
+
+    let tokens = tokenize_format_section(section);
+    if tokens.iter().any(|t| {
+        matches!(
+            t,
+            FormatToken::Exponent
+                | FormatToken::Fraction
+                | FormatToken::Condition
+                | FormatToken::Text
+        )
+    }) {
+        return None;
+    }
+    if tokens
+        .iter()
+        .any(|t| matches!(t, FormatToken::DatePart(..) | FormatToken::Elapsed(_)))
+    {
+        return format_datetime_value(&tokens, value, datetime);
+    }
+    Some(format_number_value(&tokens, value, negative))
+}
+
+fn format_number_value(tokens: &[FormatToken], mut value: f64, negative: bool) -> String {
+    use FormatToken::{Comma, Digit, General, Literal, Percent, Point};
+
+    let point = tokens
+        .iter()
+        .position(|t| *t == Point)
+        .unwrap_or(tokens.len());
+    let int_digits: Vec<usize> = (0..point)
+        .filter(|&i| matches!(tokens[i], Digit(_)))
+        .collect();
+    let decimal_placeholders: Vec<char> = tokens[point..]
+        .iter()
+        .filter_map(|t| if let Digit(c) = t { Some(*c) } else { None })
+        .collect();
+
+    // commas between integer digit placeholders group thousands. Commas right after
+    // the digit placeholders scale the value by 1,000
+    let mut grouping = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if *token != Comma {
+            continue;
+        }
+        if i < point && int_digits.last().is_some_and(|&last| i < last) {
+            grouping = true;
+        } else if i > 0 && matches!(tokens[i - 1], Digit(_) | Comma) {
+            value /= 1000.0;
+        }
+    }
+    for token in tokens {
+        if *token == Percent {
+            value *= 100.0;
+        }
+    }
+
+    // round half away from zero, as Excel does
+    let decimals = decimal_placeholders.len();
+    let scale = 10_f64.powi(decimals as i32);
+    let rounded = format!("{:.decimals$}", (value * scale).round() / scale);
+    let (int_part, frac_part) = rounded.split_once('.').unwrap_or((&rounded, ""));
+
+    let min_int = int_digits
+        .iter()
+        .filter(|&&i| tokens[i] == Digit('0'))
+        .count();
+    let mut int_str = if int_part == "0" && min_int == 0 {
+        String::new()
+    } else {
+        format!("{int_part:0>min_int$}")
+    };
+    let min_decimals = decimal_placeholders
+        .iter()
+        .rposition(|&c| c == '0')
+        .map_or(0, |p| p + 1);
+    let mut frac_str = frac_part.to_string();
+    while frac_str.len() > min_decimals && frac_str.ends_with('0') {
+        frac_str.pop();
+    }
+
+    let int_region = int_digits.first().zip(int_digits.last());
+    let interleaved = int_region.is_some_and(|(&first, &last)| {
+        tokens[first..=last].iter().any(|t| matches!(t, Literal(_)))
+    });
+    if let (true, Some((&first, &last))) = (interleaved, int_region) {
+        // fill the digit placeholders from the right, keeping the literals between them
+        // e.g. 000-00-0000. Digits left over go before the first placeholder
+        let mut digits: Vec<char> = int_str.chars().collect();
+        let mut reversed = String::new();
+        for token in tokens[first..=last].iter().rev() {
+            match token {
+                Digit(_) => {
+                    if let Some(digit) = digits.pop() {
+                        reversed.push(digit);
+                    }
+                },
+                Literal(s) => reversed.extend(s.chars().rev()),
+                _ => {},
+            }
+        }
+        reversed.extend(digits.iter().rev());
+        int_str = reversed.chars().rev().collect();
+    } else if grouping {
+        let len = int_str.len();
+        int_str = int_str.chars().enumerate().fold(
+            String::with_capacity(len + len / 3),
+            |mut grouped, (i, c)| {
+                if i > 0 && (len - i) % 3 == 0 {
+                    grouped.push(',');
+                }
+                grouped.push(c);
+                grouped
+            },
+        );
+    }
+
+    let mut displayed = String::new();
+    if negative {
+        displayed.push('-');
+    }
+    let mut frac_written = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some((&first, &last)) = int_region {
+            if i == first {
+                displayed.push_str(&int_str);
+            }
+            if (first..=last).contains(&i) {
+                continue;
+            }
+        }
+        match token {
+            Digit(_) if i > point && !frac_written => {
+                displayed.push_str(&frac_str);
+                frac_written = true;
+            },
+            Point => displayed.push('.'),
+            Percent => displayed.push('%'),
+            Literal(s) => displayed.push_str(s),
+            General =>
+            {
+                #[allow(clippy::cast_precision_loss)]
+                if value.fract().abs() > f64::EPSILON || value > i64::MAX as f64 {
+                    displayed.push_str(ryu::Buffer::new().format_finite(value));
+                } else {
+                    displayed.push_str(itoa::Buffer::new().format(value as i64));
+                }
+            },
+            _ => {},
+        }
+    }
+    displayed
+}
+
+fn format_datetime_value(
+    tokens: &[FormatToken],
+    serial: f64,
+    datetime: Option<NaiveDateTime>,
+) -> Option<String> {
+    use FormatToken::{AmPm, Comma, DatePart, Digit, Elapsed, Literal, Percent, Point};
+
+    // dates that weren't detected by calamine are Excel serial dates - days since 1899-12-30
+    #[allow(clippy::cast_possible_truncation)]
+    let datetime = datetime.or_else(|| {
+        let base = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+        base.checked_add_signed(chrono::Duration::milliseconds(
+            (serial * 86_400_000.0).round() as i64,
+        ))
+    })?;
+    let frac_digits = tokens
+        .iter()
+        .skip_while(|t| **t != Point)
+        .filter(|t| matches!(t, Digit(_)))
+        .count()
+        .min(3);
+    // without fractional seconds, seconds are rounded
+    let datetime = if frac_digits == 0 {
+        (datetime + chrono::Duration::milliseconds(500)).with_nanosecond(0)?
+    } else {
+        datetime
+    };
+    let twelve_hour = tokens.iter().any(|t| matches!(t, AmPm { .. }));
+
+    // m is minutes if it follows an hour or precedes seconds, otherwise it's months
+    let time_parts: Vec<(usize, char)> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            DatePart(part, _) | Elapsed(part) => Some((i, *part)),
+            _ => None,
+        })
+        .collect();
+    let is_minute = |i: usize| {
+        let pos = time_parts.iter().position(|(j, _)| *j == i).unwrap_or(0);
+        (pos > 0 && time_parts[pos - 1].1 == 'h')
+            || time_parts
+                .get(pos + 1)
+                .is_some_and(|(_, part)| *part == 's')
+    };
+
+    let mut displayed = String::new();
+    let mut in_fraction = false;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            DatePart('y', n) if *n <= 2 => {
+                write!(displayed, "{:02}", datetime.year() % 100).ok()?;
+            },
+            DatePart('y', _) => write!(displayed, "{:04}", datetime.year()).ok()?,
+            DatePart('m', n) if is_minute(i) => {
+                write!(displayed, "{:0n$}", datetime.minute(), n = (*n).min(2)).ok()?;
+            },
+            DatePart('m', n) => match n {
+                1 | 2 => write!(displayed, "{:0n$}", datetime.month(), n = *n).ok()?,
+                3 => write!(displayed, "{}", datetime.format("%b")).ok()?,
+                4 => write!(displayed, "{}", datetime.format("%B")).ok()?,
+                _ => write!(displayed, "{:.1}", datetime.format("%B").to_string()).ok()?,
+            },
+            DatePart('d', n) => match n {
+                1 | 2 => write!(displayed, "{:0n$}", datetime.day(), n = *n).ok()?,
+                3 => write!(displayed, "{}", datetime.format("%a")).ok()?,
+                _ => write!(displayed, "{}", datetime.format("%A")).ok()?,
+            },
+            DatePart('h', n) => {
+                let hour = if twelve_hour {
+                    datetime.hour12().1
+                } else {
+                    datetime.hour()
+                };
+                write!(displayed, "{hour:0n$}", n = (*n).min(2)).ok()?;
+            },
+            DatePart('s', n) => {
+                write!(displayed, "{:0n$}", datetime.second(), n = (*n).min(2)).ok()?;
+            },
+            DatePart(..) => {},
+            #[allow(clippy::cast_possible_truncation)]
+            Elapsed(unit) => {
+                let elapsed = match unit {
+                    'h' => (serial * 24.0).floor(),
+                    'm' => (serial * 1_440.0).floor(),
+                    _ => (serial * 86_400.0).round(),
+                };
+                write!(displayed, "{}", elapsed as i64).ok()?;
+            },
+            AmPm { short } => {
+                let pm = datetime.hour12().0;
+                displayed.push_str(match (pm, short) {
+                    (false, false) => "AM",
+                    (true, false) => "PM",
+                    (false, true) => "A",
+                    (true, true) => "P",
+                });
+            },
+            Point if frac_digits > 0 => {
+                displayed.push('.');
+                let millis = datetime.nanosecond() / 1_000_000;
+                write!(displayed, "{:.frac_digits$}", format!("{millis:03}")).ok()?;
+                in_fraction = true;
+            },
+            Digit(_) if in_fraction => {},
+            Point => displayed.push('.'),
+            Comma => displayed.push(','),
+            Percent => displayed.push('%'),
+            Literal(s) => displayed.push_str(s),
+            _ => {},
+        }
+    }
+    Some(displayed)
+}
+
+/// the kind of a number format, for --format-sidecar
+fn number_format_type(code: &str) -> &'static str {
+    use FormatToken::{
+        AmPm, DatePart, Digit, Elapsed, Exponent, Fraction, General, Literal, Percent, Text,
+    };
+
+    let tokens = tokenize_format_section(split_format_sections(code)[0]);
+    let has = |f: &dyn Fn(&FormatToken) -> bool| tokens.iter().any(f);
+
+    let has_time = has(&|t| matches!(t, DatePart('h' | 's', _) | Elapsed(_) | AmPm { .. }));
+    let has_date = has(&|t| matches!(t, DatePart('y' | 'd', _)))
+        || (!has_time && has(&|t| matches!(t, DatePart('m', _))));
+    match (has_date, has_time) {
+        (true, true) => return "datetime",
+        (true, false) => return "date",
+        (false, true) => return "time",
+        _ => {},
+    }
+    if has(&|t| *t == Exponent) {
+        "scientific"
+    } else if has(&|t| *t == Fraction) {
+        "fraction"
+    } else if has(&|t| *t == Percent) {
+        "percent"
+    } else if has(
+        &|t| matches!(t, Literal(s) if s.contains(['$', '€', '£', '¥', '₹', '₩', '₽', '¢', '₺', '₪', '₫', '฿'])),
+    ) {
+        "currency"
+    } else if has(&|t| matches!(t, Digit(_))) {
+        "number"
+    } else if has(&|t| *t == Text) {
+        "text"
+    } else if has(&|t| *t == General) || tokens.is_empty() {
+        "general"
+    } else {
+        "text"
+    }
+}
+
+/// Writes the detected number format of every column to a CSV sidecar file - the format of
+/// most of its non-empty data cells.
+fn write_format_sidecar(
+    sidecar: &str,
+    range: &Range<Data>,
+    headers: &[String],
+    header_rows: usize,
+    cell_formats: &CellFormats,
+) -> CliResult<()> {
+    let range_start = range.start().unwrap_or((0, 0));
+    let mut format_counts: Vec<HashMap<&str, usize>> = vec![HashMap::new(); headers.len()];
+    for (row_idx, row) in range.rows().enumerate().skip(header_rows) {
+        for (col_idx, cell) in row.iter().enumerate().take(headers.len()) {
+            if *cell == Data::Empty {
+                continue;
+            }
+            let code = cell_formats
+                .get((
+                    range_start.0 + row_idx as u32,
+                    range_start.1 + col_idx as u32,
+                ))
+                .unwrap_or("General");
+            *format_counts[col_idx].entry(code).or_default() += 1;
+        }
+    }
+
+    let mut wtr = csv::Writer::from_path(sidecar)?;
+    wtr.write_record(["column", "number_format", "format_type"])?;
+    for (header, counts) in headers.iter().zip(format_counts) {
+        // ties go to the first format code in alphabetical order
+        let code = counts
+            .into_iter()
+            .max_by(|(code_a, count_a), (code_b, count_b)| {
+                count_a.cmp(count_b).then(code_b.cmp(code_a))
+            })
+            .map_or("General", |(code, _)| code);
+        wtr.write_record([header.as_str(), code, number_format_type(code)])?;
+    }
+    Ok(wtr.flush()?)
+}
+
+/// Writes a CSV into a sheet of an existing xlsx/xlsm workbook (--write).
+/// Only the xml of the sheet is rewritten - other sheets, styles, formulas, charts, etc.
+/// are copied as is.
+#[cfg(feature = "excel_xml")]
+fn write_into_workbook(path: &Path, format: &str, args: &Args) -> CliResult<()> {
+    if format != "xlsx" && format != "xlsm" {
+        return fail_incorrectusage_clierror!("--write is only supported for xlsx and xlsm files.");
+    }
+    if args.arg_input == "-" && args.flag_output.is_none() {
+        return fail_incorrectusage_clierror!(
+            "--output is required to --write to a workbook read from stdin."
+        );
+    }
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(std::fs::File::open(path)?))?;
+    let sheet_parts = workbook_sheet_parts(&mut archive)?;
+    if sheet_parts.is_empty() {
+        return fail!("No sheets found.");
+    }
+
+    // the target sheet & cells - from --range if it has a sheet, otherwise from --sheet
+    let (range_sheet, start, end) = match args.flag_range {
+        Some(ref requested_range) => {
+            parse_write_range(requested_range, &workbook_defined_names(&mut archive)?)?
+        },
+        None => (None, (0, 0), None),
+    };
+    let requested_sheet = range_sheet.as_deref().unwrap_or(&args.flag_sheet);
+    let Some((sheet_name, sheet_part)) =
+        find_write_sheet(&sheet_parts, requested_sheet, range_sheet.is_some())
+    else {
+        return fail_clierror!(
+            "Sheet \"{requested_sheet}\" not found in available sheets: {:?}.",
+            sheet_parts.iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+    };
+    if !sheet_part.contains("worksheets/") {
+        return fail_incorrectusage_clierror!("Can only write to Worksheets.");
+    }
+
+    let mut rdr = Config::new(args.flag_write.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(true)
+        .flexible(true)
+        .reader()?;
+    let mut records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    if (args.flag_data_only || args.flag_append) && !records.is_empty() {
+        records.remove(0);
+    }
+
+    let mut sheet_xml = Vec::new();
+    archive.by_name(sheet_part)?.read_to_end(&mut sheet_xml)?;
+
+    let (start, append_styles) = if args.flag_append {
+        let (last_row, last_row_styles) = last_used_row(&sheet_xml)?;
+        (
+            (last_row.map_or(start.0, |row| row + 1), start.1),
+            last_row_styles,
+        )
+    } else {
+        (start, HashMap::new())
+    };
+    if let (Some(end), false) = (end, args.flag_append) {
+        let col_count = records
+            .iter()
+            .map(csv::StringRecord::len)
+            .max()
+            .unwrap_or(0);
+        if start.0 as usize + records.len() > end.0 as usize + 1
+            || start.1 as usize + col_count > end.1 as usize + 1
+        {
+            return fail_clierror!(
+                "The CSV ({} rows, {col_count} columns) doesn't fit in range \"{}\".",
+                records.len(),
+                args.flag_range.as_deref().unwrap_or_default()
+            );
+        }
+    }
+    let sheet_xml = write_sheet_cells(&sheet_xml, &records, start, &append_styles)?;
+
+    // write the updated workbook to a temp file in the destination directory first,
+    // so the input workbook is only replaced if everything went well
+    let output = args.flag_output.as_ref().map_or(path, Path::new);
+    let output_dir = match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let tmpfile = tempfile::NamedTempFile::new_in(output_dir)?;
+    let mut zip_wtr = zip::ZipWriter::new(std::io::BufWriter::new(tmpfile.as_file()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut part_xml = Vec::new();
+    for i in 0..archive.len() {
+        let name = archive.by_index_raw(i)?.name().to_string();
+        let updated_xml: Option<Cow<[u8]>> = match name.as_str() {
+            part if part == sheet_part => Some(Cow::Borrowed(&sheet_xml)),
+            // the calculation chain is rebuilt by Excel. It's removed as the written cells
+            // may have replaced formulas
+            "xl/calcChain.xml" => continue,
+            "xl/workbook.xml" | "xl/_rels/workbook.xml.rels" | "[Content_Types].xml" => {
+                part_xml.clear();
+                archive.by_index(i)?.read_to_end(&mut part_xml)?;
+                Some(Cow::Owned(if name == "xl/workbook.xml" {
+                    recalculate_on_load(&part_xml)?
+                } else {
+                    remove_calc_chain(&part_xml)?
+                }))
+            },
+            _ => None,
+        };
+        if let Some(xml) = updated_xml {
+            zip_wtr.start_file(name, options)?;
+            zip_wtr.write_all(&xml)?;
+        } else {
+            zip_wtr.raw_copy_file(archive.by_index_raw(i)?)?;
+        }
+    }
+    zip_wtr.finish()?.flush()?;
+    // temp files are only readable by their owner, so keep the permissions of the
+    // workbook being replaced, or of the input workbook for a new --output
+    let permissions = std::fs::metadata(output)
+        .or_else(|_| std::fs::metadata(path))?
+        .permissions();
+    tmpfile.as_file().set_permissions(permissions)?;
+    tmpfile.persist(output).map_err(|e| e.error)?;
+
+    if !args.flag_quiet {
         winfo!(
-            "{}",
-            format!(
-                "{} {}-column rows exported from {msg}",
-                HumanCount(row_count.saturating_sub(1) as u64),
-                HumanCount(col_count as u64),
-            )
+            "{} rows written to \"{sheet_name}\" sheet at {}, in {}",
+            HumanCount(records.len() as u64),
+            cell_reference(start),
+            output.display()
         );
     }
+    Ok(())
+}
+
+#[cfg(not(feature = "excel_xml"))]
+fn write_into_workbook(_path: &Path, _format: &str, _args: &Args) -> CliResult<()> {
+    fail_incorrectusage_clierror!(
+        "--write requires qsv to be built with the \"excel_xml\" feature."
+    )
+}
+
+/// the sheet to write to, by name (case-insensitive) or, unless `by_name_only`,
+/// by zero-based index. Negative indices start from the end.
+#[cfg(feature = "excel_xml")]
+fn find_write_sheet<'a>(
+    sheet_parts: &'a [(String, String)],
+    sheet: &str,
+    by_name_only: bool,
+) -> Option<(&'a str, &'a str)> {
+    let lower_sheet = sheet.to_lowercase();
+    let by_name = sheet_parts
+        .iter()
+        .find(|(name, _)| name.to_lowercase() == lower_sheet);
+    let found = if by_name.is_some() || by_name_only {
+        by_name
+    } else {
+        let index = atoi_simd::parse::<i32>(sheet.as_bytes()).ok()?;
+        let index = if index < 0 {
+            sheet_parts
+                .len()
+                .checked_sub(index.unsigned_abs() as usize)?
+        } else {
+            index as usize
+        };
+        sheet_parts.get(index)
+    };
+    found.map(|(name, part)| (name.as_str(), part.as_str()))
+}
 
+/// the defined names of an xlsx workbook, with the range they refer to
+#[cfg(feature = "excel_xml")]
+fn workbook_defined_names(
+    archive: &mut zip::ZipArchive<std::io::BufReader<std::fs::File>>,
+) -> CliResult<Vec<(String, String)>> {
+    let mut defined_names = Vec::new();
+    let mut current_name: Option<String> = None;
+    let mut refers_to = String::new();
+    read_xml_part(archive, "xl/workbook.xml", |event| match event {
+        Event::Start(e) if e.local_name().as_ref() == b"definedName" => {
+            current_name = xml_attr(e, b"name");
+            refers_to.clear();
+        },
+        Event::Text(text) if current_name.is_some() => {
+            if let Ok(text) = text.unescape() {
+                refers_to.push_str(&text);
+            }
+        },
+        Event::End(e) if e.local_name().as_ref() == b"definedName" => {
+            if let Some(name) = current_name.take() {
+                defined_names.push((name, refers_to.clone()));
+            }
+        },
+        _ => {},
+    })?;
+    Ok(defined_names)
+}
+
+/// parses the --range to write to - a cell (C3), a range (C3:T25), either of them in
+/// a named sheet ('Sheet 2'!$C$3) or a defined name.
+/// Returns the sheet name, if any, the zero-based top-left cell and the bottom-right cell.
+#[cfg(feature = "excel_xml")]
+#[allow(clippy::type_complexity)]
+fn parse_write_range(
+    requested_range: &str,
+    defined_names: &[(String, String)],
+) -> CliResult<(Option<String>, (u32, u32), Option<(u32, u32)>)> {
+    let lower_range = requested_range.to_lowercase();
+    let range = defined_names
+        .iter()
+        .find(|(name, _)| name.to_lowercase() == lower_range)
+        .map_or(requested_range, |(_, refers_to)| refers_to.as_str());
+
+    let (sheet, cells) = match range.rsplit_once('!') {
+        Some((sheet, cells)) => {
+            let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                Some(quoted) => quoted.replace("''", "'"),
+                None => sheet.to_string(),
+            };
+            (Some(sheet), cells)
+        },
+        None => (None, range),
+    };
+    let cells = cells.replace('$', "");
+    let (start, end) = match cells.split_once(':') {
+        Some((start, end)) => (cell_position(start), cell_position(end).map(Some)),
+        None => (cell_position(&cells), Some(None)),
+    };
+    let (Some(start), Some(end)) = (start, end) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid range \"{requested_range}\". --write requires a cell (e.g. C3), a range \
+             (e.g. C3:T25) or a defined name."
+        );
+    };
+    Ok((sheet, start, end))
+}
+
+/// the A1-style reference of a zero-based (row, column) cell position
+#[cfg(feature = "excel_xml")]
+fn cell_reference((row, col): (u32, u32)) -> String {
+    let mut letters = Vec::new();
+    let mut col = col + 1;
+    while col > 0 {
+        letters.push(b'A' + ((col - 1) % 26) as u8);
+        col = (col - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row + 1)
+}
+
+/// the zero-based last row of a sheet with a cell with a value or formula, and the
+/// styles of its cells by column, for --append. Rows that are only formatted are ignored.
+#[cfg(feature = "excel_xml")]
+fn last_used_row(sheet_xml: &[u8]) -> CliResult<(Option<u32>, HashMap<u32, String>)> {
+    let mut reader = quick_xml::Reader::from_reader(sheet_xml);
+    let mut buf = Vec::new();
+    let mut last_used: (Option<u32>, HashMap<u32, String>) = (None, HashMap::new());
+    let (mut row, mut col) = (u32::MAX, u32::MAX);
+    let mut row_styles: HashMap<u32, String> = HashMap::new();
+    let mut row_used = false;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"row" => {
+                    row = row_position(&e, row);
+                    col = u32::MAX;
+                    row_styles.clear();
+                    row_used = false;
+                },
+                b"c" => {
+                    col = column_position(&e, col);
+                    if let Some(style) = xml_attr(&e, b"s") {
+                        row_styles.insert(col, style);
+                    }
+                },
+                b"v" | b"f" | b"is" => row_used = true,
+                _ => {},
+            },
+            Event::End(e) if e.local_name().as_ref() == b"row" && row_used => {
+                last_used = (Some(row), row_styles.clone());
+            },
+            _ => {},
+        }
+        buf.clear();
+    }
+    Ok(last_used)
+}
+
+/// Rewrites the sheetData of a sheet, writing the records to the cells starting at `start`.
+/// The styles of the overwritten cells are kept. New cells get the style of their column in
+/// `styles`, if any. The sheet's dimension is updated to include the written cells.
+#[cfg(feature = "excel_xml")]
+fn write_sheet_cells(
+    sheet_xml: &[u8],
+    records: &[csv::StringRecord],
+    start: (u32, u32),
+    styles: &HashMap<u32, String>,
+) -> CliResult<Vec<u8>> {
+    let mut reader = quick_xml::Reader::from_reader(sheet_xml);
+    let mut wtr = quick_xml::Writer::new(Vec::with_capacity(sheet_xml.len()));
+    let mut buf = Vec::new();
+
+    let last_row = start.0 + records.len().saturating_sub(1) as u32;
+    let last_col = start.1
+        + records
+            .iter()
+            .map(csv::StringRecord::len)
+            .max()
+            .unwrap_or(1)
+            .saturating_sub(1) as u32;
+    // the next record to write
+    let mut next_record = 0_usize;
+    let mut row = u32::MAX;
+    // the cells of the row being rewritten - by column, the cell's events and style
+    let mut row_cells: Option<(
+        BytesStart<'static>,
+        BTreeMap<u32, (Vec<Event<'static>>, Option<String>)>,
+    )> = None;
+    let mut cell_col = 0_u32;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        match event {
+            Event::Eof => break,
+            Event::Empty(ref e) if e.local_name().as_ref() == b"dimension" => {
+                let mut dimension = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()));
+                let existing = xml_attr(e, b"ref").unwrap_or_default();
+                let mut cells = existing.split(':').filter_map(cell_position);
+                let (first, last) = match (cells.next(), cells.next()) {
+                    (Some(first), Some(last)) => (first, last),
+                    (Some(first), None) => (first, first),
+                    _ => (start, start),
+                };
+                let reference = if records.is_empty() {
+                    existing
+                } else {
+                    format!(
+                        "{}:{}",
+                        cell_reference((first.0.min(start.0), first.1.min(start.1))),
+                        cell_reference((last.0.max(last_row), last.1.max(last_col)))
+                    )
+                };
+                dimension.push_attribute(("ref", reference.as_str()));
+                wtr.write_event(Event::Empty(dimension))?;
+            },
+            Event::Empty(ref e) if e.local_name().as_ref() == b"sheetData" => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                wtr.write_event(Event::Start(e.borrow()))?;
+                write_new_rows(&mut wtr, records, &mut next_record, start, u32::MAX, styles)?;
+                wtr.write_event(Event::End(BytesEnd::new(name)))?;
+            },
+            Event::Start(ref e) if e.local_name().as_ref() == b"row" => {
+                row = row_position(e, row);
+                write_new_rows(&mut wtr, records, &mut next_record, start, row, styles)?;
+                if next_record < records.len() && start.0 + next_record as u32 == row {
+                    row_cells = Some((e.clone().into_owned(), BTreeMap::new()));
+                    cell_col = u32::MAX;
+                } else {
+                    wtr.write_event(Event::Start(e.borrow()))?;
+                }
+            },
+            Event::Empty(ref e) if e.local_name().as_ref() == b"row" => {
+                row = row_position(e, row);
+                write_new_rows(&mut wtr, records, &mut next_record, start, row, styles)?;
+                if next_record < records.len() && start.0 + next_record as u32 == row {
+                    write_row(
+                        &mut wtr,
+                        row,
+                        e,
+                        BTreeMap::new(),
+                        &records[next_record],
+                        start.1,
+                        styles,
+                    )?;
+                    next_record += 1;
+                } else {
+                    wtr.write_event(Event::Empty(e.borrow()))?;
+                }
+            },
+            Event::End(ref e) if e.local_name().as_ref() == b"row" && row_cells.is_some() => {
+                // safety: we just checked row_cells is some
+                let (row_start, cells) = row_cells.take().unwrap();
+                write_row(
+                    &mut wtr,
+                    row,
+                    &row_start,
+                    cells,
+                    &records[next_record],
+                    start.1,
+                    styles,
+                )?;
+                next_record += 1;
+            },
+            Event::End(ref e) if e.local_name().as_ref() == b"sheetData" => {
+                write_new_rows(&mut wtr, records, &mut next_record, start, u32::MAX, styles)?;
+                wtr.write_event(Event::End(e.borrow()))?;
+            },
+            event => {
+                if let Some((_, ref mut cells)) = row_cells {
+                    // collect the cells of the row being rewritten
+                    if let Event::Start(ref e) | Event::Empty(ref e) = event {
+                        if e.local_name().as_ref() == b"c" {
+                            cell_col = column_position(e, cell_col);
+                            cells.insert(cell_col, (Vec::new(), xml_attr(e, b"s")));
+                        }
+                    }
+                    if let Some((cell_events, _)) = cells.get_mut(&cell_col) {
+                        cell_events.push(event.into_owned());
+                    }
+                } else {
+                    wtr.write_event(event)?;
+                }
+            },
+        }
+        buf.clear();
+    }
+    Ok(wtr.into_inner())
+}
+
+/// the zero-based row of a row element. Rows without a reference follow the previous row.
+#[cfg(feature = "excel_xml")]
+fn row_position(row: &BytesStart, previous: u32) -> u32 {
+    xml_attr(row, b"r")
+        .and_then(|r| r.parse::<u32>().ok())
+        .and_then(|r| r.checked_sub(1))
+        .unwrap_or_else(|| previous.wrapping_add(1))
+}
+
+/// the zero-based column of a cell element. Cells without a reference follow the previous cell.
+#[cfg(feature = "excel_xml")]
+fn column_position(cell: &BytesStart, previous: u32) -> u32 {
+    xml_attr(cell, b"r")
+        .and_then(|r| cell_position(&r))
+        .map_or_else(|| previous.wrapping_add(1), |(_, col)| col)
+}
+
+/// writes the records that go in rows before `before_row` as new rows
+#[cfg(feature = "excel_xml")]
+fn write_new_rows(
+    wtr: &mut quick_xml::Writer<Vec<u8>>,
+    records: &[csv::StringRecord],
+    next_record: &mut usize,
+    start: (u32, u32),
+    before_row: u32,
+    styles: &HashMap<u32, String>,
+) -> CliResult<()> {
+    while *next_record < records.len() && start.0 + (*next_record as u32) < before_row {
+        let row = start.0 + *next_record as u32;
+        let mut row_start = BytesStart::new("row");
+        row_start.push_attribute(("r", (row + 1).to_string().as_str()));
+        write_row(
+            wtr,
+            row,
+            &row_start,
+            BTreeMap::new(),
+            &records[*next_record],
+            start.1,
+            styles,
+        )?;
+        *next_record += 1;
+    }
+    Ok(())
+}
+
+/// writes a row with a record written to its cells from `start_col`, and its other
+/// existing cells as is
+#[cfg(feature = "excel_xml")]
+fn write_row(
+    wtr: &mut quick_xml::Writer<Vec<u8>>,
+    row: u32,
+    row_start: &BytesStart,
+    mut cells: BTreeMap<u32, (Vec<Event<'static>>, Option<String>)>,
+    record: &csv::StringRecord,
+    start_col: u32,
+    styles: &HashMap<u32, String>,
+) -> CliResult<()> {
+    // the spans of the row's cells may change, and are optional
+    let row_name = String::from_utf8_lossy(row_start.name().as_ref()).to_string();
+    let mut new_row_start = BytesStart::new(row_name.clone());
+    new_row_start.extend_attributes(
+        row_start
+            .attributes()
+            .flatten()
+            .filter(|attr| attr.key.local_name().as_ref() != b"spans"),
+    );
+    wtr.write_event(Event::Start(new_row_start))?;
+
+    for (i, field) in record.iter().enumerate() {
+        let col = start_col + i as u32;
+        let style = match cells.remove(&col) {
+            Some((events, style)) => {
+                // the other cells of a shared formula only refer to it by its index,
+                // so they would lose their formulas along with its master cell
+                if let Some(range) = shared_formula_range(&events) {
+                    return fail_clierror!(
+                        "Cannot overwrite cell {} as it has the shared formula of {range}, which \
+                         the other cells of {range} also use. Expand the formula in the workbook \
+                         first, or write to other cells.",
+                        cell_reference((row, col))
+                    );
+                }
+                style
+            },
+            None => styles.get(&col).cloned(),
+        };
+        // write the existing cells before this one
+        let before: Vec<u32> = cells.range(..col).map(|(c, _)| *c).collect();
+        for c in before {
+            if let Some((events, _)) = cells.remove(&c) {
+                for event in events {
+                    wtr.write_event(event)?;
+                }
+            }
+        }
+        write_cell(wtr, (row, col), field, style.as_deref())?;
+    }
+    for (events, _) in cells.into_values() {
+        for event in events {
+            wtr.write_event(event)?;
+        }
+    }
+    wtr.write_event(Event::End(BytesEnd::new(row_name)))?;
+    Ok(())
+}
+
+/// the range of the shared formula of a cell, if it's the master cell of a shared formula
+/// spanning several cells
+#[cfg(feature = "excel_xml")]
+fn shared_formula_range(cell_events: &[Event]) -> Option<String> {
+    cell_events.iter().find_map(|event| match event {
+        Event::Start(e) | Event::Empty(e)
+            if e.local_name().as_ref() == b"f"
+                && xml_attr(e, b"t").as_deref() == Some("shared") =>
+        {
+            xml_attr(e, b"ref").filter(|range| range.contains(':'))
+        },
+        _ => None,
+    })
+}
+
+/// writes a cell. Numbers are written as numbers, other values as inline strings.
+/// Empty values clear the cell, keeping its style.
+#[cfg(feature = "excel_xml")]
+fn write_cell(
+    wtr: &mut quick_xml::Writer<Vec<u8>>,
+    position: (u32, u32),
+    value: &str,
+    style: Option<&str>,
+) -> CliResult<()> {
+    let mut cell = BytesStart::new("c");
+    cell.push_attribute(("r", cell_reference(position).as_str()));
+    if let Some(style) = style {
+        cell.push_attribute(("s", style));
+    }
+    if value.is_empty() {
+        wtr.write_event(Event::Empty(cell))?;
+        return Ok(());
+    }
+
+    // numbers with leading zeros or more than 15 digits are kept as text,
+    // as Excel would drop the zeros or lose precision
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let is_number = unsigned.starts_with(|c: char| c.is_ascii_digit())
+        && value.parse::<f64>().is_ok_and(f64::is_finite)
+        && !(unsigned.len() > 1 && unsigned.starts_with('0') && !unsigned.starts_with("0."))
+        && value.bytes().filter(u8::is_ascii_digit).count() <= 15;
+    if is_number {
+        wtr.write_event(Event::Start(cell))?;
+        wtr.write_event(Event::Start(BytesStart::new("v")))?;
+        wtr.write_event(Event::Text(BytesText::new(value)))?;
+        wtr.write_event(Event::End(BytesEnd::new("v")))?;
+    } else {
+        cell.push_attribute(("t", "inlineStr"));
+        wtr.write_event(Event::Start(cell))?;
+        wtr.write_event(Event::Start(BytesStart::new("is")))?;
+        let mut text = BytesStart::new("t");
+        if value.starts_with(char::is_whitespace) || value.ends_with(char::is_whitespace) {
+            text.push_attribute(("xml:space", "preserve"));
+        }
+        wtr.write_event(Event::Start(text))?;
+        wtr.write_event(Event::Text(BytesText::new(value)))?;
+        wtr.write_event(Event::End(BytesEnd::new("t")))?;
+        wtr.write_event(Event::End(BytesEnd::new("is")))?;
+    }
+    wtr.write_event(Event::End(BytesEnd::new("c")))?;
     Ok(())
 }
+
+/// sets fullCalcOnLoad in the workbook's calculation properties, so Excel recalculates
+/// the formulas that depend on the written cells when the workbook is opened
+#[cfg(feature = "excel_xml")]
+fn recalculate_on_load(workbook_xml: &[u8]) -> CliResult<Vec<u8>> {
+    let workbook = String::from_utf8_lossy(workbook_xml);
+    let has_calc_pr = workbook.contains("calcPr");
+    // calcPr goes after definedNames, or sheets if there are no defined names
+    let insert_after: &[u8] = if workbook.contains("definedNames>") {
+        b"definedNames"
+    } else {
+        b"sheets"
+    };
+
+    let mut reader = quick_xml::Reader::from_reader(workbook_xml);
+    let mut wtr = quick_xml::Writer::new(Vec::with_capacity(workbook_xml.len() + 32));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Start(ref e) | Event::Empty(ref e) if e.local_name().as_ref() == b"calcPr" => {
+                let mut calc_pr = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()));
+                calc_pr.extend_attributes(
+                    e.attributes()
+                        .flatten()
+                        .filter(|attr| attr.key.local_name().as_ref() != b"fullCalcOnLoad"),
+                );
+                calc_pr.push_attribute(("fullCalcOnLoad", "1"));
+                wtr.write_event(Event::Empty(calc_pr))?;
+            },
+            Event::End(ref e) if e.local_name().as_ref() == b"calcPr" => {},
+            Event::End(ref e) if !has_calc_pr && e.local_name().as_ref() == insert_after => {
+                wtr.write_event(Event::End(e.borrow()))?;
+                let mut calc_pr = BytesStart::new("calcPr");
+                calc_pr.push_attribute(("fullCalcOnLoad", "1"));
+                wtr.write_event(Event::Empty(calc_pr))?;
+            },
+            event => wtr.write_event(event)?,
+        }
+        buf.clear();
+    }
+    Ok(wtr.into_inner())
+}
+
+/// removes the references to the calculation chain from the workbook relationships &
+/// the content types
+#[cfg(feature = "excel_xml")]
+fn remove_calc_chain(xml: &[u8]) -> CliResult<Vec<u8>> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut wtr = quick_xml::Writer::new(Vec::with_capacity(xml.len()));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Empty(ref e)
+                if xml_attr(e, b"Target")
+                    .or_else(|| xml_attr(e, b"PartName"))
+                    .is_some_and(|part| part.ends_with("calcChain.xml")) => {},
+            event => wtr.write_event(event)?,
+        }
+        buf.clear();
+    }
+    Ok(wtr.into_inner())
+}
@@ -53,7 +53,7 @@ Common options:
                            Must be a single character. (default: ,)
 "#;
 
-use std::{collections::hash_map::Entry, fs, io, str};
+use std::{collections::hash_map::Entry, io, str};
 
 use ahash::AHashMap;
 use byteorder::{BigEndian, WriteBytesExt};
@@ -62,6 +62,7 @@ use serde::Deserialize;
 use crate::{
     config::{Config, Delimiter},
     index::Indexed,
+    seekable::InputFile,
     select::{SelectColumns, Selection},
     util,
     util::ByteString,
@@ -129,7 +130,7 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 }
 
 impl Args {
-    fn new_io_state(&self) -> CliResult<IoState<fs::File, Box<dyn io::Write + 'static>>> {
+    fn new_io_state(&self) -> CliResult<IoState<InputFile, Box<dyn io::Write + 'static>>> {
         let rconf1 = Config::new(Some(self.arg_input1.clone()).as_ref())
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers)
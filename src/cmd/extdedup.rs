@@ -18,6 +18,20 @@ This command has TWO modes of operation.
 
 A duplicate count will be sent to <stderr>.
 
+In CSV MODE, --keep picks which record of each group of duplicates survives.
+As this requires reading the input twice, the input must be a file - stdin is not
+supported. The survivor of each group is tracked in a hash table that grows with
+the number of distinct keys, up to --memory-limit.
+In this mode, --dupes-output is a valid CSV that lists every removed record prefixed
+with the columns dupe_rowno, dupe_group, dupe_group_count and kept_rowno, so each
+removed row can be traced back to the row that replaced it.
+
+With --state, the hash table is loaded from and saved back to a state file, so that
+rows seen in previous runs are also treated as duplicates. This is useful to only
+keep records never seen before when ingesting overlapping batches.
+The state file records how its keys were built (LINE MODE or the selected columns)
+and cannot be used with a different selection.
+
 Usage:
     qsv extdedup [options] [<input>] [<output>]
     qsv extdedup --help
@@ -33,8 +47,28 @@ extdedup options:
                                Note that the file will NOT be a valid CSV.
                                It is a list of duplicate lines, with the row number of the
                                duplicate separated by a tab from the duplicate line itself.
+    --keep <policy>            CSV MODE ONLY. Which record of a group of duplicates to keep.
+                               Valid policies:
+                                 first         - the first record in input order
+                                 last          - the last record in input order
+                                 max:<column>  - the record with the largest value in <column>
+                                 min:<column>  - the record with the smallest value in <column>
+                                 most-complete - the record with the fewest empty fields
+                               max/min compare numbers numerically and dates chronologically.
+                               Other values rank below any number or date.
+                               Ties are resolved in favor of the earlier record.
+                               The output retains the input sort order.
+                               Requires an input file. Does not work with <stdin>.
     -H, --human-readable       Comma separate duplicate count.
+    --state <file>             Load the dedup state from <file> before deduping, and save
+                               the updated state back to <file> afterwards.
+                               If <file> does not exist, it will be created.
+                               Cannot be used with --keep.
+    --reset-state              Ignore the contents of the --state file and start
+                               from an empty state. The file is overwritten when done.
     --memory-limit <arg>       The maximum amount of memory to buffer the on-disk hash table.
+                               With --keep, the maximum memory of the groups table.
+                               With --state, the hash table of the state file has to fit in it.
                                If less than 50, this is a percentage of total memory.
                                If more than 50, this is the memory in MB to allocate, capped
                                at 90 percent of total memory.
@@ -55,7 +89,9 @@ Common options:
 
 use std::{
     fs,
-    io::{self, stdin, stdout, BufRead, Write},
+    io::{self, stdout, BufRead, Write},
+    path::Path,
+    str::FromStr,
 };
 
 use indicatif::HumanCount;
@@ -63,10 +99,11 @@ use serde::Deserialize;
 use sysinfo::System;
 
 use crate::{
+    cmd::dedup::{KeepPolicy, KeepScorer, DUPE_GROUP_COLUMNS},
     config,
     config::{Config, Delimiter},
     odhtcache,
-    select::SelectColumns,
+    select::{SelectColumns, Selection},
     util, CliResult,
 };
 
@@ -79,13 +116,22 @@ struct Args {
     flag_delimiter:      Option<Delimiter>,
     flag_no_output:      bool,
     flag_dupes_output:   Option<String>,
+    flag_keep:           Option<String>,
     flag_human_readable: bool,
+    flag_state:          Option<String>,
+    flag_reset_state:    bool,
     flag_memory_limit:   Option<u64>,
     flag_quiet:          bool,
 }
 
 const MEMORY_LIMITED_BUFFER: u64 = 100 * 1_000_000; // 100 MB
 
+/// the key spec of states saved in LINE MODE
+const LINE_MODE_KEY_SPEC: &str = "line";
+
+/// separates the selected fields of a CSV MODE key (ASCII unit separator)
+const KEY_SEPARATOR: char = '\x1f';
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -97,8 +143,21 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let quiet = args.flag_quiet;
     let human_readable = args.flag_human_readable;
 
+    if args.flag_state.is_none() && args.flag_reset_state {
+        return fail_incorrectusage_clierror!("--reset-state requires --state.");
+    }
+
     let dupes_count = if args.flag_select.is_some() {
-        dedup_csv(args, mem_limited_buffer_bytes)?
+        if args.flag_keep.is_some() {
+            if args.flag_state.is_some() {
+                return fail_incorrectusage_clierror!("--state cannot be used with --keep.");
+            }
+            dedup_csv_keep(args, mem_limited_buffer_bytes)?
+        } else {
+            dedup_csv(args, mem_limited_buffer_bytes)?
+        }
+    } else if args.flag_keep.is_some() {
+        return fail_incorrectusage_clierror!("--keep requires --select (CSV MODE).");
     } else {
         dedup_lines(args, mem_limited_buffer_bytes)?
     };
@@ -123,7 +182,7 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select.unwrap());
+        .select(args.flag_select.clone().unwrap());
 
     let mut rdr = rconfig.reader()?;
     let mut wtr = Config::new(args.arg_output.as_ref()).writer()?;
@@ -138,9 +197,18 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
         dupewtr.write_byte_record(&dupe_headers)?;
     }
 
-    let mut dedup_cache = odhtcache::ExtDedupCache::new(mem_limited_buffer);
-    let mut dupes_count = 0_u64;
     let sel = rconfig.selection(&headers)?;
+    let key_spec = if args.flag_no_headers {
+        format!("csv:{:?}", &*sel)
+    } else {
+        let names = sel
+            .iter()
+            .map(|&i| String::from_utf8_lossy(&headers[i]))
+            .collect::<Vec<_>>();
+        format!("csv:{}", names.join(","))
+    };
+    let mut dedup_cache = load_state(&args, &key_spec, mem_limited_buffer)?;
+    let mut dupes_count = 0_u64;
 
     rconfig.write_headers(&mut rdr, &mut wtr)?;
 
@@ -152,16 +220,7 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
 
     for (row_idx, row) in rdr.byte_records().enumerate() {
         curr_row.clone_from(&row?);
-        key.clear();
-        for field in sel.select(&curr_row) {
-            if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
-                key.push_str(s_utf8);
-            } else {
-                utf8_string.clear();
-                utf8_string.push_str(&String::from_utf8_lossy(field));
-                key.push_str(&utf8_string);
-            }
-        }
+        build_key(&sel, &curr_row, &mut key, &mut utf8_string);
 
         if dedup_cache.contains(&key) {
             dupes_count += 1;
@@ -179,26 +238,134 @@ fn dedup_csv(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes
 
     dupewtr.flush()?;
     wtr.flush()?;
+    save_state(&args, &key_spec, &mut dedup_cache)?;
 
     Ok(dupes_count)
 }
 
-fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes::CliError> {
-    let input_reader: Box<dyn BufRead> = match &args.arg_input {
-        Some(input_path) => {
-            if input_path.to_lowercase().ends_with(".sz") {
-                return fail_clierror!(
-                    "Input file cannot be a .sz file. Use 'qsv snappy decompress' first."
-                );
-            }
-            let file = fs::File::open(input_path)?;
-            Box::new(io::BufReader::with_capacity(
-                config::DEFAULT_RDR_BUFFER_CAPACITY,
-                file,
-            ))
-        },
-        None => Box::new(io::BufReader::new(stdin().lock())),
+/// Dedupes in two passes. The first pass finds the surviving record of every key
+/// using the --keep policy. The second pass writes the survivors in input order.
+fn dedup_csv_keep(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes::CliError> {
+    let keep_policy = match KeepPolicy::from_str(args.flag_keep.as_deref().unwrap_or_default()) {
+        Ok(policy) => policy,
+        Err(e) => return fail_incorrectusage_clierror!("{e}"),
     };
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.flag_select.unwrap());
+    if rconfig.is_stdin() {
+        return fail_incorrectusage_clierror!(
+            "--keep requires an input file as it reads the input twice. stdin is not supported."
+        );
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+    let scorer = KeepScorer::new(keep_policy, &headers, args.flag_no_headers)?;
+    let replaces = |kept: f64, candidate: f64| scorer.replaces(kept, candidate);
+
+    // size the groups table from the row count if the input is indexed
+    let row_count_hint = match rconfig.indexed()? {
+        Some(idx) => usize::try_from(idx.count()).unwrap_or(usize::MAX),
+        None => 0,
+    };
+    let mut groups = odhtcache::ExtDedupGroups::new(row_count_hint, mem_limited_buffer);
+    let mut key = String::with_capacity(20);
+    let mut utf8_string = String::with_capacity(20);
+    let mut curr_row = csv::ByteRecord::new();
+
+    // first pass - find the survivor of each group
+    let mut rowno = 0_u64;
+    while rdr.read_byte_record(&mut curr_row)? {
+        rowno += 1;
+        build_key(&sel, &curr_row, &mut key, &mut utf8_string);
+        if let Err(e) = groups.observe(&key, rowno, scorer.score(&curr_row), replaces) {
+            return fail_clierror!("{e} Increase --memory-limit.");
+        }
+    }
+
+    // second pass - write the survivors and the dupes
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(args.arg_output.as_ref()).writer()?;
+    let dupes_output = args.flag_dupes_output.is_some();
+    let mut dupewtr = Config::new(args.flag_dupes_output.as_ref()).writer()?;
+
+    if dupes_output {
+        let mut dupe_headers = csv::ByteRecord::new();
+        for col in DUPE_GROUP_COLUMNS {
+            dupe_headers.push_field(col.as_bytes());
+        }
+        dupe_headers.extend(headers.iter());
+        dupewtr.write_byte_record(&dupe_headers)?;
+    }
+    rconfig.write_headers(&mut rdr, &mut wtr)?;
+
+    let mut dupes_count = 0_u64;
+    let mut dupe_row = csv::ByteRecord::new();
+    let mut itoa_buffer = itoa::Buffer::new();
+    rowno = 0;
+    while rdr.read_byte_record(&mut curr_row)? {
+        rowno += 1;
+        build_key(&sel, &curr_row, &mut key, &mut utf8_string);
+        let Some(group) = groups.get(&key) else {
+            return fail_clierror!(
+                "Input changed between passes. Row {rowno} was not seen before."
+            );
+        };
+
+        if group.kept_rowno == rowno {
+            if !args.flag_no_output {
+                wtr.write_byte_record(&curr_row)?;
+            }
+            continue;
+        }
+
+        dupes_count += 1;
+        if dupes_output {
+            dupe_row.clear();
+            dupe_row.push_field(itoa_buffer.format(rowno).as_bytes());
+            dupe_row.push_field(itoa_buffer.format(group.group_id).as_bytes());
+            dupe_row.push_field(itoa_buffer.format(group.count).as_bytes());
+            dupe_row.push_field(itoa_buffer.format(group.kept_rowno).as_bytes());
+            dupe_row.extend(curr_row.iter());
+            dupewtr.write_byte_record(&dupe_row)?;
+        }
+    }
+
+    dupewtr.flush()?;
+    wtr.flush()?;
+
+    Ok(dupes_count)
+}
+
+/// concatenates the selected fields of `row` into `key`, separated by KEY_SEPARATOR
+/// so that ("ab", "c") & ("a", "bc") are different keys
+#[inline]
+fn build_key(sel: &Selection, row: &csv::ByteRecord, key: &mut String, utf8_string: &mut String) {
+    key.clear();
+    for (i, field) in sel.select(row).enumerate() {
+        if i > 0 {
+            key.push(KEY_SEPARATOR);
+        }
+        if let Ok(s_utf8) = simdutf8::basic::from_utf8(field) {
+            key.push_str(s_utf8);
+        } else {
+            utf8_string.clear();
+            utf8_string.push_str(&String::from_utf8_lossy(field));
+            key.push_str(utf8_string);
+        }
+    }
+}
+
+fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clitypes::CliError> {
+    // the config's reader transparently decodes compressed input
+    let input_reader: Box<dyn BufRead> = Box::new(io::BufReader::with_capacity(
+        config::DEFAULT_RDR_BUFFER_CAPACITY,
+        Config::new(args.arg_input.as_ref()).io_reader()?,
+    ));
     let mut output_writer: Box<dyn Write> = match &args.arg_output {
         Some(output_path) => Box::new(io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -211,7 +378,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
     };
     let mut write_dupes = false;
     #[cfg(target_family = "unix")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(ref dupes_output) = args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -224,7 +391,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
         )
     };
     #[cfg(target_family = "windows")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(ref dupes_output) = args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -236,7 +403,7 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
             fs::File::create("nul")?,
         )
     };
-    let mut dedup_cache = odhtcache::ExtDedupCache::new(mem_limited_buffer);
+    let mut dedup_cache = load_state(&args, LINE_MODE_KEY_SPEC, mem_limited_buffer)?;
     let mut dupes_count = 0_u64;
     let mut line_work = String::with_capacity(1024);
     for (row_idx, line) in input_reader.lines().enumerate() {
@@ -256,10 +423,51 @@ fn dedup_lines(args: Args, mem_limited_buffer: u64) -> Result<u64, crate::clityp
     }
     dupes_writer.flush()?;
     output_writer.flush()?;
+    save_state(&args, LINE_MODE_KEY_SPEC, &mut dedup_cache)?;
 
     Ok(dupes_count)
 }
 
+/// Creates the dedup cache, loading it from the --state file if there is one.
+fn load_state(
+    args: &Args,
+    key_spec: &str,
+    mem_limited_buffer: u64,
+) -> CliResult<odhtcache::ExtDedupCache> {
+    match args.flag_state {
+        Some(ref state_path) if !args.flag_reset_state && Path::new(state_path).exists() => {
+            match odhtcache::ExtDedupCache::load(
+                Path::new(state_path),
+                key_spec,
+                mem_limited_buffer,
+            ) {
+                Ok(cache) => Ok(cache),
+                Err(e) if e.kind() == std::io::ErrorKind::OutOfMemory => {
+                    fail_clierror!(
+                        "Cannot load state file {state_path}: {e} Increase --memory-limit."
+                    )
+                },
+                Err(e) => fail_clierror!("Cannot load state file {state_path}: {e}"),
+            }
+        },
+        _ => Ok(odhtcache::ExtDedupCache::new(mem_limited_buffer)),
+    }
+}
+
+/// Saves the dedup cache to the --state file, if there is one.
+fn save_state(
+    args: &Args,
+    key_spec: &str,
+    dedup_cache: &mut odhtcache::ExtDedupCache,
+) -> CliResult<()> {
+    if let Some(ref state_path) = args.flag_state {
+        if let Err(e) = dedup_cache.save(Path::new(state_path), key_spec) {
+            return fail_clierror!("Cannot save state file {state_path}: {e}");
+        }
+    }
+    Ok(())
+}
+
 /// Determines the memory buffer size to use for on-disk hash table based on
 /// the provided flag and the system's total memory.
 ///
@@ -45,8 +45,7 @@ Common options:
 "#;
 
 use std::{
-    fs,
-    io::{self, stdin, stdout, BufRead, Write},
+    io::{self, BufRead, Write},
     path,
 };
 
@@ -249,40 +248,16 @@ fn sort_lines(
     args: &Args,
     sorter: &ExternalSorter<String, io::Error, MemoryLimitedBufferBuilder>,
 ) -> Result<(), crate::clitypes::CliError> {
-    let mut input_rdr: Box<dyn BufRead> = match &args.arg_input {
-        Some(input_path) => {
-            if input_path.to_lowercase().ends_with(".sz") {
-                return fail_incorrectusage_clierror!(
-                    "Input file cannot be a .sz file. Use 'qsv snappy decompress' first."
-                );
-            }
-            let file = fs::File::open(input_path)?;
-            Box::new(io::BufReader::with_capacity(
-                config::DEFAULT_RDR_BUFFER_CAPACITY,
-                file,
-            ))
-        },
-        None => Box::new(io::BufReader::new(stdin().lock())),
-    };
+    // the config's reader & writer transparently handle compressed input & output
+    let mut input_rdr: Box<dyn BufRead> = Box::new(io::BufReader::with_capacity(
+        config::DEFAULT_RDR_BUFFER_CAPACITY,
+        Config::new(args.arg_input.as_ref()).io_reader()?,
+    ));
 
-    let mut output_wtr: Box<dyn Write> = match &args.arg_output {
-        Some(output_path) => {
-            if output_path.to_lowercase().ends_with(".sz") {
-                return fail_clierror!(
-                    "Output file cannot be a .sz file. Compress it after sorting with 'qsv snappy \
-                     compress'."
-                );
-            }
-            Box::new(io::BufWriter::with_capacity(
-                RW_BUFFER_CAPACITY,
-                fs::File::create(output_path)?,
-            ))
-        },
-        None => Box::new(io::BufWriter::with_capacity(
-            RW_BUFFER_CAPACITY,
-            stdout().lock(),
-        )),
-    };
+    let mut output_wtr: Box<dyn Write> = Box::new(io::BufWriter::with_capacity(
+        RW_BUFFER_CAPACITY,
+        Config::new(args.arg_output.as_ref()).io_writer()?,
+    ));
 
     let mut header = String::new();
     if !args.flag_no_headers {
@@ -0,0 +1,832 @@
+static USAGE: &str = r#"
+Converts fixed-width text files to CSV and back, using a separate layout file
+that describes where each field starts and how long it is.
+
+The "import" subcommand reads fixed-width records and writes them as CSV.
+The "export" subcommand reads CSV and writes fixed-width records, padding or
+truncating each value to its field width.
+
+The layout is either:
+  * a CSV file with a header row and the columns:
+      name      - the field name. Empty or FILLER fields are skipped.
+      start     - the 1-based starting position of the field. If this column
+                  is missing or empty, the field starts right after the
+                  previous one.
+      length    - the width of the field (alias: width). Alternatively, give
+                  an "end" column with the 1-based inclusive end position.
+      type      - optional. One of string (default), integer or decimal.
+      decimals  - optional. The number of implied decimal places.
+  * a COBOL copybook (.cpy, .cob or .cbl extension, or when --copybook is set).
+    Only elementary DISPLAY items are supported: PIC X/A fields become
+    strings, PIC 9 fields become numbers, V marks the implied decimal point
+    and S makes the field signed (using trailing overpunch). FILLER items
+    take up space but are not output. Level 88 condition names are ignored.
+    REDEFINES, OCCURS, COMP/BINARY usages, SIGN clauses and edited pictures
+    are not supported.
+
+Positions and lengths are in bytes.
+
+Numeric fields may carry implied decimals (e.g. "0012345" with 2 decimals is
+123.45) and a sign, either as a leading or trailing "+"/"-", or with --overpunch,
+as a signed overpunch character replacing the last digit ("{", "A"-"I" for
+positive values and "}", "J"-"R" for negative values). On export, numbers are
+right-aligned and zero-padded, while strings are left-aligned and padded.
+
+Examples:
+  Convert a fixed-width extract to CSV using a CSV layout
+    $ qsv fixedwidth import --layout layout.csv extract.txt -o extract.csv
+
+  Convert a mainframe extract using its copybook, keeping leading spaces
+    $ qsv fixedwidth import --layout CUSTREC.cpy --trim right CUSTREC.dat
+
+  Convert a CSV back to fixed-width, with CRLF record terminators
+    $ qsv fixedwidth export --layout layout.csv --crlf data.csv -o data.txt
+
+  Export 120-byte records without record terminators, failing on overlong values
+    $ qsv fixedwidth export --layout layout.csv --record-length 120 --strict data.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_fixedwidth.rs.
+
+Usage:
+    qsv fixedwidth import --layout <file> [options] [<input>]
+    qsv fixedwidth export --layout <file> [options] [<input>]
+    qsv fixedwidth --help
+
+fixedwidth options:
+    -L, --layout <file>        The layout file describing the fields.
+    --copybook                 Parse the layout as a COBOL copybook, regardless
+                               of its file extension.
+    --overpunch                Numeric fields in a CSV layout use signed overpunch.
+                               Copybook fields declared with S always use it.
+    --trim <mode>              import: how to trim string fields.
+                               One of none, left, right or both. [default: both]
+    --record-length <n>        Records are exactly <n> bytes with no record
+                               terminator. On export, records are padded to <n>.
+                               Without this option, records are lines.
+    --pad <char>               export: the character used to pad string fields.
+                               (default: space)
+    --strict                   import: fail on records shorter than the layout.
+                               export: fail on strings longer than their field
+                               instead of truncating them.
+    --crlf                     export: end records with CRLF instead of LF.
+
+Common options:
+    -h, --help                 Display this message
+    -o, --output <file>        Write output to <file> instead of stdout.
+    -n, --no-headers           import: do not write a header row.
+                               export: the input CSV has no header row, so
+                               fields are matched to columns by position
+                               instead of by name.
+    -d, --delimiter <arg>      The field delimiter of the CSV data.
+                               Must be a single character. (default: ,)
+"#;
+
+use std::{
+    fs,
+    io::{self, BufRead, Read, Write},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    config::{Config, Delimiter, DEFAULT_RDR_BUFFER_CAPACITY, DEFAULT_WTR_BUFFER_CAPACITY},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    cmd_import:         bool,
+    cmd_export:         bool,
+    arg_input:          Option<String>,
+    flag_layout:        String,
+    flag_copybook:      bool,
+    flag_overpunch:     bool,
+    flag_trim:          Trim,
+    flag_record_length: Option<usize>,
+    flag_pad:           Option<String>,
+    flag_strict:        bool,
+    flag_crlf:          bool,
+    flag_output:        Option<String>,
+    flag_no_headers:    bool,
+    flag_delimiter:     Option<Delimiter>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+enum Trim {
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+impl Trim {
+    fn apply(self, s: &str) -> &str {
+        match self {
+            Trim::None => s,
+            Trim::Left => s.trim_start(),
+            Trim::Right => s.trim_end(),
+            Trim::Both => s.trim(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FieldType {
+    String,
+    Integer,
+    Decimal,
+}
+
+#[derive(Debug)]
+struct Field {
+    name:      String,
+    /// 0-based byte offset of the field in the record
+    start:     usize,
+    length:    usize,
+    kind:      FieldType,
+    /// number of implied decimal places
+    decimals:  usize,
+    signed:    bool,
+    overpunch: bool,
+    filler:    bool,
+}
+
+impl Field {
+    const fn end(&self) -> usize {
+        self.start + self.length
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let layout_path = Path::new(&args.flag_layout);
+    let is_copybook = args.flag_copybook
+        || layout_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "cpy" | "cob" | "cbl"));
+    let fields = if is_copybook {
+        let copybook = fs::read_to_string(layout_path)
+            .map_err(|e| format!("Cannot read copybook {}: {e}", layout_path.display()))?;
+        parse_copybook(&copybook)?
+    } else {
+        read_csv_layout(&args.flag_layout, args.flag_overpunch)?
+    };
+    if fields.iter().all(|f| f.filler) {
+        return fail_incorrectusage_clierror!("The layout does not define any fields.");
+    }
+    let layout_len = fields.iter().map(Field::end).max().unwrap_or_default();
+
+    if let Some(record_length) = args.flag_record_length {
+        if record_length == 0 {
+            return fail_incorrectusage_clierror!("--record-length must be greater than 0.");
+        }
+        if record_length < layout_len {
+            return fail_incorrectusage_clierror!(
+                "--record-length {record_length} is shorter than the layout ({layout_len} bytes)."
+            );
+        }
+    }
+
+    if args.cmd_import {
+        import(&args, &fields, layout_len)
+    } else if args.cmd_export {
+        export(&args, &fields, layout_len)
+    } else {
+        unreachable!()
+    }
+}
+
+fn import(args: &Args, fields: &[Field], layout_len: usize) -> CliResult<()> {
+    let mut rdr = io::BufReader::with_capacity(
+        DEFAULT_RDR_BUFFER_CAPACITY,
+        Config::new(args.arg_input.as_ref()).io_reader()?,
+    );
+    let mut wtr = Config::new(args.flag_output.as_ref())
+        .delimiter(args.flag_delimiter)
+        .writer()?;
+
+    if !args.flag_no_headers {
+        wtr.write_record(fields.iter().filter(|f| !f.filler).map(|f| &f.name))?;
+    }
+
+    let mut line = Vec::with_capacity(layout_len + 2);
+    let mut record = csv::StringRecord::with_capacity(layout_len, fields.len());
+    let mut recno = 0_u64;
+    loop {
+        line.clear();
+        if let Some(record_length) = args.flag_record_length {
+            (&mut rdr)
+                .take(record_length as u64)
+                .read_to_end(&mut line)?;
+            if line.is_empty() {
+                break;
+            }
+            if line.len() < record_length {
+                // ignore a trailing newline at the end of the file
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    break;
+                }
+                return fail_clierror!(
+                    "Record {} is truncated: expected {record_length} bytes, got {}.",
+                    recno + 1,
+                    line.len()
+                );
+            }
+        } else {
+            if rdr.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            while line.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+                line.pop();
+            }
+            if line.is_empty() {
+                continue;
+            }
+        }
+        recno += 1;
+
+        if args.flag_strict && line.len() < layout_len {
+            return fail_clierror!(
+                "Record {recno} is {} bytes long, shorter than the layout ({layout_len} bytes).",
+                line.len()
+            );
+        }
+
+        record.clear();
+        for field in fields.iter().filter(|f| !f.filler) {
+            let start = field.start.min(line.len());
+            let end = field.end().min(line.len());
+            let raw = String::from_utf8_lossy(&line[start..end]);
+            if field.kind == FieldType::String {
+                record.push_field(args.flag_trim.apply(&raw));
+            } else {
+                let value = decode_number(&raw, field)
+                    .map_err(|e| format!("Record {recno}, field \"{}\": {e}", field.name))?;
+                record.push_field(&value);
+            }
+        }
+        wtr.write_record(&record)?;
+    }
+
+    Ok(wtr.flush()?)
+}
+
+fn export(args: &Args, fields: &[Field], layout_len: usize) -> CliResult<()> {
+    let pad = match args.flag_pad.as_deref().map(str::as_bytes) {
+        None => b' ',
+        Some([b]) if b.is_ascii() => *b,
+        _ => return fail_incorrectusage_clierror!("--pad must be a single ASCII character."),
+    };
+
+    let mut rdr = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .reader()?;
+
+    // map each output field to its column in the input
+    let headers = rdr.headers()?.clone();
+    let mut columns = Vec::with_capacity(fields.len());
+    for (pos, field) in fields.iter().filter(|f| !f.filler).enumerate() {
+        let column = if args.flag_no_headers {
+            pos
+        } else if let Some(column) = headers.iter().position(|h| h == field.name) {
+            column
+        } else {
+            return fail_incorrectusage_clierror!(
+                "Column \"{}\" of the layout was not found in the input.",
+                field.name
+            );
+        };
+        columns.push((field, column));
+    }
+
+    let mut wtr = io::BufWriter::with_capacity(
+        DEFAULT_WTR_BUFFER_CAPACITY,
+        Config::new(args.flag_output.as_ref()).io_writer()?,
+    );
+    let record_len = args.flag_record_length.unwrap_or(layout_len);
+    let terminator: &[u8] = match (args.flag_record_length, args.flag_crlf) {
+        (Some(_), _) => b"",
+        (None, true) => b"\r\n",
+        (None, false) => b"\n",
+    };
+
+    let mut line = vec![b' '; record_len];
+    let mut record = csv::StringRecord::new();
+    let mut recno = 0_u64;
+    while rdr.read_record(&mut record)? {
+        recno += 1;
+        line.fill(b' ');
+        for &(field, column) in &columns {
+            let Some(value) = record.get(column) else {
+                return fail_clierror!(
+                    "Record {recno} has no column {} for field \"{}\".",
+                    column + 1,
+                    field.name
+                );
+            };
+            let target = &mut line[field.start..field.end()];
+            let result = if field.kind == FieldType::String {
+                encode_string(value, target, pad, args.flag_strict)
+            } else {
+                encode_number(value, field).map(|s| target.copy_from_slice(s.as_bytes()))
+            };
+            result.map_err(|e| format!("Record {recno}, field \"{}\": {e}", field.name))?;
+        }
+        wtr.write_all(&line)?;
+        wtr.write_all(terminator)?;
+    }
+
+    Ok(wtr.flush()?)
+}
+
+/// Reads a layout from a CSV file with name, start, length/end, type and
+/// decimals columns.
+fn read_csv_layout(path: &str, overpunch: bool) -> CliResult<Vec<Field>> {
+    let mut rdr = Config::new(Some(&path.to_string()))
+        .no_headers(false)
+        .reader()?;
+    let headers = rdr.headers()?.clone();
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|h| names.iter().any(|n| h.trim().eq_ignore_ascii_case(n)))
+    };
+    let Some(name_col) = column(&["name", "field"]) else {
+        return fail_incorrectusage_clierror!("The layout has no \"name\" column.");
+    };
+    let start_col = column(&["start", "position"]);
+    let length_col = column(&["length", "width"]);
+    let end_col = column(&["end"]);
+    let type_col = column(&["type"]);
+    let decimals_col = column(&["decimals"]);
+    if length_col.is_none() && (end_col.is_none() || start_col.is_none()) {
+        return fail_incorrectusage_clierror!(
+            "The layout needs a \"length\" column, or \"start\" and \"end\" columns."
+        );
+    }
+
+    let get = |record: &csv::StringRecord, col: Option<usize>| -> Option<String> {
+        col.and_then(|c| record.get(c))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let parse_num = |value: Option<String>, what: &str, row: usize| -> CliResult<Option<usize>> {
+        value
+            .map(|v| {
+                v.parse::<usize>()
+                    .map_err(|_| format!("Layout row {row}: invalid {what} \"{v}\".").into())
+            })
+            .transpose()
+    };
+
+    let mut fields = Vec::new();
+    let mut next_start = 0_usize;
+    for (i, record) in rdr.records().enumerate() {
+        let record = record?;
+        let row = i + 1;
+        let name = get(&record, Some(name_col)).unwrap_or_default();
+
+        let start = match parse_num(get(&record, start_col), "start", row)? {
+            Some(0) => {
+                return fail_incorrectusage_clierror!(
+                    "Layout row {row}: start positions are 1-based."
+                )
+            },
+            Some(start) => start - 1,
+            None => next_start,
+        };
+        let length = match (
+            parse_num(get(&record, length_col), "length", row)?,
+            parse_num(get(&record, end_col), "end", row)?,
+        ) {
+            (Some(length), _) => length,
+            (None, Some(end)) if end > start => end - start,
+            (None, Some(end)) => {
+                return fail_incorrectusage_clierror!(
+                    "Layout row {row}: end position {end} is before start position {}.",
+                    start + 1
+                )
+            },
+            (None, None) => {
+                return fail_incorrectusage_clierror!("Layout row {row}: missing length.")
+            },
+        };
+        if length == 0 {
+            return fail_incorrectusage_clierror!(
+                "Layout row {row}: length must be greater than 0."
+            );
+        }
+
+        let decimals = parse_num(get(&record, decimals_col), "decimals", row)?.unwrap_or_default();
+        let kind = match get(&record, type_col)
+            .map(|t| t.to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("string" | "text" | "str" | "char" | "alphanumeric") => {
+                if decimals > 0 {
+                    return fail_incorrectusage_clierror!(
+                        "Layout row {row}: string fields cannot have decimals."
+                    );
+                }
+                FieldType::String
+            },
+            Some("integer" | "int") if decimals == 0 => FieldType::Integer,
+            Some("integer" | "int" | "decimal" | "number" | "numeric" | "float") => {
+                FieldType::Decimal
+            },
+            Some(other) => {
+                return fail_incorrectusage_clierror!(
+                    "Layout row {row}: unknown type \"{other}\". Valid types are string, integer \
+                     and decimal."
+                )
+            },
+        };
+        if kind != FieldType::String && decimals >= length {
+            return fail_incorrectusage_clierror!(
+                "Layout row {row}: {decimals} decimals do not fit in a field of length {length}."
+            );
+        }
+
+        let filler = name.is_empty() || name.eq_ignore_ascii_case("filler");
+        fields.push(Field {
+            name,
+            start,
+            length,
+            kind,
+            decimals,
+            signed: kind != FieldType::String,
+            overpunch: overpunch && kind != FieldType::String,
+            filler,
+        });
+        next_start = start + length;
+    }
+    Ok(fields)
+}
+
+/// Parses the elementary items of a COBOL copybook into a layout.
+/// Group items only structure the record; fields are laid out one after
+/// another in the order they are declared.
+fn parse_copybook(source: &str) -> CliResult<Vec<Field>> {
+    // strip the sequence and indicator areas and comment lines
+    let mut code = String::with_capacity(source.len());
+    for line in source.lines() {
+        let bytes = line.as_bytes();
+        let fixed_format = bytes.len() >= 7
+            && (bytes[..6].iter().all(u8::is_ascii_digit) || bytes[..6].iter().all(|&b| b == b' '))
+            && matches!(bytes[6], b' ' | b'*' | b'/');
+        let line = if fixed_format {
+            if bytes[6] != b' ' {
+                continue;
+            }
+            // columns 73-80 are the identification area
+            line.get(7..line.len().min(72)).unwrap_or_default()
+        } else {
+            line
+        };
+        if line.trim_start().starts_with('*') {
+            continue;
+        }
+        code.push_str(line);
+        code.push(' ');
+    }
+
+    let mut fields = Vec::new();
+    let mut position = 0_usize;
+    let mut statement: Vec<&str> = Vec::new();
+    for token in code.split_whitespace() {
+        if let Some(last) = token.strip_suffix('.') {
+            if !last.is_empty() {
+                statement.push(last);
+            }
+            if let Some(field) = parse_copybook_entry(&statement, position)? {
+                position = field.end();
+                fields.push(field);
+            }
+            statement.clear();
+        } else {
+            statement.push(token);
+        }
+    }
+    if !statement.is_empty() {
+        return fail_incorrectusage_clierror!(
+            "Copybook entry \"{}\" is not terminated by a period.",
+            statement.join(" ")
+        );
+    }
+    Ok(fields)
+}
+
+fn parse_copybook_entry(tokens: &[&str], position: usize) -> CliResult<Option<Field>> {
+    let Some((level, rest)) = tokens.split_first() else {
+        return Ok(None);
+    };
+    let entry = tokens.join(" ");
+    let level = match level.parse::<u8>() {
+        Ok(88) => return Ok(None),
+        Ok(66) => {
+            return fail_incorrectusage_clierror!(
+                "Copybook entry \"{entry}\": level 66 (RENAMES) is not supported."
+            )
+        },
+        Ok(level) if (1..=49).contains(&level) || level == 77 => level,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Copybook entry \"{entry}\": invalid level number."
+            )
+        },
+    };
+
+    let is_clause = |t: &str| {
+        matches!(
+            t.to_ascii_uppercase().as_str(),
+            "PIC" | "PICTURE" | "USAGE" | "VALUE" | "VALUES" | "REDEFINES" | "OCCURS"
+        )
+    };
+    let (name, clauses) = match rest.split_first() {
+        Some((name, clauses)) if !is_clause(name) => (*name, clauses),
+        _ => ("FILLER", rest),
+    };
+
+    let mut picture = None;
+    let mut clauses = clauses.iter().map(|t| t.to_ascii_uppercase());
+    while let Some(clause) = clauses.next() {
+        match clause.as_str() {
+            "PIC" | "PICTURE" => {
+                let mut pic = clauses.next();
+                if pic.as_deref() == Some("IS") {
+                    pic = clauses.next();
+                }
+                picture = pic;
+            },
+            "USAGE" | "IS" | "DISPLAY" => {},
+            "VALUE" | "VALUES" => break,
+            "REDEFINES" | "OCCURS" | "SIGN" | "RENAMES" => {
+                return fail_incorrectusage_clierror!(
+                    "Copybook entry \"{entry}\": {clause} is not supported."
+                )
+            },
+            usage
+                if usage.starts_with("COMP")
+                    || matches!(usage, "BINARY" | "PACKED-DECIMAL" | "POINTER" | "INDEX") =>
+            {
+                return fail_incorrectusage_clierror!(
+                    "Copybook entry \"{entry}\": {usage} usage is not supported. Only DISPLAY \
+                     fields can be read from fixed-width text."
+                )
+            },
+            _ => {},
+        }
+    }
+
+    // group items have no picture
+    let Some(picture) = picture else {
+        return Ok(None);
+    };
+    let Some((kind, length, decimals, signed)) = parse_picture(&picture) else {
+        return fail_incorrectusage_clierror!(
+            "Copybook entry \"{entry}\": unsupported picture \"{picture}\". Only X, A, 9, S and V \
+             are supported."
+        );
+    };
+    log::debug!("copybook level {level} {name}: {picture} at {position}");
+    Ok(Some(Field {
+        name: name.to_string(),
+        start: position,
+        length,
+        kind,
+        decimals,
+        signed,
+        overpunch: signed,
+        filler: name.eq_ignore_ascii_case("filler"),
+    }))
+}
+
+/// Parses a PICTURE string, returning the field type, length in bytes,
+/// number of implied decimals and whether it is signed.
+fn parse_picture(picture: &str) -> Option<(FieldType, usize, usize, bool)> {
+    // expand repetitions, e.g. 9(5)V99 => 99999V99
+    let mut expanded = String::new();
+    let mut chars = picture.chars();
+    while let Some(c) = chars.next() {
+        if c == '(' {
+            let count: String = chars.by_ref().take_while(|&c| c != ')').collect();
+            let count = count.parse::<usize>().ok().filter(|&n| n > 0)?;
+            let last = expanded.chars().last()?;
+            expanded.extend(std::iter::repeat_n(last, count - 1));
+        } else {
+            expanded.push(c.to_ascii_uppercase());
+        }
+    }
+
+    let (signed, expanded) = match expanded.strip_prefix('S') {
+        Some(rest) => (true, rest),
+        None => (false, expanded.as_str()),
+    };
+    if expanded.is_empty() || !expanded.chars().all(|c| matches!(c, 'X' | 'A' | '9' | 'V')) {
+        return None;
+    }
+
+    if expanded.contains(['X', 'A']) {
+        if signed || expanded.contains('V') {
+            return None;
+        }
+        return Some((FieldType::String, expanded.len(), 0, false));
+    }
+    let (integer, fraction) = expanded.split_once('V').unwrap_or((expanded, ""));
+    if fraction.contains('V') {
+        return None;
+    }
+    let length = integer.len() + fraction.len();
+    if length == 0 {
+        return None;
+    }
+    let kind = if fraction.is_empty() {
+        FieldType::Integer
+    } else {
+        FieldType::Decimal
+    };
+    Some((kind, length, fraction.len(), signed))
+}
+
+/// Decodes a signed overpunch character into its digit and sign
+/// (true if negative).
+const fn decode_overpunch(c: char) -> Option<(char, bool)> {
+    match c {
+        '{' => Some(('0', false)),
+        'A'..='I' => Some(((c as u8 - b'A' + b'1') as char, false)),
+        '}' => Some(('0', true)),
+        'J'..='R' => Some(((c as u8 - b'J' + b'1') as char, true)),
+        _ => None,
+    }
+}
+
+const fn encode_overpunch(digit: u8, negative: bool) -> u8 {
+    match (digit, negative) {
+        (b'0', false) => b'{',
+        (b'0', true) => b'}',
+        (d, false) => d - b'1' + b'A',
+        (d, true) => d - b'1' + b'J',
+    }
+}
+
+/// Converts a fixed-width numeric field into a plain decimal number,
+/// applying its sign and implied decimals.
+fn decode_number(raw: &str, field: &Field) -> Result<String, String> {
+    let value = raw.trim();
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+
+    let (mut negative, unsigned) = if let Some(v) = value.strip_prefix('-') {
+        (true, v)
+    } else if let Some(v) = value.strip_suffix('-') {
+        (true, v)
+    } else if let Some(v) = value.strip_prefix('+').or_else(|| value.strip_suffix('+')) {
+        (false, v)
+    } else {
+        (false, value)
+    };
+    let mut digits = unsigned.trim().to_string();
+    if field.overpunch && unsigned.len() == value.len() {
+        if let Some((digit, is_negative)) = digits.chars().last().and_then(decode_overpunch) {
+            digits.pop();
+            digits.push(digit);
+            negative = is_negative;
+        }
+    }
+    if digits.is_empty() {
+        return Err(format!("invalid numeric value \"{raw}\""));
+    }
+
+    let explicit_point = field.kind == FieldType::Decimal && field.decimals == 0;
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) if explicit_point => (integer.to_string(), fraction.to_string()),
+        Some(_) => return Err(format!("invalid numeric value \"{raw}\"")),
+        None if field.decimals > 0 => {
+            let padded = format!("{digits:0>width$}", width = field.decimals + 1);
+            let (integer, fraction) = padded.split_at(padded.len() - field.decimals);
+            (integer.to_string(), fraction.to_string())
+        },
+        None => (digits, String::new()),
+    };
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(format!("invalid numeric value \"{raw}\""));
+    }
+    if negative && !field.signed {
+        return Err(format!("negative value \"{raw}\" in an unsigned field"));
+    }
+
+    let integer = integer.trim_start_matches('0');
+    let is_zero = integer.is_empty() && fraction.bytes().all(|b| b == b'0');
+    let mut out = String::with_capacity(integer.len() + fraction.len() + 3);
+    if negative && !is_zero {
+        out.push('-');
+    }
+    out.push_str(if integer.is_empty() { "0" } else { integer });
+    if !fraction.is_empty() {
+        out.push('.');
+        out.push_str(&fraction);
+    }
+    Ok(out)
+}
+
+/// Writes a string left-aligned into its field, padding with `pad` and
+/// truncating (at a character boundary) unless `strict` is set.
+fn encode_string(value: &str, target: &mut [u8], pad: u8, strict: bool) -> Result<(), String> {
+    let mut end = value.len().min(target.len());
+    if end < value.len() {
+        if strict {
+            return Err(format!(
+                "value \"{value}\" is longer than the field width of {}",
+                target.len()
+            ));
+        }
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+    }
+    target[..end].copy_from_slice(&value.as_bytes()[..end]);
+    target[end..].fill(pad);
+    Ok(())
+}
+
+/// Formats a number for a fixed-width numeric field: right-aligned and
+/// zero-padded, with implied decimals and a leading "-" or overpunch sign.
+fn encode_number(value: &str, field: &Field) -> Result<String, String> {
+    let width = field.length;
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(" ".repeat(width));
+    }
+
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(format!("invalid numeric value \"{value}\""));
+    }
+    if negative && !field.signed {
+        return Err(format!("negative value \"{value}\" in an unsigned field"));
+    }
+    let too_wide = || format!("value \"{value}\" does not fit in a field of width {width}");
+
+    if field.kind == FieldType::Decimal && field.decimals == 0 {
+        // no implied decimals, so keep the decimal point
+        let sign_width = usize::from(negative);
+        if unsigned.len() + sign_width > width {
+            return Err(too_wide());
+        }
+        let mut out = format!("{unsigned:0>width$}", width = width - sign_width);
+        if negative {
+            out.insert(0, '-');
+        }
+        return Ok(out);
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > field.decimals {
+        return Err(format!(
+            "value \"{value}\" has more than {} decimal places",
+            field.decimals
+        ));
+    }
+    let digits = format!(
+        "{}{fraction:0<decimals$}",
+        integer.trim_start_matches('0'),
+        decimals = field.decimals
+    );
+
+    let overpunch = field.overpunch && field.signed;
+    let sign_width = usize::from(negative && !overpunch);
+    if digits.len() + sign_width > width {
+        return Err(too_wide());
+    }
+    let mut out = format!("{digits:0>width$}", width = width - sign_width).into_bytes();
+    if overpunch {
+        let is_zero = out.iter().all(|&b| b == b'0');
+        // safety: width is always > 0
+        let last = out.last_mut().unwrap();
+        *last = encode_overpunch(*last, negative && !is_zero);
+    } else if negative {
+        out.insert(0, b'-');
+    }
+    // safety: out only contains ASCII characters
+    Ok(String::from_utf8(out).unwrap())
+}
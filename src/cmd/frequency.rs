@@ -131,6 +131,7 @@ use threadpool::ThreadPool;
 use crate::{
     config::{Config, Delimiter},
     index::Indexed,
+    seekable::InputFile,
     select::{SelectColumns, Selection},
     util,
     util::{get_stats_records, ByteString, StatsMode},
@@ -377,7 +378,7 @@ impl Args {
 
     pub fn parallel_ftables(
         &self,
-        idx: &Indexed<fs::File, fs::File>,
+        idx: &Indexed<InputFile, fs::File>,
     ) -> CliResult<(Headers, FTables)> {
         let mut rdr = self.rconfig().reader()?;
         let (headers, sel) = self.sel_headers(&mut rdr)?;
@@ -12,6 +12,12 @@ However, if the environment variable QSV_AUTOINDEX is set, qsv will automaticall
 create an index when none is detected, and stale indices will be automatically
 updated as well.
 
+Compressed files cannot be indexed, with the exception of block-compressed files:
+BGZF files (as created by `bgzip`, with a .gz extension) and zstd files in the
+seekable format (as created by `zstd --seekable` or `t2sz`, with a .zst extension).
+Their index records virtual offsets into the compressed blocks, so commands can
+jump to any row by only decompressing the block that holds it.
+
 Usage:
     qsv index [options] <input>
     qsv index --help
@@ -36,7 +42,7 @@ use serde::Deserialize;
 
 use crate::{
     config::{Config, DEFAULT_WTR_BUFFER_CAPACITY},
-    util, CliResult,
+    seekable, util, CliResult,
 };
 
 #[derive(Deserialize)]
@@ -48,8 +54,15 @@ struct Args {
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    if args.arg_input.to_lowercase().ends_with(".sz") {
-        return fail_incorrectusage_clierror!("Cannot index a snappy file.");
+    let rconfig = Config::new(Some(&args.arg_input));
+    let block_rdr = rconfig.block_reader()?;
+    let compression = rconfig.compression();
+    if compression.is_compressed() && block_rdr.is_none() {
+        return fail_incorrectusage_clierror!(
+            "Cannot index a {} compressed file, unless it is block-compressed (BGZF or seekable \
+             zstd).",
+            compression.name()
+        );
     }
 
     let pidx = match args.flag_output {
@@ -57,11 +70,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Some(p) => PathBuf::from(&p),
     };
 
-    let rconfig = Config::new(Some(args.arg_input).as_ref());
-    let mut rdr = rconfig.reader_file()?;
     let mut wtr =
         io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, fs::File::create(pidx)?);
-    RandomAccessSimple::create(&mut rdr, &mut wtr)?;
+    if let Some(block_rdr) = block_rdr {
+        seekable::create_index(&mut rconfig.from_reader(block_rdr), &mut wtr)?;
+    } else {
+        let mut rdr = rconfig.reader_file()?;
+        RandomAccessSimple::create(&mut rdr, &mut wtr)?;
+    }
     io::Write::flush(&mut wtr)?;
 
     Ok(())
@@ -6,18 +6,24 @@ Generally, all qsv commands support basic options like specifying the delimiter
 used in CSV data. However, this does not cover all possible types of CSV data. For
 example, some CSV files don't use '"' for quotes or use different escaping styles.
 
+Files with multi-character field delimiters (e.g. "||" or "~|~") and custom record
+terminators (e.g. "\x1e" or ";;\n") can also be read with the --delimiter and
+--terminator options. Fields containing commas, quotes or newlines are then
+properly quoted in the normalized output.
+
 Also, CSVs with preamble lines can have them skipped with the --skip-lines & --auto-skip
 options. Similarly, --skip-lastlines allows epilogue lines to be skipped.
 
 Finally, non UTF-8 encoded files are "lossy" saved to UTF-8 by default, replacing all
 invalid UTF-8 sequences with �. Note though that this is not true transcoding.
-
-If you need to properly transcode non UTF-8 files, you'll need to use a tool like `iconv`
-before processing it with qsv - e.g. to convert an ISO-8859-1 encoded file to UTF-8:
-    `iconv -f ISO-8859-1 -t UTF-8 input.csv -o utf8_output.csv`.
-
 You can change this behavior with the --encoding-errors option.
 
+To properly transcode non UTF-8 files (e.g. Windows-1252, Latin-1, Shift-JIS or UTF-16),
+use the --from-encoding option. It transcodes the input to UTF-8 as it is read, either
+from a given encoding, or with "auto", from the encoding detected from a BOM or, failing
+that, statistically from the first 64KB of the input. Use `qsv sniff` to check the
+detected encoding and its confidence beforehand.
+
 See https://github.com/dathere/qsv#utf-8-encoding for more details.
 
 This command is typically used at the beginning of a data pipeline (thus the name `input`)
@@ -59,26 +65,44 @@ input options:
                                   skip: Fields with encoding errors are "<SKIPPED>".
                                 strict: Fail on any encoding errors.
                              [default: replace]
+                             When transcoding with --from-encoding, sequences that are
+                             invalid in the source encoding are handled the same way.
+    --from-encoding <enc>    Transcode the input from this character encoding to UTF-8.
+                             Either a WHATWG encoding label (e.g. windows-1252, latin1,
+                             shift_jis, utf-16le, utf-16be) or "auto" to detect it.
+                             A BOM takes precedence over the given encoding.
+    --terminator <arg>       The record terminator of the input. Can be several
+                             characters, with \n, \r, \t, \\ and \xHH escapes
+                             (e.g. "\x1e" or ";;\n"). When not set, records end
+                             with LF, CRLF or CR.
 
 Common options:
     -h, --help               Display this message
     -o, --output <file>      Write output to <file> instead of stdout.
     -d, --delimiter <arg>    The field delimiter for reading CSV data.
-                             Must be a single character. (default: ,)
+                             Can be several characters (e.g. "||" or "~|~"),
+                             with the same escapes as --terminator.
+                             (default: ,)
 "#;
 
-use std::{env, str::FromStr};
+use std::{
+    env,
+    io::{self, Read},
+    str::FromStr,
+};
 
+#[cfg(feature = "encoding")]
+use encoding_rs::Encoding;
 use log::{debug, info, warn};
 use serde::Deserialize;
 use strum_macros::EnumString;
 
 use crate::{
-    config::{Config, Delimiter},
+    config::{Config, Delimiter, DEFAULT_RDR_BUFFER_CAPACITY},
     util, CliResult,
 };
 
-#[derive(EnumString, Clone, Copy)]
+#[derive(EnumString, Clone, Copy, PartialEq)]
 #[strum(ascii_case_insensitive)]
 #[allow(non_camel_case_types)]
 enum EncodingHandling {
@@ -91,7 +115,7 @@ enum EncodingHandling {
 struct Args {
     arg_input:            Option<String>,
     flag_output:          Option<String>,
-    flag_delimiter:       Option<Delimiter>,
+    flag_delimiter:       Option<String>,
     flag_quote:           Delimiter,
     flag_escape:          Option<Delimiter>,
     flag_no_quoting:      bool,
@@ -103,6 +127,8 @@ struct Args {
     flag_trim_fields:     bool,
     flag_comment:         Option<char>,
     flag_encoding_errors: String,
+    flag_from_encoding:   Option<String>,
+    flag_terminator:      Option<String>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -126,6 +152,26 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         std::env::set_var("QSV_SNIFF_PREAMBLE", "1");
     }
 
+    // a multi-character delimiter or a custom record terminator can't be
+    // expressed with the CSV parser, so we split records ourselves
+    let (delimiter, multichar_delimiter) = match args.flag_delimiter.as_deref() {
+        None => (None, None),
+        Some(delim) => match Delimiter::decode_delimiter(delim) {
+            Ok(delimiter) => (Some(delimiter), None),
+            Err(_) => match unescape(delim)?.as_slice() {
+                [] => return fail_incorrectusage_clierror!("--delimiter cannot be empty."),
+                [byte] => (Some(Delimiter(*byte)), None),
+                bytes => (None, Some(bytes.to_vec())),
+            },
+        },
+    };
+    let record_terminator = match args.flag_terminator.as_deref().map(unescape) {
+        Some(Ok(terminator)) if terminator.is_empty() => {
+            return fail_incorrectusage_clierror!("--terminator cannot be empty.")
+        },
+        terminator => terminator.transpose()?,
+    };
+
     let comment_char: Option<u8> = if let Ok(cmt_char) = env::var("QSV_COMMENT_CHAR") {
         Some(cmt_char.as_bytes().first().unwrap().to_owned())
     } else {
@@ -133,7 +179,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
 
     let mut rconfig = Config::new(args.arg_input.as_ref())
-        .delimiter(args.flag_delimiter)
+        .delimiter(delimiter)
         .no_headers(true)
         .quote(args.flag_quote.as_byte())
         .comment(comment_char)
@@ -167,11 +213,51 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         rconfig = rconfig.flexible(true);
     }
 
+    let splitter = if multichar_delimiter.is_some() || record_terminator.is_some() {
+        if args.flag_auto_skip {
+            return fail_incorrectusage_clierror!(
+                "--auto-skip cannot be used with a multi-character delimiter or a custom record \
+                 terminator. Use --skip-lines instead."
+            );
+        }
+        Some(SplitDialect {
+            delimiter:  multichar_delimiter.unwrap_or_else(|| vec![rconfig.get_delimiter()]),
+            terminator: record_terminator,
+            quote:      (!args.flag_no_quoting).then_some(args.flag_quote.as_byte()),
+            escape:     args.flag_escape.map(Delimiter::as_byte),
+            comment:    comment_char,
+            trim:       matches!(trim_setting, csv::Trim::Fields | csv::Trim::All),
+        })
+    } else {
+        None
+    };
+
     let mut total_lines = 0_u64;
     if let Some(skip_llines) = args.flag_skip_lastlines {
-        // use the regular count_rows to get the row_count
-        // as Polars doesn't support skipping last lines
-        let row_count = util::count_rows_regular(&rconfig)?;
+        let row_count = if args.flag_from_encoding.is_some() || splitter.is_some() {
+            // count the transcoded/split rows, as they may not match the raw CSV rows
+            if rconfig.is_stdin() {
+                return fail_incorrectusage_clierror!(
+                    "--skip-lastlines cannot be used on <stdin> with --from-encoding, a \
+                     multi-character delimiter or a custom record terminator."
+                );
+            }
+            let (mut rdr, _) = open_records(
+                &rconfig,
+                args.flag_from_encoding.as_deref(),
+                splitter.as_ref(),
+            )?;
+            let mut record = csv::ByteRecord::new();
+            let mut count = 0_u64;
+            while rdr.read_byte_record(&mut record)? {
+                count += 1;
+            }
+            count
+        } else {
+            // use the regular count_rows to get the row_count
+            // as Polars doesn't support skipping last lines
+            util::count_rows_regular(&rconfig)?
+        };
         if skip_llines > row_count {
             return fail_incorrectusage_clierror!(
                 "--skip-lastlines: {skip_llines} is greater than row_count: {row_count}."
@@ -181,7 +267,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         total_lines = row_count.saturating_sub(skip_llines);
     }
 
-    let mut rdr = rconfig.reader()?;
+    let (mut rdr, transcoded) = open_records(
+        &rconfig,
+        args.flag_from_encoding.as_deref(),
+        splitter.as_ref(),
+    )?;
     let mut wtr = wconfig.writer()?;
     let mut row = csv::ByteRecord::new();
     let mut str_row = csv::StringRecord::new();
@@ -238,10 +328,17 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         str_row.clear();
         for field in &row {
-            if let Ok(utf8_field) = simdutf8::basic::from_utf8(field) {
-                str_row.push_field(utf8_field);
-            } else {
-                match encode_handler {
+            match simdutf8::basic::from_utf8(field) {
+                // when transcoding, invalid sequences in the source encoding
+                // were already replaced with U+FFFD
+                Ok(utf8_field)
+                    if !(transcoded
+                        && encode_handler != EncodingHandling::Replace
+                        && utf8_field.contains('\u{FFFD}')) =>
+                {
+                    str_row.push_field(utf8_field);
+                },
+                _ => match encode_handler {
                     EncodingHandling::Replace => {
                         lossy_field = String::from_utf8_lossy(field);
                         str_row.push_field(&lossy_field);
@@ -264,7 +361,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             "STRICT. Invalid UTF8 - row {idx} in \"{lossy_field}\"."
                         );
                     },
-                }
+                },
             };
         }
         wtr.write_record(&str_row)?;
@@ -292,3 +389,279 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     info!("Wrote {} rows...", idx - 1);
     Ok(wtr.flush()?)
 }
+
+/// The source of input records - the CSV parser, or a `RecordSplitter` for
+/// dialects the CSV parser can't express.
+enum Records {
+    Csv(csv::Reader<Box<dyn Read + Send>>),
+    Split(RecordSplitter<Box<dyn Read + Send>>),
+}
+
+impl Records {
+    fn read_byte_record(&mut self, record: &mut csv::ByteRecord) -> CliResult<bool> {
+        match self {
+            Records::Csv(rdr) => Ok(rdr.read_byte_record(record)?),
+            Records::Split(rdr) => Ok(rdr.read_byte_record(record)?),
+        }
+    }
+}
+
+/// Opens the input records, transcoding the input first if `from_encoding`
+/// is set. Returns the records and whether the input is being transcoded.
+fn open_records(
+    rconfig: &Config,
+    from_encoding: Option<&str>,
+    splitter: Option<&SplitDialect>,
+) -> CliResult<(Records, bool)> {
+    let (rdr, transcoded) = match (from_encoding, splitter) {
+        (Some(label), _) => open_transcoded(rconfig, label)?,
+        (None, Some(_)) => (rconfig.io_reader()?, false),
+        (None, None) => return Ok((Records::Csv(rconfig.reader()?), false)),
+    };
+    let records = match splitter {
+        Some(dialect) => Records::Split(RecordSplitter::new(rdr, dialect.clone())),
+        None => Records::Csv(rconfig.from_reader(rdr)),
+    };
+    Ok((records, transcoded))
+}
+
+/// Opens the input, transcoding it to UTF-8 from the encoding named by `label`.
+/// With "auto", the encoding is detected from a sample of the input.
+/// Returns the reader and whether it is being transcoded - UTF-8 input is
+/// passed through as is, so --encoding-errors still applies to it.
+#[cfg(feature = "encoding")]
+fn open_transcoded(rconfig: &Config, label: &str) -> CliResult<(Box<dyn Read + Send>, bool)> {
+    let rdr = rconfig.io_reader()?;
+    let (encoding, rdr): (&'static Encoding, Box<dyn Read + Send>) =
+        if label.eq_ignore_ascii_case("auto") {
+            let (detected, rdr) = util::sniff_encoding(rdr)?;
+            info!(
+                "detected {} encoding (confidence: {}{})",
+                detected.encoding.name(),
+                detected.confidence,
+                if detected.bom { ", BOM" } else { "" }
+            );
+            (detected.encoding, Box::new(rdr))
+        } else if let Some(encoding) = Encoding::for_label(label.trim().as_bytes()) {
+            (encoding, rdr)
+        } else {
+            return fail_incorrectusage_clierror!(
+                "Unknown --from-encoding: \"{label}\". Use a WHATWG encoding label (e.g. \
+                 windows-1252, shift_jis, utf-16le) or \"auto\"."
+            );
+        };
+
+    if encoding == encoding_rs::UTF_8 {
+        return Ok((rdr, false));
+    }
+    info!("transcoding from {} to UTF-8...", encoding.name());
+    Ok((util::transcode_reader(rdr, encoding), true))
+}
+
+#[cfg(not(feature = "encoding"))]
+fn open_transcoded(_rconfig: &Config, _label: &str) -> CliResult<(Box<dyn Read + Send>, bool)> {
+    fail_incorrectusage_clierror!(
+        "--from-encoding requires qsv to be built with the \"encoding\" feature."
+    )
+}
+
+/// Decodes the \n, \r, \t, \\ and \xHH escapes of a delimiter or record terminator.
+fn unescape(s: &str) -> CliResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0_u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                if hex.len() != 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return fail_incorrectusage_clierror!("Invalid \\x escape in \"{s}\".");
+                }
+                // safety: we just checked that hex is two hex digits
+                bytes.push(u8::from_str_radix(&hex, 16).unwrap());
+            },
+            _ => return fail_incorrectusage_clierror!("Invalid escape sequence in \"{s}\"."),
+        }
+    }
+    Ok(bytes)
+}
+
+/// The dialect of input that `RecordSplitter` reads.
+#[derive(Clone)]
+struct SplitDialect {
+    /// the field delimiter, one or more bytes
+    delimiter:  Vec<u8>,
+    /// the record terminator. If None, records end with LF, CRLF or CR.
+    terminator: Option<Vec<u8>>,
+    /// the quote character, if quoting is enabled
+    quote:      Option<u8>,
+    /// the escape character for quotes. If None, quotes are escaped by doubling them.
+    escape:     Option<u8>,
+    comment:    Option<u8>,
+    /// trim the whitespace around fields
+    trim:       bool,
+}
+
+/// Splits a stream into records with multi-byte field delimiters and custom
+/// record terminators, honoring quoted fields the same way the CSV parser does.
+/// Like the CSV parser, it skips empty records.
+struct RecordSplitter<R> {
+    rdr:     R,
+    dialect: SplitDialect,
+    buf:     Vec<u8>,
+    pos:     usize,
+    eof:     bool,
+    field:   Vec<u8>,
+}
+
+impl<R: Read> RecordSplitter<R> {
+    fn new(rdr: R, dialect: SplitDialect) -> Self {
+        Self {
+            rdr,
+            dialect,
+            buf: Vec::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY),
+            pos: 0,
+            eof: false,
+            field: Vec::new(),
+        }
+    }
+
+    /// Makes at least `n` unread bytes available in the buffer, unless the
+    /// stream ends first. Returns the number of unread bytes.
+    fn fill(&mut self, n: usize) -> io::Result<usize> {
+        while self.buf.len() - self.pos < n && !self.eof {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+            let len = self.buf.len();
+            self.buf.resize(len + DEFAULT_RDR_BUFFER_CAPACITY, 0);
+            let read = self.rdr.read(&mut self.buf[len..])?;
+            self.buf.truncate(len + read);
+            self.eof = read == 0;
+        }
+        Ok(self.buf.len() - self.pos)
+    }
+
+    /// Consumes a field delimiter, if one is next.
+    fn delimiter(&mut self) -> io::Result<bool> {
+        let len = self.dialect.delimiter.len();
+        if self.fill(len)? >= len && self.buf[self.pos..].starts_with(&self.dialect.delimiter) {
+            self.pos += len;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Consumes a record terminator, if one is next.
+    fn terminator(&mut self) -> io::Result<bool> {
+        if let Some(len) = self.dialect.terminator.as_ref().map(Vec::len) {
+            let matched = self.fill(len)? >= len
+                && self
+                    .dialect
+                    .terminator
+                    .as_ref()
+                    .is_some_and(|terminator| self.buf[self.pos..].starts_with(terminator));
+            if matched {
+                self.pos += len;
+            }
+            return Ok(matched);
+        }
+        match self.buf.get(self.pos) {
+            Some(b'\n') => self.pos += 1,
+            Some(b'\r') => {
+                self.pos += 1;
+                if self.fill(1)? > 0 && self.buf[self.pos] == b'\n' {
+                    self.pos += 1;
+                }
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Skips the rest of the record, ignoring quotes.
+    fn skip_record(&mut self) -> io::Result<()> {
+        while self.fill(1)? > 0 {
+            if self.terminator()? {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    /// Reads a quoted field's content, after its opening quote.
+    fn read_quoted(&mut self, quote: u8) -> io::Result<()> {
+        while self.fill(2)? > 0 {
+            let byte = self.buf[self.pos];
+            let next = self.buf.get(self.pos + 1).copied();
+            if Some(byte) == self.dialect.escape && next == Some(quote) {
+                self.field.push(quote);
+                self.pos += 2;
+            } else if byte == quote {
+                if self.dialect.escape.is_none() && next == Some(quote) {
+                    self.field.push(quote);
+                    self.pos += 2;
+                } else {
+                    self.pos += 1;
+                    return Ok(());
+                }
+            } else {
+                self.field.push(byte);
+                self.pos += 1;
+            }
+        }
+        // like the CSV parser, an unterminated quoted field ends at the end of the input
+        Ok(())
+    }
+
+    fn read_byte_record(&mut self, record: &mut csv::ByteRecord) -> io::Result<bool> {
+        loop {
+            record.clear();
+            if self.fill(1)? == 0 {
+                return Ok(false);
+            }
+            if self.terminator()? {
+                // an empty record
+                continue;
+            }
+            if self.dialect.comment == Some(self.buf[self.pos]) {
+                self.skip_record()?;
+                continue;
+            }
+
+            self.field.clear();
+            let mut field_start = true;
+            loop {
+                if self.fill(1)? == 0 || self.terminator()? {
+                    record.push_field(&self.field);
+                    break;
+                }
+                if self.delimiter()? {
+                    record.push_field(&self.field);
+                    self.field.clear();
+                    field_start = true;
+                    continue;
+                }
+                let byte = self.buf[self.pos];
+                self.pos += 1;
+                match self.dialect.quote {
+                    Some(quote) if field_start && byte == quote => self.read_quoted(quote)?,
+                    _ => self.field.push(byte),
+                }
+                field_start = false;
+            }
+            if self.dialect.trim {
+                record.trim();
+            }
+            return Ok(true);
+        }
+    }
+}
@@ -0,0 +1,506 @@
+static USAGE: &str = r#"
+Discover the columns that identify a row (candidate keys) and the columns that
+determine other columns (functional dependencies) of a CSV.
+
+A candidate key is a minimal combination of up to --max-key-size columns whose combined
+values are unique across all rows. Minimal means that no subset of its columns is also
+unique - e.g. if "id" is unique, "id,name" is not reported.
+
+A functional dependency A -> B holds if each value of column A always occurs with the
+same value of column B (e.g. zip -> city). As real-world data is messy, approximate
+dependencies are also reported, with a confidence score - the fraction of rows whose
+B value is the most common B value for their A value. Only rows whose A value occurs
+more than once are counted, as a unique A value trivially determines every column.
+This row count is reported as the support. Columns that are unique by themselves
+determine every column, so they are not reported as determinants.
+
+The column values are loaded into memory. The cardinalities from the stats cache (see
+--stats-mode) are used to skip constant columns before loading, and to shortlist the
+columns with the highest cardinalities as key candidates (see --max-candidates).
+
+The output is a CSV with the columns kind, columns, dependent, confidence & support.
+"kind" is either "uniqueKey" or "functionalDependency". "columns" is the comma-separated
+list of key columns, or the determinant column of a functional dependency.
+
+With --json, the output is a JSON object with the properties:
+  rowcount               - the number of rows
+  primaryKey             - the first candidate key without empty values, with the
+                           fewest columns. It can be used as the "primaryKey" of a
+                           Frictionless Table Schema.
+  uniqueKey              - all the candidate keys, as the `uniqueKey` keyword
+                           checked by `qsv validate`.
+  functionalDependencies - an array of objects with the properties determinant,
+                           dependent, confidence & support.
+
+For examples, see https://github.com/dathere/qsv/blob/master/tests/test_keys.rs.
+
+Usage:
+    qsv keys [options] [<input>]
+    qsv keys --help
+
+keys options:
+    -s, --select <arg>        Select the columns to consider.
+                              See 'qsv select --help' for the format details.
+    -k, --max-key-size <n>    The maximum number of columns of a candidate key.
+                              [default: 3]
+    --max-candidates <n>      Only the <n> columns with the highest cardinalities
+                              are considered as key columns.
+                              [default: 20]
+    --allow-nulls             Consider columns with empty values as key columns.
+                              Empty values are compared like any other value.
+    --min-confidence <n>      The minimum confidence of a reported functional
+                              dependency, from 0.0 to 1.0. Set to 1.0 to only report
+                              exact dependencies.
+                              [default: 0.95]
+    --no-fds                  Do not look for functional dependencies.
+    --stats-mode <arg>        The stats mode to use to get column cardinalities.
+                              There are three modes:
+                                auto: use the stats cache if it already exists.
+                                force: force stats calculation. If the stats cache
+                                       does not exist, it will be created.
+                                none: don't use the stats cache.
+                              [default: auto]
+    --json                    Write the keys & dependencies as JSON instead of CSV.
+    --pretty-json             Same as --json but in pretty JSON format.
+    -j, --jobs <arg>          The number of jobs to run in parallel.
+                              When not set, the number of jobs is set to the
+                              number of CPUs detected.
+
+Common options:
+    -h, --help                Display this message
+    -o, --output <file>       Write output to <file> instead of stdout.
+    -n, --no-headers          When set, the first row will not be interpreted
+                              as headers. Columns are then named by their
+                              1-based index.
+    -d, --delimiter <arg>     The field delimiter for reading CSV data.
+                              Must be a single character. (default: ,)
+    --memcheck                Check if there is enough memory to load the entire
+                              CSV into memory using CONSERVATIVE heuristics.
+"#;
+
+use std::{cmp::Reverse, io::Write};
+
+use ahash::{AHashMap, AHashSet};
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util::{self, get_stats_records, StatsMode},
+    CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:           Option<String>,
+    flag_select:         SelectColumns,
+    flag_max_key_size:   usize,
+    flag_max_candidates: usize,
+    flag_allow_nulls:    bool,
+    flag_min_confidence: f64,
+    flag_no_fds:         bool,
+    flag_stats_mode:     String,
+    flag_json:           bool,
+    flag_pretty_json:    bool,
+    flag_jobs:           Option<usize>,
+    flag_output:         Option<String>,
+    flag_no_headers:     bool,
+    flag_delimiter:      Option<Delimiter>,
+    flag_memcheck:       bool,
+}
+
+/// a dictionary-encoded column - each distinct value is replaced by a dense id
+struct Column {
+    name:        String,
+    ids:         Vec<u32>,
+    cardinality: usize,
+    nullcount:   u64,
+}
+
+#[derive(Serialize)]
+struct FunctionalDependency {
+    determinant: String,
+    dependent:   String,
+    confidence:  f64,
+    support:     u64,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_max_key_size == 0 {
+        return fail_incorrectusage_clierror!("--max-key-size must be at least 1.");
+    }
+    if !(0.0..=1.0).contains(&args.flag_min_confidence) {
+        return fail_incorrectusage_clierror!("--min-confidence must be between 0.0 and 1.0.");
+    }
+    let stats_mode = match args.flag_stats_mode.as_str() {
+        "auto" => StatsMode::Frequency,
+        "force" => StatsMode::FrequencyForceStats,
+        "none" => StatsMode::None,
+        _ => return fail_incorrectusage_clierror!("Invalid stats mode"),
+    };
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.flag_select.clone());
+
+    // we're loading the column values into memory, we need to check avail mem
+    if let Some(path) = rconfig.path.clone() {
+        util::mem_file_check(&path, false, args.flag_memcheck)?;
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let mut sel_cols = rconfig.selection(&headers)?.to_vec();
+    sel_cols.sort_unstable();
+    sel_cols.dedup();
+
+    let names: Vec<String> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            if args.flag_no_headers {
+                (i + 1).to_string()
+            } else {
+                String::from_utf8_lossy(name).to_string()
+            }
+        })
+        .collect();
+
+    let stats = stats_cardinalities(&args, stats_mode, headers.len())?;
+    let shortlist = shortlist_columns(&args, &sel_cols, stats.as_deref());
+    let (columns, rowcount) = load_columns(&mut rdr, &names, &shortlist)?;
+
+    util::njobs(args.flag_jobs);
+    let keys = find_keys(&args, &columns, rowcount);
+    let fds = if args.flag_no_fds {
+        Vec::new()
+    } else {
+        find_functional_dependencies(&columns, rowcount, args.flag_min_confidence)
+    };
+
+    let key_names: Vec<Vec<&str>> = keys
+        .iter()
+        .map(|key| key.iter().map(|&c| columns[c].name.as_str()).collect())
+        .collect();
+
+    if args.flag_json || args.flag_pretty_json {
+        // single column keys are written as a column name, composite keys as an array,
+        // the same as the uniqueKey keyword of validate
+        let unique_keys: Vec<Value> = key_names
+            .iter()
+            .map(|names| {
+                if names.len() == 1 {
+                    json!(names[0])
+                } else {
+                    json!(names)
+                }
+            })
+            .collect();
+        let primary_key = keys
+            .iter()
+            .position(|key| key.iter().all(|&c| columns[c].nullcount == 0))
+            .map_or(Value::Null, |i| unique_keys[i].clone());
+        let keys_json = json!({
+            "rowcount": rowcount,
+            "primaryKey": primary_key,
+            "uniqueKey": unique_keys,
+            "functionalDependencies": &fds,
+        });
+        let json_output = if args.flag_pretty_json {
+            serde_json::to_string_pretty(&keys_json)?
+        } else {
+            serde_json::to_string(&keys_json)?
+        };
+        let mut out: Box<dyn Write> = match args.flag_output {
+            Some(ref output) => Box::new(std::fs::File::create(output)?),
+            None => Box::new(std::io::stdout()),
+        };
+        writeln!(out, "{json_output}")?;
+        out.flush()?;
+    } else {
+        let rowcount_str = rowcount.to_string();
+        let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+        wtr.write_record(["kind", "columns", "dependent", "confidence", "support"])?;
+        for names in &key_names {
+            wtr.write_record([
+                "uniqueKey",
+                names.join(",").as_str(),
+                "",
+                "1",
+                rowcount_str.as_str(),
+            ])?;
+        }
+        for fd in &fds {
+            wtr.write_record([
+                "functionalDependency",
+                fd.determinant.as_str(),
+                fd.dependent.as_str(),
+                fd.confidence.to_string().as_str(),
+                fd.support.to_string().as_str(),
+            ])?;
+        }
+        wtr.flush()?;
+    }
+
+    winfo!(
+        "{} candidate key/s and {} functional dependencies found in {rowcount} rows.",
+        keys.len(),
+        fds.len()
+    );
+
+    Ok(())
+}
+
+/// gets the (cardinality, nullcount) of every column from the stats cache.
+/// Returns None if the stats are not available.
+fn stats_cardinalities(
+    args: &Args,
+    stats_mode: StatsMode,
+    num_fields: usize,
+) -> CliResult<Option<Vec<(u64, u64)>>> {
+    let schema_args = util::SchemaArgs {
+        flag_enum_threshold:  0,
+        flag_ignore_case:     false,
+        flag_strict_dates:    false,
+        flag_pattern_columns: SelectColumns::parse("")?,
+        flag_dates_whitelist: String::new(),
+        flag_prefer_dmy:      false,
+        flag_force:           false,
+        flag_stdout:          false,
+        flag_jobs:            Some(util::njobs(args.flag_jobs)),
+        flag_no_headers:      args.flag_no_headers,
+        flag_delimiter:       args.flag_delimiter,
+        arg_input:            args.arg_input.clone(),
+        flag_memcheck:        false,
+    };
+    let (csv_fields, csv_stats) = get_stats_records(&schema_args, stats_mode)?;
+
+    // stats cache files created without --cardinality have zero cardinalities
+    if csv_fields.len() != num_fields
+        || csv_stats.len() != num_fields
+        || csv_stats.iter().all(|stats| stats.cardinality == 0)
+    {
+        return Ok(None);
+    }
+    Ok(Some(
+        csv_stats
+            .iter()
+            .map(|stats| (stats.cardinality, stats.nullcount))
+            .collect(),
+    ))
+}
+
+/// picks the columns to load. Constant columns can neither be part of a minimal key
+/// nor a meaningful dependency, so they're skipped. With --no-fds, only the key
+/// candidates are loaded.
+fn shortlist_columns(args: &Args, sel_cols: &[usize], stats: Option<&[(u64, u64)]>) -> Vec<usize> {
+    let Some(stats) = stats else {
+        return sel_cols.to_vec();
+    };
+
+    let mut shortlist: Vec<usize> = sel_cols
+        .iter()
+        .copied()
+        .filter(|&i| stats[i].0 > 1)
+        .collect();
+    if args.flag_no_fds {
+        shortlist.retain(|&i| args.flag_allow_nulls || stats[i].1 == 0);
+        // sort_by_key is stable, so columns with the same cardinality stay in column order
+        shortlist.sort_by_key(|&i| Reverse(stats[i].0));
+        shortlist.truncate(args.flag_max_candidates);
+        shortlist.sort_unstable();
+    }
+    shortlist
+}
+
+/// reads the values of the shortlisted columns, dictionary-encoding them.
+/// Returns the columns and the row count.
+#[allow(clippy::cast_possible_truncation)]
+fn load_columns<R: std::io::Read>(
+    rdr: &mut csv::Reader<R>,
+    names: &[String],
+    shortlist: &[usize],
+) -> CliResult<(Vec<Column>, usize)> {
+    let mut columns: Vec<Column> = shortlist
+        .iter()
+        .map(|&i| Column {
+            name:        names[i].clone(),
+            ids:         Vec::new(),
+            cardinality: 0,
+            nullcount:   0,
+        })
+        .collect();
+    let mut dictionaries: Vec<AHashMap<Vec<u8>, u32>> = vec![AHashMap::new(); shortlist.len()];
+
+    let mut record = csv::ByteRecord::new();
+    let mut rowcount = 0_usize;
+    while rdr.read_byte_record(&mut record)? {
+        rowcount += 1;
+        for ((column, dictionary), &i) in columns
+            .iter_mut()
+            .zip(dictionaries.iter_mut())
+            .zip(shortlist)
+        {
+            let field = record.get(i).unwrap_or_default();
+            if field.is_empty() {
+                column.nullcount += 1;
+            }
+            let id = if let Some(id) = dictionary.get(field) {
+                *id
+            } else {
+                let next_id = dictionary.len() as u32;
+                dictionary.insert(field.to_vec(), next_id);
+                next_id
+            };
+            column.ids.push(id);
+        }
+    }
+
+    for (column, dictionary) in columns.iter_mut().zip(&dictionaries) {
+        column.cardinality = dictionary.len();
+    }
+    Ok((columns, rowcount))
+}
+
+/// finds the minimal unique combinations of up to --max-key-size columns,
+/// by increasing number of columns. Returns the indices of the key columns.
+fn find_keys(args: &Args, columns: &[Column], rowcount: usize) -> Vec<Vec<usize>> {
+    let mut candidates: Vec<usize> = (0..columns.len())
+        .filter(|&c| {
+            columns[c].cardinality > 1 && (args.flag_allow_nulls || columns[c].nullcount == 0)
+        })
+        .collect();
+    candidates.sort_by_key(|&c| Reverse(columns[c].cardinality));
+    candidates.truncate(args.flag_max_candidates);
+    candidates.sort_unstable();
+
+    let mut keys: Vec<Vec<usize>> = Vec::new();
+    for size in 1..=args.flag_max_key_size.min(candidates.len()) {
+        let combos: Vec<Vec<usize>> = candidates
+            .iter()
+            .copied()
+            .combinations(size)
+            // skip the supersets of keys we already found, and the combinations
+            // that have fewer distinct values than there are rows
+            .filter(|combo| !keys.iter().any(|key| key.iter().all(|c| combo.contains(c))))
+            .filter(|combo| {
+                combo
+                    .iter()
+                    .try_fold(1_usize, |acc, &c| acc.checked_mul(columns[c].cardinality))
+                    .is_none_or(|combinations| combinations >= rowcount)
+            })
+            .collect();
+        let mut found: Vec<Vec<usize>> = combos
+            .into_par_iter()
+            .filter(|combo| is_unique(columns, combo, rowcount))
+            .collect();
+        keys.append(&mut found);
+    }
+    keys
+}
+
+/// whether the combined values of the columns are unique across all rows
+fn is_unique(columns: &[Column], combo: &[usize], rowcount: usize) -> bool {
+    let Some((last, rest)) = combo.split_last() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return columns[*last].cardinality == rowcount;
+    }
+
+    let prefix_ids = combined_ids(columns, rest);
+    let mut seen = AHashSet::with_capacity(rowcount);
+    prefix_ids
+        .iter()
+        .zip(&columns[*last].ids)
+        .all(|(prefix_id, id)| seen.insert((*prefix_id, *id)))
+}
+
+/// dictionary-encodes the combined values of the columns
+#[allow(clippy::cast_possible_truncation)]
+fn combined_ids(columns: &[Column], combo: &[usize]) -> Vec<u32> {
+    let mut combined = columns[combo[0]].ids.clone();
+    for &c in &combo[1..] {
+        let mut dictionary: AHashMap<(u32, u32), u32> = AHashMap::new();
+        for (combined_id, id) in combined.iter_mut().zip(&columns[c].ids) {
+            let next_id = dictionary.len() as u32;
+            *combined_id = *dictionary.entry((*combined_id, *id)).or_insert(next_id);
+        }
+    }
+    combined
+}
+
+/// finds the functional dependencies between single columns with at least
+/// `min_confidence` confidence, ordered by determinant then dependent column
+fn find_functional_dependencies(
+    columns: &[Column],
+    rowcount: usize,
+    min_confidence: f64,
+) -> Vec<FunctionalDependency> {
+    (0..columns.len())
+        .into_par_iter()
+        .filter(|&a| columns[a].cardinality > 1 && columns[a].cardinality < rowcount)
+        .flat_map_iter(|a| dependencies_of(columns, a, min_confidence))
+        .collect()
+}
+
+/// finds the columns determined by column `a`
+#[allow(clippy::cast_precision_loss)]
+fn dependencies_of(columns: &[Column], a: usize, min_confidence: f64) -> Vec<FunctionalDependency> {
+    let determinant = &columns[a];
+
+    // the number of rows with each determinant value.
+    // Only the rows of values that occur more than once support a dependency.
+    let mut group_sizes = vec![0_u32; determinant.cardinality];
+    for &id in &determinant.ids {
+        group_sizes[id as usize] += 1;
+    }
+    let support = determinant
+        .ids
+        .iter()
+        .filter(|&&id| group_sizes[id as usize] > 1)
+        .count() as u64;
+    if support == 0 {
+        return Vec::new();
+    }
+
+    let mut dependencies = Vec::new();
+    for (b, dependent) in columns.iter().enumerate() {
+        if b == a || dependent.cardinality <= 1 {
+            continue;
+        }
+
+        // the number of rows with each (determinant, dependent) value pair
+        let mut pair_counts: AHashMap<(u32, u32), u32> = AHashMap::new();
+        for (&x, &y) in determinant.ids.iter().zip(&dependent.ids) {
+            if group_sizes[x as usize] > 1 {
+                *pair_counts.entry((x, y)).or_insert(0) += 1;
+            }
+        }
+
+        // the rows that agree with the most common dependent value of their determinant value
+        let mut max_counts = vec![0_u32; determinant.cardinality];
+        for ((x, _), count) in pair_counts {
+            let max_count = &mut max_counts[x as usize];
+            *max_count = (*max_count).max(count);
+        }
+        let agreeing: u64 = max_counts.iter().map(|&count| u64::from(count)).sum();
+
+        let confidence = agreeing as f64 / support as f64;
+        if confidence >= min_confidence {
+            dependencies.push(FunctionalDependency {
+                determinant: determinant.name.clone(),
+                dependent: dependent.name.clone(),
+                confidence: (confidence * 10_000.0).round() / 10_000.0,
+                support,
+            });
+        }
+    }
+    dependencies
+}
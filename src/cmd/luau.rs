@@ -252,7 +252,7 @@ use mlua::{Lua, LuaSerdeExt, Value};
 use serde::Deserialize;
 
 use crate::{
-    config::{Config, Delimiter, DEFAULT_WTR_BUFFER_CAPACITY},
+    config::{Compression, Config, Delimiter, DEFAULT_WTR_BUFFER_CAPACITY},
     lookup, util, CliError, CliResult,
 };
 
@@ -1399,8 +1399,8 @@ fn create_index(arg_input: Option<&String>) -> Result<bool, CliError> {
         return Ok(false);
     };
 
-    if input.to_ascii_lowercase().ends_with(".sz") {
-        log::warn!("qsv_autoindex() does not work with snappy files.");
+    if Compression::from_extension(&input.to_ascii_lowercase()).is_compressed() {
+        log::warn!("qsv_autoindex() does not work with compressed files.");
         return Ok(false);
     }
 
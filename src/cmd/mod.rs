@@ -31,6 +31,8 @@ pub mod fetchpost;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod fill;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod fixedwidth;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod fixlengths;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod flatten;
@@ -39,6 +41,8 @@ pub mod fmt;
 #[cfg(all(feature = "foreach", not(feature = "lite")))]
 pub mod foreach;
 pub mod frequency;
+#[cfg(all(feature = "to", feature = "feature_capable"))]
+pub mod from;
 #[cfg(all(feature = "geocode", feature = "feature_capable"))]
 pub mod geocode;
 pub mod headers;
@@ -55,6 +59,8 @@ pub mod joinp;
 pub mod json;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod jsonl;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod keys;
 #[cfg(feature = "lens")]
 pub mod lens;
 #[cfg(feature = "luau")]
@@ -80,6 +86,8 @@ pub mod safenames;
 pub mod sample;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod schema;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod schemadiff;
 pub mod search;
 pub mod searchset;
 pub mod select;
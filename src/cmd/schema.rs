@@ -26,10 +26,48 @@ exists and is current (i.e. stats generated with --cardinality and --infer-dates
 Otherwise, it will run the `stats` command to generate the `stats.csv.data.jsonl` file first,
 and then use that to generate the schema file.
 
+Other schema formats can be generated from the same inferred types, enums and nullability
+with the --format option:
+
+  * frictionless: a Frictionless Data Table Schema (https://specs.frictionlessdata.io/table-schema/)
+                  with the same type, required, enum, minLength, maxLength, minimum, maximum &
+                  pattern constraints. It can also be used with the `validate` command.
+                  Suffix: `.tableschema.json`
+  * sql-postgres, sql-sqlite & sql-duckdb: a CREATE TABLE statement in the given SQL dialect,
+                  with NOT NULL and enum CHECK constraints. The table is named after the input
+                  file stem. Suffix: `.postgres.sql`, `.sqlite.sql` & `.duckdb.sql`
+  * polars:       a Polars schema, as used by `sqlp --cache-schema`. It is written to the
+                  input's `.pschema.json` file (mydata.pschema.json for mydata.csv), so `sqlp`
+                  uses it. Not available on qsvlite.
+  * arrow:        an Arrow schema in the Arrow JSON format. Suffix: `.arrow.json`
+  * avro:         an Avro record schema. Field names are sanitized into valid Avro names.
+                  Suffix: `.avsc`
+
+Except for polars, date & datetime columns are only typed as such with --strict-dates.
+
+MULTIPLE INPUTS:
+When given several inputs, a directory or an `.infile-list` file, `schema` infers the schema
+of each file and reconciles them into one unified schema:
+
+  * columns are listed in order of first appearance across the files.
+  * column types are widened to the narrowest type that holds the values of every file:
+    integer -> number -> string. Dates are only kept as such if all files agree.
+  * columns missing from some of the files, or with empty values in any of them, are optional.
+    Columns present in every file are required.
+  * value constraints (minimum/maximum, minLength/maxLength, enum & pattern) are combined
+    when every file has them, and dropped otherwise.
+
+A per-file report of where each file disagrees with the unified schema (missing columns and
+widened types) is printed to stderr.
+
+The unified schema is written to `unified` with the format's suffix appended
+(e.g. `unified.schema.json`) in the current directory, unless --output or --stdout is set.
+The polars format and --pattern-columns are not supported with multiple inputs.
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_schema.rs.
 
 Usage:
-    qsv schema [options] [<input>]
+    qsv schema [options] [<input>...]
     qsv schema --help
 
 Schema options:
@@ -60,7 +98,12 @@ Schema options:
                                Otherwise, use mdy format.
     --force                    Force recomputing cardinality and unique values
                                even if stats cache file exists and is current.
-    --stdout                   Send generated JSON schema file to stdout instead.
+    --format <format>          The schema format to generate. One of jsonschema, frictionless,
+                               sql-postgres, sql-sqlite, sql-duckdb, polars, arrow or avro.
+                               [default: jsonschema]
+    -o, --output <file>        Write the generated schema to <file> instead of
+                               the input's path with the format's suffix appended.
+    --stdout                   Send generated schema to stdout instead.
     -j, --jobs <arg>           The number of jobs to run in parallel.
                                When not set, the number of jobs is set to the
                                number of CPUs detected.
@@ -77,7 +120,11 @@ Common options:
                                CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{fs::File, io::Write, path::Path};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use ahash::{AHashMap, AHashSet};
 use csv::ByteRecord;
@@ -85,16 +132,80 @@ use grex::RegExpBuilder;
 use itertools::Itertools;
 use log::{debug, error, info, warn};
 use rayon::slice::ParallelSliceMut;
+use serde::Deserialize;
 use serde_json::{json, value::Number, Map, Value};
 use stats::Frequencies;
 
-use crate::{cmd::stats::StatsData, config::Config, util, util::StatsMode, CliResult};
+use crate::{cmd::stats::StatsData, config::Config, util, util::StatsMode, CliError, CliResult};
 
 const STDIN_CSV: &str = "stdin.csv";
 
+/// the options only `schema` has. The options it shares with the other commands
+/// that infer a schema are deserialized into `util::SchemaArgs`
+#[derive(Deserialize)]
+struct Args {
+    flag_format: String,
+    flag_output: Option<String>,
+    arg_input:   Vec<PathBuf>,
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
-    let mut args: util::SchemaArgs = util::get_args(USAGE, argv)?;
+    let args: Args = util::get_args(USAGE, argv)?;
+    let mut schema_args: util::SchemaArgs = util::get_args(USAGE, argv)?;
+    let schema_format: SchemaFormat = args.flag_format.parse()?;
+
+    // we can do this directly here, since schema_args is mutable and
+    // Config has not been created yet at this point
+    schema_args.flag_prefer_dmy =
+        schema_args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY");
+    if schema_args.flag_prefer_dmy {
+        winfo!("Prefer DMY set.");
+    }
+
+    // a single input file is used as is, so the schema file is written next to it
+    let single_input = match args.arg_input.as_slice() {
+        [] => Some(None),
+        [path]
+            if !path.is_dir()
+                && path
+                    .extension()
+                    .and_then(std::ffi::OsStr::to_str)
+                    .is_none_or(|ext| !ext.eq_ignore_ascii_case("infile-list")) =>
+        {
+            Some(Some(path.to_string_lossy().into_owned()))
+        },
+        _ => None,
+    };
+    if let Some(input) = single_input {
+        schema_args.arg_input = input;
+        return schema_from_input(schema_args, schema_format, args.flag_output.as_deref());
+    }
 
+    let tmpdir = tempfile::tempdir()?;
+    let inputs = util::process_input(
+        args.arg_input.clone(),
+        &tmpdir,
+        "No data on stdin. Please provide at least one input file or pipe data to stdin.",
+    )?;
+    if let [input] = inputs.as_slice() {
+        schema_args.arg_input = Some(input.to_string_lossy().into_owned());
+        return schema_from_input(schema_args, schema_format, args.flag_output.as_deref());
+    }
+
+    unified_schema_from_inputs(
+        &schema_args,
+        &inputs,
+        schema_format,
+        args.flag_output.as_deref(),
+    )
+}
+
+/// infers the schema of a single input, or of stdin if there is no input
+fn schema_from_input(
+    mut args: util::SchemaArgs,
+    schema_format: SchemaFormat,
+    output: Option<&str>,
+) -> CliResult<()> {
     // if using stdin, we create a stdin.csv file as stdin is not seekable and we need to
     // open the file multiple times to compile stats/unique values, etc.
     // We use a fixed "stdin.csv" filename instead of a temporary file with random characters
@@ -118,17 +229,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
 
     // we're loading the entire file into memory, we need to check avail mem
-    util::mem_file_check(
-        &std::path::PathBuf::from(&input_path),
-        false,
-        args.flag_memcheck,
-    )?;
+    util::mem_file_check(&PathBuf::from(&input_path), false, args.flag_memcheck)?;
 
-    // we can do this directly here, since args is mutable and
-    // Config has not been created yet at this point
-    args.flag_prefer_dmy = args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY");
-    if args.flag_prefer_dmy {
-        winfo!("Prefer DMY set.");
+    if schema_format == SchemaFormat::Polars {
+        return write_polars_schema(&args, &input_path, output);
     }
 
     // build schema for each field by their inferred type, min/max value/length, and unique values
@@ -156,37 +260,61 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
-    // generate list of required fields
-    let required_fields = get_required_fields(&properties_map);
-
-    // create final JSON object for output
-    let schema = json!({
-        "$schema": "https://json-schema.org/draft-07/schema",
-        "title": format!("JSON Schema for {input_filename}"),
-        "description": "Inferred JSON Schema from QSV schema command",
-        "type": "object",
-        "properties": Value::Object(properties_map),
-        "required": Value::Array(required_fields)
-    });
-
-    let schema_pretty = match serde_json::to_string_pretty(&schema) {
-        Ok(s) => s,
-        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+    let table_name = Path::new(&input_filename).file_stem().map_or_else(
+        || input_filename.clone(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+
+    // create final schema for output
+    let schema_output = match schema_format {
+        SchemaFormat::JsonSchema => {
+            // generate list of required fields
+            let required_fields = get_required_fields(&properties_map);
+
+            to_pretty_json(&json!({
+                "$schema": "https://json-schema.org/draft-07/schema",
+                "title": format!("JSON Schema for {input_filename}"),
+                "description": "Inferred JSON Schema from QSV schema command",
+                "type": "object",
+                "properties": Value::Object(properties_map),
+                "required": Value::Array(required_fields)
+            }))?
+        },
+        SchemaFormat::Frictionless => to_pretty_json(&to_table_schema(&properties_map))?,
+        SchemaFormat::SqlPostgres | SchemaFormat::SqlSqlite | SchemaFormat::SqlDuckdb => {
+            to_sql_ddl(schema_format, &table_name, &column_defs(&properties_map))
+        },
+        SchemaFormat::Arrow => to_pretty_json(&to_arrow_schema(&column_defs(&properties_map)))?,
+        SchemaFormat::Avro => {
+            to_pretty_json(&to_avro_schema(&table_name, &column_defs(&properties_map)))?
+        },
+        SchemaFormat::Polars => unreachable!("polars schema is written above"),
     };
 
-    if args.flag_stdout {
+    let schema_output_filename = output.map_or_else(
+        || input_path + schema_format.suffix(),
+        std::string::ToString::to_string,
+    );
+    write_schema(&schema_output, args.flag_stdout, &schema_output_filename)
+}
+
+fn write_schema(
+    schema_output: &str,
+    to_stdout: bool,
+    schema_output_filename: &str,
+) -> CliResult<()> {
+    if to_stdout {
         let stdout = std::io::stdout();
         let mut handle = stdout.lock();
 
-        handle.write_all(schema_pretty.as_bytes())?;
+        handle.write_all(schema_output.as_bytes())?;
         handle.flush()?;
 
         info!("Schema written to stdout");
     } else {
-        let schema_output_filename = input_path + ".schema.json";
-        let mut schema_output_file = File::create(&schema_output_filename)?;
+        let mut schema_output_file = File::create(schema_output_filename)?;
 
-        schema_output_file.write_all(schema_pretty.as_bytes())?;
+        schema_output_file.write_all(schema_output.as_bytes())?;
         schema_output_file.flush()?;
 
         woutinfo!("Schema written to {schema_output_filename}");
@@ -195,6 +323,596 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// infers the schema of each input and reconciles them into one unified schema,
+/// reporting where each input disagrees with it
+fn unified_schema_from_inputs(
+    args: &util::SchemaArgs,
+    inputs: &[PathBuf],
+    schema_format: SchemaFormat,
+    output: Option<&str>,
+) -> CliResult<()> {
+    if schema_format == SchemaFormat::Polars {
+        return fail_incorrectusage_clierror!(
+            "The polars format is not supported with multiple inputs."
+        );
+    }
+    if !args.flag_pattern_columns.is_empty() {
+        wwarn!("--pattern-columns is not supported with multiple inputs and is ignored.");
+    }
+
+    let mut file_properties: Vec<Map<String, Value>> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        util::mem_file_check(input, false, args.flag_memcheck)?;
+        let input_filename = input
+            .file_name()
+            .map_or_else(|| input.to_string_lossy(), |name| name.to_string_lossy())
+            .to_string();
+        let file_args = util::SchemaArgs {
+            arg_input: Some(input.to_string_lossy().into_owned()),
+            ..args.clone()
+        };
+        match infer_schema_from_stats(&file_args, &input_filename, true) {
+            Ok(map) => file_properties.push(map),
+            Err(e) => {
+                return fail_clierror!(
+                    "Failed to infer schema via stats and frequency from {input_filename}: {e}"
+                );
+            },
+        }
+    }
+
+    let (properties_map, required_fields) =
+        unify_properties(&file_properties, args.flag_enum_threshold);
+
+    winfo!("Per-file report:");
+    for (input, properties) in inputs.iter().zip(&file_properties) {
+        let disagreements = file_disagreements(properties, &properties_map);
+        if disagreements.is_empty() {
+            winfo!("  {}: agrees with the unified schema", input.display());
+        } else {
+            winfo!("  {}:", input.display());
+            for disagreement in disagreements {
+                winfo!("    {disagreement}");
+            }
+        }
+    }
+
+    let table_name = output
+        .and_then(|output| Path::new(output).file_name())
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("unified")
+        .to_string();
+
+    let schema_output = match schema_format {
+        SchemaFormat::JsonSchema => to_pretty_json(&json!({
+            "$schema": "https://json-schema.org/draft-07/schema",
+            "title": format!("Unified JSON Schema for {} files", inputs.len()),
+            "description": "Inferred JSON Schema from QSV schema command",
+            "type": "object",
+            "properties": Value::Object(properties_map),
+            "required": Value::Array(required_fields)
+        }))?,
+        SchemaFormat::Frictionless => to_pretty_json(&to_table_schema(&properties_map))?,
+        SchemaFormat::SqlPostgres | SchemaFormat::SqlSqlite | SchemaFormat::SqlDuckdb => {
+            to_sql_ddl(schema_format, &table_name, &column_defs(&properties_map))
+        },
+        SchemaFormat::Arrow => to_pretty_json(&to_arrow_schema(&column_defs(&properties_map)))?,
+        SchemaFormat::Avro => {
+            to_pretty_json(&to_avro_schema(&table_name, &column_defs(&properties_map)))?
+        },
+        SchemaFormat::Polars => unreachable!("polars is rejected above"),
+    };
+
+    let schema_output_filename = output.map_or_else(
+        || format!("unified{}", schema_format.suffix()),
+        ToString::to_string,
+    );
+    write_schema(&schema_output, args.flag_stdout, &schema_output_filename)
+}
+
+/// the base type of a field definition - its first non-null type - and its format, if any
+fn field_type(field_def: &Value) -> (&str, Option<&str>) {
+    let base_type = field_def["type"]
+        .as_array()
+        .and_then(|types| {
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null")
+        })
+        .unwrap_or("null");
+    (base_type, field_def.get("format").and_then(Value::as_str))
+}
+
+fn field_type_name((base_type, format): (&str, Option<&str>)) -> &str {
+    format.unwrap_or(base_type)
+}
+
+fn is_nullable(field_def: &Value) -> bool {
+    field_def["type"]
+        .as_array()
+        .is_some_and(|types| types.iter().any(|t| t == "null"))
+}
+
+/// widens two field types to the narrowest type that holds the values of both:
+/// integer -> number -> string. An all-empty ("null") column holds no values.
+fn widen_type<'a>(
+    a: (&'a str, Option<&'a str>),
+    b: (&'a str, Option<&'a str>),
+) -> (&'a str, Option<&'a str>) {
+    match (a, b) {
+        (("null", _), other) | (other, ("null", _)) => other,
+        _ if a == b => a,
+        (("integer" | "number", _), ("integer" | "number", _)) => ("number", None),
+        _ => ("string", None),
+    }
+}
+
+/// the loosest bound of `key` across the field definitions,
+/// or None if any of them does not have it
+fn loosest_bound(field_defs: &[&Value], key: &str, take_min: bool) -> Option<Value> {
+    let mut loosest: Option<(f64, &Value)> = None;
+    for field_def in field_defs {
+        let bound = field_def.get(key)?;
+        let n = bound.as_f64()?;
+        if loosest.is_none_or(|(current, _)| if take_min { n < current } else { n > current }) {
+            loosest = Some((n, bound));
+        }
+    }
+    loosest.map(|(_, bound)| bound.clone())
+}
+
+/// reconciles the properties inferred from each file into one unified properties map.
+/// Also returns the required fields - the columns present in every file.
+fn unify_properties(
+    file_properties: &[Map<String, Value>],
+    enum_threshold: u64,
+) -> (Map<String, Value>, Vec<Value>) {
+    let file_count = file_properties.len();
+
+    // columns in order of first appearance
+    let mut column_names: Vec<&String> = Vec::new();
+    for properties in file_properties {
+        for name in properties.keys() {
+            if !column_names.contains(&name) {
+                column_names.push(name);
+            }
+        }
+    }
+
+    let mut properties_map: Map<String, Value> = Map::with_capacity(column_names.len());
+    let mut required_fields: Vec<Value> = Vec::with_capacity(column_names.len());
+
+    for name in column_names {
+        let field_defs: Vec<&Value> = file_properties
+            .iter()
+            .filter_map(|properties| properties.get(name))
+            .collect();
+        // all-empty columns hold no values, so they do not constrain the unified column
+        let typed_defs: Vec<&Value> = field_defs
+            .iter()
+            .copied()
+            .filter(|field_def| field_type(field_def).0 != "null")
+            .collect();
+
+        let unified_type = field_defs
+            .iter()
+            .map(|field_def| field_type(field_def))
+            .fold(("null", None), widen_type);
+        let nullable = field_defs.len() < file_count || field_defs.iter().any(|d| is_nullable(d));
+        if field_defs.len() == file_count {
+            required_fields.push(Value::String(name.clone()));
+        }
+
+        let mut field_map: Map<String, Value> = Map::with_capacity(10);
+        let desc = if field_defs.len() == file_count {
+            format!("{name} column from {file_count} files")
+        } else {
+            format!(
+                "{name} column from {} of {file_count} files",
+                field_defs.len()
+            )
+        };
+        field_map.insert("description".to_string(), Value::String(desc));
+
+        let mut type_list = vec![Value::String(unified_type.0.to_string())];
+        if nullable && unified_type.0 != "null" {
+            type_list.push(Value::String("null".to_string()));
+        }
+        field_map.insert("type".to_string(), Value::Array(type_list));
+        if let Some(format) = unified_type.1 {
+            field_map.insert("format".to_string(), Value::String(format.to_string()));
+        }
+
+        let same_type = typed_defs.iter().all(|d| field_type(d) == unified_type);
+        let bound_keys = match unified_type.0 {
+            "integer" | "number" => Some(("minimum", "maximum")),
+            "string" if same_type => Some(("minLength", "maxLength")),
+            _ => None,
+        };
+        if let Some((min_key, max_key)) = bound_keys {
+            if let Some(min) = loosest_bound(&typed_defs, min_key, true) {
+                field_map.insert(min_key.to_string(), min);
+            }
+            if let Some(max) = loosest_bound(&typed_defs, max_key, false) {
+                field_map.insert(max_key.to_string(), max);
+            }
+        }
+
+        // enum & const constraints are combined into one enum, if every file has one
+        if same_type
+            && !typed_defs.is_empty()
+            && typed_defs
+                .iter()
+                .all(|d| d.get("enum").is_some() || d.get("const").is_some())
+        {
+            let mut enum_list: Vec<Value> = Vec::new();
+            for field_def in &typed_defs {
+                let values = match field_def.get("enum") {
+                    Some(Value::Array(values)) => values.iter().collect(),
+                    _ => field_def.get("const").into_iter().collect::<Vec<_>>(),
+                };
+                for value in values {
+                    if !value.is_null() && !enum_list.contains(value) {
+                        enum_list.push(value.clone());
+                    }
+                }
+            }
+            let all_const = typed_defs.iter().all(|d| d.get("enum").is_none());
+            if enum_list.len() == 1 && all_const {
+                field_map.insert("const".to_string(), enum_list.swap_remove(0));
+            } else if enum_list.len() as u64 <= enum_threshold {
+                if nullable {
+                    enum_list.push(Value::Null);
+                }
+                field_map.insert("enum".to_string(), Value::Array(enum_list));
+            }
+        }
+
+        // a pattern constraint is only kept if every file has the same one
+        if let Some(pattern) = typed_defs.first().and_then(|d| d.get("pattern")) {
+            if typed_defs.iter().all(|d| d.get("pattern") == Some(pattern)) {
+                field_map.insert("pattern".to_string(), pattern.clone());
+            }
+        }
+
+        properties_map.insert(name.clone(), Value::Object(field_map));
+    }
+
+    (properties_map, required_fields)
+}
+
+/// lists where the properties inferred from a file disagree with the unified properties
+fn file_disagreements(
+    properties: &Map<String, Value>,
+    unified_properties: &Map<String, Value>,
+) -> Vec<String> {
+    let mut disagreements = Vec::new();
+    for (name, unified_def) in unified_properties {
+        let Some(field_def) = properties.get(name) else {
+            disagreements.push(format!("{name}: missing, optional in the unified schema"));
+            continue;
+        };
+        let file_type = field_type(field_def);
+        let unified_type = field_type(unified_def);
+        if file_type.0 != "null" && file_type != unified_type {
+            disagreements.push(format!(
+                "{name}: {} widened to {}",
+                field_type_name(file_type),
+                field_type_name(unified_type)
+            ));
+        }
+    }
+    disagreements
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SchemaFormat {
+    JsonSchema,
+    Frictionless,
+    SqlPostgres,
+    SqlSqlite,
+    SqlDuckdb,
+    Polars,
+    Arrow,
+    Avro,
+}
+
+impl std::str::FromStr for SchemaFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonschema" => Ok(SchemaFormat::JsonSchema),
+            "frictionless" => Ok(SchemaFormat::Frictionless),
+            "sql-postgres" => Ok(SchemaFormat::SqlPostgres),
+            "sql-sqlite" => Ok(SchemaFormat::SqlSqlite),
+            "sql-duckdb" => Ok(SchemaFormat::SqlDuckdb),
+            "polars" => Ok(SchemaFormat::Polars),
+            "arrow" => Ok(SchemaFormat::Arrow),
+            "avro" => Ok(SchemaFormat::Avro),
+            _ => Err(CliError::IncorrectUsage(format!(
+                "Invalid --format: {s}. Must be one of jsonschema, frictionless, sql-postgres, \
+                 sql-sqlite, sql-duckdb, polars, arrow or avro."
+            ))),
+        }
+    }
+}
+
+impl SchemaFormat {
+    /// the suffix appended to the input path for the schema file
+    const fn suffix(self) -> &'static str {
+        match self {
+            SchemaFormat::JsonSchema => ".schema.json",
+            SchemaFormat::Frictionless => ".tableschema.json",
+            SchemaFormat::SqlPostgres => ".postgres.sql",
+            SchemaFormat::SqlSqlite => ".sqlite.sql",
+            SchemaFormat::SqlDuckdb => ".duckdb.sql",
+            SchemaFormat::Polars => ".pschema.json",
+            SchemaFormat::Arrow => ".arrow.json",
+            SchemaFormat::Avro => ".avsc",
+        }
+    }
+}
+
+fn to_pretty_json(schema: &Value) -> CliResult<String> {
+    match serde_json::to_string_pretty(schema) {
+        Ok(s) => Ok(s),
+        Err(e) => fail_clierror!("Cannot prettify schema json: {e}"),
+    }
+}
+
+/// writes the Polars schema to the input's ".pschema.json" file, where `sqlp --cache-schema`
+/// looks for it. The schema is derived from stats the same way `sqlp` does.
+#[cfg(feature = "polars")]
+fn write_polars_schema(
+    args: &util::SchemaArgs,
+    input_path: &str,
+    output: Option<&str>,
+) -> CliResult<()> {
+    let (csv_fields, csv_stats) = util::get_stats_records(args, StatsMode::PolarsSchema)?;
+    let schema = util::polars_schema_from_stats(&csv_fields, &csv_stats);
+    let schema_json = to_pretty_json(&serde_json::to_value(&schema)?)?;
+
+    if args.flag_stdout {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        handle.write_all(schema_json.as_bytes())?;
+        handle.flush()?;
+
+        info!("Schema written to stdout");
+    } else {
+        let schema_output_path = output.map_or_else(
+            || Path::new(input_path).with_extension("pschema.json"),
+            PathBuf::from,
+        );
+        let mut schema_output_file = File::create(&schema_output_path)?;
+        schema_output_file.write_all(schema_json.as_bytes())?;
+        schema_output_file.flush()?;
+
+        woutinfo!("Schema written to {}", schema_output_path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "polars"))]
+fn write_polars_schema(
+    _args: &util::SchemaArgs,
+    _input_path: &str,
+    _output: Option<&str>,
+) -> CliResult<()> {
+    fail_incorrectusage_clierror!("The polars format requires the polars feature.")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ColumnType {
+    /// whether the values fit in 32 bits
+    Integer(bool),
+    Float,
+    String,
+    Date,
+    DateTime,
+    Null,
+}
+
+/// a column definition derived from the JSON Schema "properties", for the other schema formats
+struct ColumnDef<'a> {
+    name:        &'a str,
+    col_type:    ColumnType,
+    nullable:    bool,
+    enum_values: Vec<&'a Value>,
+    description: Option<&'a str>,
+}
+
+fn column_defs(properties_map: &Map<String, Value>) -> Vec<ColumnDef<'_>> {
+    properties_map
+        .iter()
+        .map(|(name, field_def)| {
+            let type_list: Vec<&str> = field_def["type"]
+                .as_array()
+                .map(|types| types.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let fits_i32 = |key: &str| {
+                field_def
+                    .get(key)
+                    .and_then(Value::as_i64)
+                    .is_some_and(|n| i32::try_from(n).is_ok())
+            };
+            let col_type = match type_list.iter().find(|t| **t != "null") {
+                Some(&"integer") => ColumnType::Integer(fits_i32("minimum") && fits_i32("maximum")),
+                Some(&"number") => ColumnType::Float,
+                Some(&"string") => match field_def.get("format").and_then(Value::as_str) {
+                    Some("date") => ColumnType::Date,
+                    Some("date-time") => ColumnType::DateTime,
+                    _ => ColumnType::String,
+                },
+                Some(_) => ColumnType::String,
+                None => ColumnType::Null,
+            };
+            let enum_values = match (field_def.get("enum"), field_def.get("const")) {
+                (Some(Value::Array(values)), _) => values.iter().filter(|v| !v.is_null()).collect(),
+                (_, Some(value)) => vec![value],
+                _ => Vec::new(),
+            };
+
+            ColumnDef {
+                name,
+                col_type,
+                nullable: type_list.contains(&"null"),
+                enum_values,
+                description: field_def.get("description").and_then(Value::as_str),
+            }
+        })
+        .collect()
+}
+
+fn quote_sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// generates a CREATE TABLE statement in the SQL dialect of the schema format
+fn to_sql_ddl(dialect: SchemaFormat, table_name: &str, columns: &[ColumnDef]) -> String {
+    let column_lines: Vec<String> = columns
+        .iter()
+        .map(|col| {
+            #[allow(clippy::match_same_arms)]
+            let sql_type = match (dialect, col.col_type) {
+                (_, ColumnType::Integer(true))
+                | (SchemaFormat::SqlSqlite, ColumnType::Integer(_)) => "INTEGER",
+                (_, ColumnType::Integer(false)) => "BIGINT",
+                (SchemaFormat::SqlPostgres, ColumnType::Float) => "DOUBLE PRECISION",
+                (SchemaFormat::SqlSqlite, ColumnType::Float) => "REAL",
+                (_, ColumnType::Float) => "DOUBLE",
+                // SQLite has no date types
+                (SchemaFormat::SqlSqlite, _) => "TEXT",
+                (_, ColumnType::Date) => "DATE",
+                (_, ColumnType::DateTime) => "TIMESTAMPTZ",
+                (SchemaFormat::SqlDuckdb, _) => "VARCHAR",
+                _ => "TEXT",
+            };
+            let column_name = quote_sql_identifier(col.name);
+            let mut line = format!("    {column_name} {sql_type}");
+            if !col.nullable {
+                line.push_str(" NOT NULL");
+            }
+            if !col.enum_values.is_empty()
+                && matches!(col.col_type, ColumnType::Integer(_) | ColumnType::String)
+            {
+                let values = col
+                    .enum_values
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+                        _ => v.to_string(),
+                    })
+                    .join(", ");
+                line.push_str(&format!(" CHECK ({column_name} IN ({values}))"));
+            }
+            line
+        })
+        .collect();
+
+    format!(
+        "CREATE TABLE {} (\n{}\n);\n",
+        quote_sql_identifier(table_name),
+        column_lines.join(",\n")
+    )
+}
+
+/// generates an Arrow schema, in the JSON format of the Arrow integration tests
+fn to_arrow_schema(columns: &[ColumnDef]) -> Value {
+    let fields: Vec<Value> = columns
+        .iter()
+        .map(|col| {
+            let arrow_type = match col.col_type {
+                ColumnType::Integer(fits_i32) => json!({
+                    "name": "int",
+                    "bitWidth": if fits_i32 { 32 } else { 64 },
+                    "isSigned": true
+                }),
+                ColumnType::Float => json!({"name": "floatingpoint", "precision": "DOUBLE"}),
+                ColumnType::String => json!({"name": "utf8"}),
+                ColumnType::Date => json!({"name": "date", "unit": "DAY"}),
+                ColumnType::DateTime => {
+                    json!({"name": "timestamp", "unit": "MICROSECOND", "timezone": "UTC"})
+                },
+                ColumnType::Null => json!({"name": "null"}),
+            };
+            json!({
+                "name": col.name,
+                "nullable": col.nullable || col.col_type == ColumnType::Null,
+                "type": arrow_type,
+                "children": []
+            })
+        })
+        .collect();
+
+    json!({ "fields": fields })
+}
+
+/// Avro names must start with [A-Za-z_] and then only contain [A-Za-z0-9_]
+fn avro_name(name: &str) -> String {
+    let mut avro_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !avro_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro_name.insert(0, '_');
+    }
+    avro_name
+}
+
+/// generates an Avro record schema
+fn to_avro_schema(table_name: &str, columns: &[ColumnDef]) -> Value {
+    let mut used_names: AHashSet<String> = AHashSet::with_capacity(columns.len());
+    let fields: Vec<Value> = columns
+        .iter()
+        .map(|col| {
+            // sanitized names have to stay unique
+            let base_name = avro_name(col.name);
+            let mut field_name = base_name.clone();
+            let mut suffix = 1;
+            while !used_names.insert(field_name.clone()) {
+                suffix += 1;
+                field_name = format!("{base_name}_{suffix}");
+            }
+
+            let avro_type = match col.col_type {
+                ColumnType::Integer(true) => json!("int"),
+                ColumnType::Integer(false) => json!("long"),
+                ColumnType::Float => json!("double"),
+                ColumnType::String => json!("string"),
+                ColumnType::Date => json!({"type": "int", "logicalType": "date"}),
+                ColumnType::DateTime => json!({"type": "long", "logicalType": "timestamp-micros"}),
+                ColumnType::Null => json!("null"),
+            };
+
+            let mut field = Map::with_capacity(4);
+            field.insert("name".to_string(), Value::String(field_name));
+            if col.nullable && col.col_type != ColumnType::Null {
+                // null has to be first in the union for a null default
+                field.insert("type".to_string(), json!(["null", avro_type]));
+                field.insert("default".to_string(), Value::Null);
+            } else {
+                field.insert("type".to_string(), avro_type);
+            }
+            if let Some(description) = col.description {
+                field.insert("doc".to_string(), Value::String(description.to_string()));
+            }
+            Value::Object(field)
+        })
+        .collect();
+
+    json!({
+        "type": "record",
+        "name": avro_name(table_name),
+        "fields": fields
+    })
+}
+
 /// Builds JSON MAP object that corresponds to the "properties" object of JSON Schema (Draft 7
 /// 2020-12) by looking at CSV value stats Supported JSON Schema validation vocabularies:
 ///  * type
@@ -426,6 +1144,65 @@ pub fn infer_schema_from_stats(
     Ok(properties_map)
 }
 
+/// Converts the "properties" object of the inferred JSON Schema to a Frictionless Data
+/// Table Schema (https://specs.frictionlessdata.io/table-schema/)
+fn to_table_schema(properties_map: &Map<String, Value>) -> Value {
+    let mut fields: Vec<Value> = Vec::with_capacity(properties_map.len());
+
+    for (field_name, field_def) in properties_map {
+        let mut field = Map::with_capacity(5);
+        field.insert("name".to_string(), Value::String(field_name.clone()));
+
+        let type_list = field_def["type"].as_array().cloned().unwrap_or_default();
+        let json_type = type_list
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .unwrap_or("any");
+        let field_type = match (json_type, field_def.get("format").and_then(Value::as_str)) {
+            ("string", Some("date")) => "date",
+            ("string", Some("date-time")) => "datetime",
+            (t, _) => t,
+        };
+        field.insert("type".to_string(), Value::String(field_type.to_string()));
+        if let Some(desc) = field_def.get("description") {
+            field.insert("description".to_string(), desc.clone());
+        }
+
+        let mut constraints = Map::new();
+        // in Table Schema, required means the value cannot be missing
+        if !type_list.contains(&Value::String("null".to_string())) {
+            constraints.insert("required".to_string(), Value::Bool(true));
+        }
+        for key in ["minLength", "maxLength", "minimum", "maximum", "pattern"] {
+            if let Some(v) = field_def.get(key) {
+                constraints.insert(key.to_string(), v.clone());
+            }
+        }
+        // Table Schema has no const, and nulls are expressed with missingValues
+        let enum_values: Vec<Value> = match (field_def.get("enum"), field_def.get("const")) {
+            (Some(Value::Array(values)), _) => {
+                values.iter().filter(|v| !v.is_null()).cloned().collect()
+            },
+            (_, Some(value)) => vec![value.clone()],
+            _ => Vec::new(),
+        };
+        if !enum_values.is_empty() {
+            constraints.insert("enum".to_string(), Value::Array(enum_values));
+        }
+        if !constraints.is_empty() {
+            field.insert("constraints".to_string(), Value::Object(constraints));
+        }
+
+        fields.push(Value::Object(field));
+    }
+
+    json!({
+        "fields": fields,
+        "missingValues": [""]
+    })
+}
+
 /// get column selector argument string for low cardinality columns
 fn build_low_cardinality_column_selector_arg(
     low_cardinality_column_indices: &mut Vec<u64>,
@@ -0,0 +1,645 @@
+static USAGE: &str = r#"
+Compare two schemas and classify the changes as breaking or non-breaking.
+
+Each input is either a JSON Schema (as generated by the `schema` command), a Frictionless
+Table Schema, or a CSV file - in which case its schema is inferred the same way `schema` does.
+Inputs with a `.json` extension are read as schemas. Everything else is read as CSV.
+
+A change is breaking if data or consumers that worked with the old schema may not work
+with the new one:
+
+  * column removed
+  * type narrowed (e.g. number -> integer, string -> integer) or changed
+  * format added or changed (e.g. string -> date)
+  * column no longer nullable
+  * new required field - a required, non-nullable column was added, or an existing
+    column became required. Only checked when the old input is a schema that lists
+    its required fields.
+  * enum shrunk - enum values were removed, or an enum was added
+  * const, pattern, minimum, maximum, minLength or maxLength added or tightened
+
+Otherwise, the change is non-breaking (e.g. optional column added, type widened,
+column now nullable, enum grown or removed, constraints loosened or removed).
+
+The changes are written as CSV with the columns severity, column, change, old & new,
+or as JSON with the --json option.
+
+Returns exit code 0 if there are no breaking changes, and exit code 1 otherwise.
+This makes it suitable as a CI check before publishing a new version of a dataset.
+
+As a CSV does not declare which columns are required, columns added to a CSV are
+non-breaking unless the old input is a schema with a `required` list, and required
+fields are only compared when both inputs are schemas.
+
+Note that schemas inferred from CSVs have enum & range constraints that reflect the
+observed values. Use --types-only to only compare columns, types, formats, nullability
+and required fields.
+
+For examples, see https://github.com/dathere/qsv/blob/master/tests/test_schemadiff.rs.
+
+Usage:
+    qsv schemadiff [options] <old> <new>
+    qsv schemadiff --help
+
+schemadiff options:
+    --types-only               Only compare columns, types, formats, nullability and
+                               required fields. Ignore enum, const, pattern & range
+                               constraints.
+    --strict                   Also return exit code 1 if there are non-breaking changes.
+    --json                     Write the changes as JSON instead of CSV. The JSON object has
+                               the properties breaking (boolean), breaking_count (number),
+                               non_breaking_count (number) & changes (array).
+    --pretty-json              Same as --json but in pretty JSON format.
+
+CSV schema inference options:
+    --enum-threshold <num>     Cardinality threshold for adding enum constraints.
+                               [default: 50]
+    --strict-dates             Type detected date/datetime columns with the
+                               "date" & "date-time" formats.
+    --dates-whitelist <list>   The case-insensitive patterns to look for when
+                               shortlisting fields for date inference.
+                               [default: date,time,due,open,close,created]
+    --prefer-dmy               Prefer to parse dates in dmy format.
+    -j, --jobs <arg>           The number of jobs to run in parallel.
+                               When not set, the number of jobs is set to the
+                               number of CPUs detected.
+
+Common options:
+    -h, --help                 Display this message
+    -o, --output <file>        Write output to <file> instead of stdout.
+    -n, --no-headers           When set, the first row of CSV inputs will not be
+                               interpreted as headers.
+    -d, --delimiter <arg>      The field delimiter for reading CSV inputs.
+                               Must be a single character. (default: ,)
+"#;
+
+use std::{fmt, io::Write, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{schema::infer_schema_from_stats, validate};
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_old:              String,
+    arg_new:              String,
+    flag_types_only:      bool,
+    flag_strict:          bool,
+    flag_json:            bool,
+    flag_pretty_json:     bool,
+    flag_enum_threshold:  u64,
+    flag_strict_dates:    bool,
+    flag_dates_whitelist: String,
+    flag_prefer_dmy:      bool,
+    flag_jobs:            Option<usize>,
+    flag_output:          Option<String>,
+    flag_no_headers:      bool,
+    flag_delimiter:       Option<Delimiter>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+enum Severity {
+    Breaking,
+    NonBreaking,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Breaking => f.write_str("breaking"),
+            Severity::NonBreaking => f.write_str("non-breaking"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Change {
+    severity: Severity,
+    column:   String,
+    change:   String,
+    old:      String,
+    new:      String,
+}
+
+#[derive(Serialize)]
+struct SchemaDiff<'a> {
+    breaking:           bool,
+    breaking_count:     usize,
+    non_breaking_count: usize,
+    changes:            &'a [Change],
+}
+
+/// the parts of a JSON Schema "properties" entry that schemadiff compares
+struct FieldInfo<'a> {
+    /// the non-null types. Empty if the field accepts any type
+    types:       Vec<&'a str>,
+    nullable:    bool,
+    required:    bool,
+    format:      Option<&'a str>,
+    enum_values: Option<Vec<&'a Value>>,
+    pattern:     Option<&'a str>,
+    minimum:     Option<f64>,
+    maximum:     Option<f64>,
+    min_length:  Option<u64>,
+    max_length:  Option<u64>,
+}
+
+impl<'a> FieldInfo<'a> {
+    fn new(field_def: &'a Value, required: bool) -> Self {
+        let mut types: Vec<&str> = match field_def.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.is_empty() || types.contains(&"null");
+        types.retain(|t| *t != "null");
+
+        let enum_values = match (field_def.get("enum"), field_def.get("const")) {
+            (Some(Value::Array(values)), _) => {
+                Some(values.iter().filter(|v| !v.is_null()).collect())
+            },
+            (_, Some(value)) => Some(vec![value]),
+            _ => None,
+        };
+
+        FieldInfo {
+            types,
+            nullable,
+            required,
+            format: field_def.get("format").and_then(Value::as_str),
+            enum_values,
+            pattern: field_def.get("pattern").and_then(Value::as_str),
+            minimum: field_def.get("minimum").and_then(Value::as_f64),
+            maximum: field_def.get("maximum").and_then(Value::as_f64),
+            min_length: field_def.get("minLength").and_then(Value::as_u64),
+            max_length: field_def.get("maxLength").and_then(Value::as_u64),
+        }
+    }
+
+    fn type_name(&self) -> String {
+        if self.types.is_empty() {
+            "any".to_string()
+        } else {
+            self.types.join("|")
+        }
+    }
+}
+
+/// a schema's properties, in column order, and its required fields
+struct SchemaProperties {
+    properties:        Map<String, Value>,
+    required:          Vec<String>,
+    /// false for schemas inferred from a CSV, which has no notion of required fields
+    explicit_required: bool,
+}
+
+impl SchemaProperties {
+    fn field_info(&self, name: &str) -> Option<FieldInfo<'_>> {
+        self.properties
+            .get(name)
+            .map(|field_def| FieldInfo::new(field_def, self.required.iter().any(|r| r == name)))
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let old_schema = load_schema(&args, &args.arg_old)?;
+    let new_schema = load_schema(&args, &args.arg_new)?;
+
+    let changes = diff_schemas(&old_schema, &new_schema, args.flag_types_only);
+    let breaking_count = changes
+        .iter()
+        .filter(|change| change.severity == Severity::Breaking)
+        .count();
+    let non_breaking_count = changes.len() - breaking_count;
+
+    if args.flag_json || args.flag_pretty_json {
+        let schema_diff = SchemaDiff {
+            breaking: breaking_count > 0,
+            breaking_count,
+            non_breaking_count,
+            changes: &changes,
+        };
+        let json_output = if args.flag_pretty_json {
+            serde_json::to_string_pretty(&schema_diff)?
+        } else {
+            serde_json::to_string(&schema_diff)?
+        };
+        let mut out: Box<dyn Write> = match args.flag_output {
+            Some(ref output) => Box::new(std::fs::File::create(output)?),
+            None => Box::new(std::io::stdout()),
+        };
+        writeln!(out, "{json_output}")?;
+        out.flush()?;
+    } else {
+        let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+        wtr.write_record(["severity", "column", "change", "old", "new"])?;
+        for change in &changes {
+            wtr.write_record([
+                change.severity.to_string(),
+                change.column.clone(),
+                change.change.clone(),
+                change.old.clone(),
+                change.new.clone(),
+            ])?;
+        }
+        wtr.flush()?;
+    }
+
+    if breaking_count > 0 {
+        return fail_clierror!(
+            "{breaking_count} breaking and {non_breaking_count} non-breaking schema change/s \
+             found."
+        );
+    }
+    if args.flag_strict && non_breaking_count > 0 {
+        return fail_clierror!("{non_breaking_count} non-breaking schema change/s found.");
+    }
+    winfo!("No breaking schema changes. {non_breaking_count} non-breaking schema change/s found.");
+
+    Ok(())
+}
+
+/// loads a JSON Schema or Table Schema, or infers the schema of a CSV
+fn load_schema(args: &Args, input: &str) -> CliResult<SchemaProperties> {
+    let input_path = Path::new(input);
+    if !input_path.exists() {
+        return fail_clierror!("Input file '{input}' does not exist");
+    }
+
+    let is_json = input_path
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    let schema = if is_json {
+        let schema_json = std::fs::read_to_string(input_path)?;
+        let schema: Value = match serde_json::from_str(&schema_json) {
+            Ok(schema) => schema,
+            Err(e) => return fail_clierror!("Cannot parse schema {input}: {e}"),
+        };
+        if validate::is_table_schema(&schema) {
+            validate::table_schema_to_json_schema(&schema)?.0
+        } else {
+            schema
+        }
+    } else {
+        let input_filename = input_path.file_name().map_or_else(
+            || input.to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        let schema_args = util::SchemaArgs {
+            flag_enum_threshold:  args.flag_enum_threshold,
+            flag_ignore_case:     false,
+            flag_strict_dates:    args.flag_strict_dates,
+            flag_pattern_columns: SelectColumns::parse("")?,
+            flag_dates_whitelist: args.flag_dates_whitelist.clone(),
+            flag_prefer_dmy:      args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY"),
+            flag_force:           false,
+            flag_stdout:          false,
+            flag_jobs:            Some(util::njobs(args.flag_jobs)),
+            flag_no_headers:      args.flag_no_headers,
+            flag_delimiter:       args.flag_delimiter,
+            arg_input:            Some(input.to_string()),
+            flag_memcheck:        false,
+        };
+        let properties = match infer_schema_from_stats(&schema_args, &input_filename, true) {
+            Ok(properties) => properties,
+            Err(e) => return fail_clierror!("Failed to infer schema from {input_filename}: {e}"),
+        };
+        // all columns of a CSV are present in every row, but the CSV does not
+        // declare them as required
+        let required = properties.keys().cloned().collect();
+        return Ok(SchemaProperties {
+            properties,
+            required,
+            explicit_required: false,
+        });
+    };
+
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return fail_clierror!("Schema {input} has no 'properties' object");
+    };
+    let explicit_required = schema.get("required").is_some();
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| {
+            required
+                .iter()
+                .filter_map(|r| r.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(SchemaProperties {
+        properties: properties.clone(),
+        required,
+        explicit_required,
+    })
+}
+
+/// whether a value of type `t` is accepted by a field with the given types
+fn accepts_type(types: &[&str], t: &str) -> bool {
+    types.is_empty() || types.contains(&t) || (t == "integer" && types.contains(&"number"))
+}
+
+fn display_values(values: &[&Value]) -> String {
+    values
+        .iter()
+        .map(|v| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_option<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(String::new, |v| v.to_string())
+}
+
+/// lists the changes from the old to the new schema, in column order
+fn diff_schemas(
+    old_schema: &SchemaProperties,
+    new_schema: &SchemaProperties,
+    types_only: bool,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let compare_required = old_schema.explicit_required && new_schema.explicit_required;
+
+    for name in old_schema.properties.keys() {
+        let old_field = old_schema.field_info(name).unwrap();
+        let Some(new_field) = new_schema.field_info(name) else {
+            changes.push(Change {
+                severity: Severity::Breaking,
+                column:   name.clone(),
+                change:   "column removed".to_string(),
+                old:      old_field.type_name(),
+                new:      String::new(),
+            });
+            continue;
+        };
+        diff_fields(
+            name,
+            &old_field,
+            &new_field,
+            types_only,
+            compare_required,
+            &mut changes,
+        );
+    }
+
+    for name in new_schema.properties.keys() {
+        if old_schema.properties.contains_key(name) {
+            continue;
+        }
+        let new_field = new_schema.field_info(name).unwrap();
+        let (severity, change) =
+            if old_schema.explicit_required && new_field.required && !new_field.nullable {
+                (Severity::Breaking, "required column added")
+            } else {
+                (Severity::NonBreaking, "optional column added")
+            };
+        changes.push(Change {
+            severity,
+            column: name.clone(),
+            change: change.to_string(),
+            old: String::new(),
+            new: new_field.type_name(),
+        });
+    }
+
+    changes
+}
+
+/// compares the definitions of a column present in both schemas
+fn diff_fields(
+    name: &str,
+    old_field: &FieldInfo,
+    new_field: &FieldInfo,
+    types_only: bool,
+    compare_required: bool,
+    changes: &mut Vec<Change>,
+) {
+    let mut push = |severity: Severity, change: &str, old: String, new: String| {
+        changes.push(Change {
+            severity,
+            column: name.to_string(),
+            change: change.to_string(),
+            old,
+            new,
+        });
+    };
+
+    // types
+    let narrowed = if old_field.types.is_empty() {
+        !new_field.types.is_empty()
+    } else {
+        old_field
+            .types
+            .iter()
+            .any(|t| !accepts_type(&new_field.types, t))
+    };
+    let widened = if new_field.types.is_empty() {
+        !old_field.types.is_empty()
+    } else {
+        new_field
+            .types
+            .iter()
+            .any(|t| !accepts_type(&old_field.types, t))
+    };
+    let type_change = match (narrowed, widened) {
+        (true, true) => Some((Severity::Breaking, "type changed")),
+        (true, false) => Some((Severity::Breaking, "type narrowed")),
+        (false, true) => Some((Severity::NonBreaking, "type widened")),
+        (false, false) => None,
+    };
+    if let Some((severity, change)) = type_change {
+        push(
+            severity,
+            change,
+            old_field.type_name(),
+            new_field.type_name(),
+        );
+    }
+
+    // format
+    match (old_field.format, new_field.format) {
+        (None, Some(new)) => push(
+            Severity::Breaking,
+            "format added",
+            String::new(),
+            new.to_string(),
+        ),
+        (Some(old), None) => push(
+            Severity::NonBreaking,
+            "format removed",
+            old.to_string(),
+            String::new(),
+        ),
+        (Some(old), Some(new)) if old != new => push(
+            Severity::Breaking,
+            "format changed",
+            old.to_string(),
+            new.to_string(),
+        ),
+        _ => {},
+    }
+
+    // nullability & required
+    if old_field.nullable && !new_field.nullable {
+        push(
+            Severity::Breaking,
+            "no longer nullable",
+            "nullable".to_string(),
+            "not nullable".to_string(),
+        );
+    } else if !old_field.nullable && new_field.nullable {
+        push(
+            Severity::NonBreaking,
+            "now nullable",
+            "not nullable".to_string(),
+            "nullable".to_string(),
+        );
+    }
+    // a schema inferred from a CSV doesn't declare its required fields
+    if compare_required && !old_field.required && new_field.required {
+        push(
+            Severity::Breaking,
+            "now required",
+            "optional".to_string(),
+            "required".to_string(),
+        );
+    } else if compare_required && old_field.required && !new_field.required {
+        push(
+            Severity::NonBreaking,
+            "no longer required",
+            "required".to_string(),
+            "optional".to_string(),
+        );
+    }
+
+    if types_only {
+        return;
+    }
+
+    // enum & const
+    match (&old_field.enum_values, &new_field.enum_values) {
+        (None, Some(new_values)) => push(
+            Severity::Breaking,
+            "enum added",
+            String::new(),
+            display_values(new_values),
+        ),
+        (Some(old_values), None) => push(
+            Severity::NonBreaking,
+            "enum removed",
+            display_values(old_values),
+            String::new(),
+        ),
+        (Some(old_values), Some(new_values)) => {
+            let removed: Vec<&Value> = old_values
+                .iter()
+                .copied()
+                .filter(|v| !new_values.contains(v))
+                .collect();
+            let added: Vec<&Value> = new_values
+                .iter()
+                .copied()
+                .filter(|v| !old_values.contains(v))
+                .collect();
+            if !removed.is_empty() {
+                push(
+                    Severity::Breaking,
+                    "enum values removed",
+                    display_values(&removed),
+                    String::new(),
+                );
+            }
+            if !added.is_empty() {
+                push(
+                    Severity::NonBreaking,
+                    "enum values added",
+                    String::new(),
+                    display_values(&added),
+                );
+            }
+        },
+        (None, None) => {},
+    }
+
+    // pattern
+    match (old_field.pattern, new_field.pattern) {
+        (None, Some(new)) => push(
+            Severity::Breaking,
+            "pattern added",
+            String::new(),
+            new.to_string(),
+        ),
+        (Some(old), None) => push(
+            Severity::NonBreaking,
+            "pattern removed",
+            old.to_string(),
+            String::new(),
+        ),
+        (Some(old), Some(new)) if old != new => push(
+            Severity::Breaking,
+            "pattern changed",
+            old.to_string(),
+            new.to_string(),
+        ),
+        _ => {},
+    }
+
+    // range constraints. A lower bound is tightened when raised, an upper bound when lowered.
+    #[allow(clippy::cast_precision_loss)]
+    let lower_bounds = [
+        ("minimum", old_field.minimum, new_field.minimum),
+        (
+            "minLength",
+            old_field.min_length.map(|n| n as f64),
+            new_field.min_length.map(|n| n as f64),
+        ),
+    ];
+    #[allow(clippy::cast_precision_loss)]
+    let upper_bounds = [
+        ("maximum", old_field.maximum, new_field.maximum),
+        (
+            "maxLength",
+            old_field.max_length.map(|n| n as f64),
+            new_field.max_length.map(|n| n as f64),
+        ),
+    ];
+    for (is_lower, (keyword, old, new)) in lower_bounds
+        .into_iter()
+        .map(|bound| (true, bound))
+        .chain(upper_bounds.into_iter().map(|bound| (false, bound)))
+    {
+        let severity = match (old, new) {
+            (None, Some(_)) => Severity::Breaking,
+            (Some(_), None) => Severity::NonBreaking,
+            (Some(old), Some(new)) if (old - new).abs() > f64::EPSILON => {
+                if (new > old) == is_lower {
+                    Severity::Breaking
+                } else {
+                    Severity::NonBreaking
+                }
+            },
+            _ => continue,
+        };
+        let change = match severity {
+            Severity::Breaking => format!("{keyword} tightened"),
+            Severity::NonBreaking => format!("{keyword} loosened"),
+        };
+        push(severity, &change, display_option(old), display_option(new));
+    }
+}
@@ -84,6 +84,7 @@ use serde::Deserialize;
 use crate::{
     config::{Config, Delimiter},
     index::Indexed,
+    seekable::InputFile,
     util, CliResult,
 };
 
@@ -150,7 +151,7 @@ impl Args {
         }
     }
 
-    fn with_index(&self, mut indexed_file: Indexed<fs::File, fs::File>) -> CliResult<()> {
+    fn with_index(&self, mut indexed_file: Indexed<InputFile, fs::File>) -> CliResult<()> {
         let (start, end) = self.range()?;
         if end - start == 0 && !self.flag_invert {
             return Ok(());
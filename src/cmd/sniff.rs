@@ -1,8 +1,14 @@
 static USAGE: &str = r#"
 Quickly sniff the first n rows and infer CSV metadata (delimiter, header row, number of
-preamble rows, quote character, flexible, is_utf8, average record length, number of records,
-content length and estimated number of records if sniffing a URL, file size, number of fields,
-field names & data types) using a Viterbi algorithm. (https://en.wikipedia.org/wiki/Viterbi_algorithm)
+preamble rows, quote character, flexible, is_utf8, character encoding, average record length,
+number of records, content length and estimated number of records if sniffing a URL, file size,
+number of fields, field names & data types) using a Viterbi algorithm.
+(https://en.wikipedia.org/wiki/Viterbi_algorithm)
+
+The character encoding is detected from a BOM or, failing that, statistically from the
+first 64KB of the file, with a confidence between 0 and 1. The sampled rows of non UTF-8
+files are transcoded to UTF-8 before sniffing - the rest of the file is only decoded to count
+its records. Use `qsv input --from-encoding` to transcode them.
 
 `sniff` is also a mime type detector, returning the detected mime type, file size and
 last modified date. If --no-infer is enabled, it doesn't even bother to infer the CSV's schema.
@@ -107,6 +113,8 @@ use std::{
     path::PathBuf,
     time::Duration,
 };
+#[cfg(feature = "encoding")]
+use std::{io::Read, path::Path};
 
 use bytes::Bytes;
 use file_format::FileFormat;
@@ -152,27 +160,29 @@ struct Args {
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct SniffStruct {
-    path:            String,
-    sniff_timestamp: String,
-    last_modified:   String,
-    delimiter_char:  char,
-    header_row:      bool,
-    preamble_rows:   usize,
-    quote_char:      String,
-    flexible:        bool,
-    is_utf8:         bool,
-    detected_mime:   String,
-    detected_kind:   String,
-    retrieved_size:  usize,
-    file_size:       usize,
-    sampled_records: usize,
-    estimated:       bool,
-    num_records:     usize,
-    avg_record_len:  usize,
-    num_fields:      usize,
-    stats_types:     bool,
-    fields:          Vec<String>,
-    types:           Vec<String>,
+    path:                String,
+    sniff_timestamp:     String,
+    last_modified:       String,
+    delimiter_char:      char,
+    header_row:          bool,
+    preamble_rows:       usize,
+    quote_char:          String,
+    flexible:            bool,
+    is_utf8:             bool,
+    encoding:            String,
+    encoding_confidence: f64,
+    detected_mime:       String,
+    detected_kind:       String,
+    retrieved_size:      usize,
+    file_size:           usize,
+    sampled_records:     usize,
+    estimated:           bool,
+    num_records:         usize,
+    avg_record_len:      usize,
+    num_fields:          usize,
+    stats_types:         bool,
+    fields:              Vec<String>,
+    types:               Vec<String>,
 }
 impl fmt::Display for SniffStruct {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -213,6 +223,11 @@ impl fmt::Display for SniffStruct {
         writeln!(f, "Quote Char: {}", self.quote_char)?;
         writeln!(f, "Flexible: {}", self.flexible)?;
         writeln!(f, "Is UTF8: {}", self.is_utf8)?;
+        writeln!(
+            f,
+            "Encoding: {} (confidence: {})",
+            self.encoding, self.encoding_confidence
+        )?;
         writeln!(f, "Detected Mime Type: {}", self.detected_mime)?;
         writeln!(f, "Detected Kind: {}", self.detected_kind)?;
         writeln!(
@@ -650,10 +665,6 @@ async fn get_file_to_sniff(args: &Args, tmpdir: &tempfile::TempDir) -> CliResult
             .keep()
             .map_err(|_| "Cannot keep temporary file")?;
 
-        if !util::isutf8_file(&path)? {
-            return fail_clierror!("stdin input is not UTF8-encoded");
-        }
-
         let metadata = file.metadata()?;
 
         let file_size = metadata.len() as usize;
@@ -678,6 +689,50 @@ async fn get_file_to_sniff(args: &Args, tmpdir: &tempfile::TempDir) -> CliResult
     }
 }
 
+/// Copies what is read from a reader to a file, until the copy is taken.
+#[cfg(feature = "encoding")]
+struct TeeReader<R> {
+    inner: R,
+    copy:  Option<fs::File>,
+}
+
+#[cfg(feature = "encoding")]
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(copy) = self.copy.as_mut() {
+            copy.write_all(&buf[..n])?;
+        }
+        Ok(n)
+    }
+}
+
+/// Counts the records of a transcoded file, writing its header & first `sample_records`
+/// records to `output` if any. The rest of the file is only read to count its records.
+#[cfg(feature = "encoding")]
+fn transcode_sample<R: Read>(
+    rdr: R,
+    conf: &Config,
+    sample_records: u64,
+    output: Option<&Path>,
+) -> CliResult<u64> {
+    let copy = output.map(fs::File::create).transpose()?;
+    let mut rdr = conf.from_reader(TeeReader { inner: rdr, copy });
+    rdr.byte_headers()?;
+    let mut record = csv::ByteRecord::new();
+    let mut n_records = 0_u64;
+    while rdr.read_byte_record(&mut record)? {
+        n_records += 1;
+        if n_records == sample_records {
+            // the reader buffers ahead, so cut the copy at the end of the last sampled record
+            if let Some(copy) = rdr.get_mut().copy.take() {
+                copy.set_len(rdr.position().byte())?;
+            }
+        }
+    }
+    Ok(n_records)
+}
+
 fn cleanup_tempfile(
     tempfile_flag: bool,
     tempfile: String,
@@ -731,9 +786,63 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
     let tmpdir = tempfile::tempdir()?;
 
     let future = get_file_to_sniff(&args, &tmpdir);
-    let sfile_info = block_on(future)?;
+    #[cfg_attr(not(feature = "encoding"), allow(unused_mut))]
+    let mut sfile_info = block_on(future)?;
     let tempfile_to_delete = sfile_info.file_to_sniff.clone();
 
+    // detect the character encoding & its confidence. Text in other encodings is
+    // transcoded to UTF-8, so its mime type & schema can be sniffed. Only the header &
+    // the sampled records are written to a temporary file - the rest of the file is
+    // only decoded to count its records.
+    #[cfg(feature = "encoding")]
+    let (detected_encoding, transcoded_rows) = {
+        let (detected, rdr) = util::sniff_encoding(fs::File::open(&sfile_info.file_to_sniff)?)?;
+        let mut transcoded_rows = None;
+        if detected.encoding != encoding_rs::UTF_8 && detected.confidence > 0.0 {
+            log::info!(
+                "transcoding {} to UTF-8 for sniffing",
+                detected.encoding.name()
+            );
+            let transcode_conf = Config::new(Some(sfile_info.file_to_sniff.clone()).as_ref())
+                .flexible(true)
+                .delimiter(args.flag_delimiter);
+            #[allow(clippy::cast_possible_truncation)]
+            #[allow(clippy::cast_sign_loss)]
+            #[allow(clippy::cast_precision_loss)]
+            let sample_records = if sample_size >= 1.0 {
+                sample_size as u64
+            } else if sample_size.abs() < f64::EPSILON {
+                u64::MAX
+            } else {
+                // its a percentage, so we need the number of records first
+                let n_records = transcode_sample(
+                    util::transcode_reader(
+                        fs::File::open(&sfile_info.file_to_sniff)?,
+                        detected.encoding,
+                    ),
+                    &transcode_conf,
+                    0,
+                    None,
+                )?;
+                (sample_size * n_records as f64).ceil() as u64
+            };
+            let transcoded_path = tmpdir.path().join("qsv_transcoded.csv");
+            transcoded_rows = Some(transcode_sample(
+                util::transcode_reader(rdr, detected.encoding),
+                &transcode_conf,
+                sample_records,
+                Some(&transcoded_path),
+            )?);
+            sfile_info.file_to_sniff = transcoded_path.display().to_string();
+        }
+        (
+            Some((detected.encoding.name(), detected.confidence)),
+            transcoded_rows,
+        )
+    };
+    #[cfg(not(feature = "encoding"))]
+    let (detected_encoding, transcoded_rows): (Option<(&str, f64)>, Option<u64>) = (None, None);
+
     // if we don't have a mime type or its a snappy file and --no-infer is disabled,
     // let's try to infer the mime type
     let file_kind: String;
@@ -810,7 +919,10 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
     let conf = Config::new(Some(sfile_info.file_to_sniff.clone()).as_ref())
         .flexible(true)
         .delimiter(args.flag_delimiter);
-    let n_rows = if sfile_info.downloaded_records == 0 {
+    let n_rows = if let Some(n) = transcoded_rows.filter(|_| sfile_info.downloaded_records == 0) {
+        // the records were counted while transcoding the file
+        n as usize
+    } else if sfile_info.downloaded_records == 0 {
         //if we have the whole file and not just a sample, we can count the number of rows
         match util::count_rows_regular(&conf) {
             Ok(n) => n as usize,
@@ -948,6 +1060,13 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
                 .collect();
 
             let delimiter_char = metadata.dialect.delimiter as char;
+            // without the "encoding" feature, only UTF-8 is recognized
+            let (encoding, encoding_confidence) =
+                detected_encoding.unwrap_or(if metadata.dialect.is_utf8 {
+                    ("UTF-8", 1.0)
+                } else {
+                    ("unknown", 0.0)
+                });
             processed_results = SniffStruct {
                 path: sfile_info.display_path,
                 sniff_timestamp: sniffed_ts,
@@ -960,7 +1079,9 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
                     qsv_sniffer::metadata::Quote::None => "none".into(),
                 },
                 flexible: metadata.dialect.flexible,
-                is_utf8: metadata.dialect.is_utf8,
+                is_utf8: metadata.dialect.is_utf8 && encoding == "UTF-8",
+                encoding: encoding.to_string(),
+                encoding_confidence,
                 detected_mime: if delimiter_char == ',' {
                     "application/csv".to_string()
                 } else {
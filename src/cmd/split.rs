@@ -108,6 +108,7 @@ use serde::Deserialize;
 use crate::{
     config::{Config, Delimiter},
     index::Indexed,
+    seekable::InputFile,
     util::{self, FilenameTemplate},
     CliResult,
 };
@@ -266,7 +267,7 @@ impl Args {
         Ok(())
     }
 
-    fn parallel_split(&self, idx: &Indexed<fs::File, fs::File>) -> CliResult<()> {
+    fn parallel_split(&self, idx: &Indexed<InputFile, fs::File>) -> CliResult<()> {
         let chunk_size;
         let idx_count = idx.count();
 
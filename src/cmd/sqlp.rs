@@ -807,44 +807,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     let (csv_fields, csv_stats) =
                         get_stats_records(&schema_args, util::StatsMode::PolarsSchema)?;
 
-                    let mut schema = Schema::with_capacity(csv_stats.len());
-                    for (idx, stat) in csv_stats.iter().enumerate() {
-                        schema.insert(
-                            PlSmallStr::from_str(
-                                simdutf8::basic::from_utf8(csv_fields.get(idx).unwrap()).unwrap(),
-                            ),
-                            {
-                                let datatype = &stat.r#type;
-                                #[allow(clippy::match_same_arms)]
-                                match datatype.as_str() {
-                                    "String" => polars::datatypes::DataType::String,
-                                    "Integer" => {
-                                        let min = stat.min.as_ref().unwrap();
-                                        let max = stat.max.as_ref().unwrap();
-                                        if min.parse::<i32>().is_ok() && max.parse::<i32>().is_ok()
-                                        {
-                                            polars::datatypes::DataType::Int32
-                                        } else {
-                                            polars::datatypes::DataType::Int64
-                                        }
-                                    },
-                                    "Float" => {
-                                        let min = stat.min.as_ref().unwrap();
-                                        let max = stat.max.as_ref().unwrap();
-                                        if min.parse::<f32>().is_ok() && max.parse::<f32>().is_ok()
-                                        {
-                                            polars::datatypes::DataType::Float32
-                                        } else {
-                                            polars::datatypes::DataType::Float64
-                                        }
-                                    },
-                                    "Boolean" => polars::datatypes::DataType::Boolean,
-                                    "Date" => polars::datatypes::DataType::Date,
-                                    _ => polars::datatypes::DataType::String,
-                                }
-                            },
-                        );
-                    }
+                    let schema = util::polars_schema_from_stats(&csv_fields, &csv_stats);
                     let stats_schema = Arc::new(schema);
                     let stats_schema_json = serde_json::to_string_pretty(&stats_schema)?;
 
@@ -264,7 +264,7 @@ use threadpool::ThreadPool;
 
 use self::FieldType::{TDate, TDateTime, TFloat, TInteger, TNull, TString};
 use crate::{
-    config::{get_delim_by_extension, Config, Delimiter},
+    config::{get_delim_by_extension, Compression, Config, Delimiter},
     select::{SelectColumns, Selection},
     util, CliResult,
 };
@@ -577,18 +577,19 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
 
     // find the delimiter to use based on the extension of the output file
-    // and if we need to snappy compress the output
-    let (output_extension, output_delim, snappy) = if let Some(ref output_path) = args.flag_output {
-        get_delim_by_extension(Path::new(&output_path), b',')
-    } else {
-        (String::new(), b',', false)
-    };
+    // and if we need to compress the output
+    let (output_extension, output_delim, compression) =
+        if let Some(ref output_path) = args.flag_output {
+            get_delim_by_extension(Path::new(&output_path), b',')
+        } else {
+            (String::new(), b',', Compression::None)
+        };
     let stats_csv_tempfile_fname = format!(
-        "{stem}.{prime_ext}{snappy_ext}",
+        "{stem}.{prime_ext}{compression_ext}",
         //safety: we know the tempfile is a valid NamedTempFile, so we can use unwrap
         stem = stats_csv_tempfile.path().to_str().unwrap(),
         prime_ext = output_extension,
-        snappy_ext = if snappy { ".sz" } else { "" }
+        compression_ext = compression.extension()
     );
 
     // we will write the stats to a temp file
@@ -606,10 +607,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // read from stdin and write to a temp file
         log::info!("Reading from stdin");
         let mut stdin_file = NamedTempFile::new()?;
-        let stdin = std::io::stdin();
-        let mut stdin_handle = stdin.lock();
-        std::io::copy(&mut stdin_handle, &mut stdin_file)?;
-        drop(stdin_handle);
+        // the config's reader decodes compressed stdin
+        std::io::copy(&mut rconfig.io_reader()?, &mut stdin_file)?;
         let (_file, tempfile_path) = stdin_file
             .keep()
             .or(Err("Cannot keep temporary file".to_string()))?;
@@ -49,6 +49,16 @@ Print dump to stdout.
 
   $ qsv to postgres --dump - file1.csv file2.csv
 
+Append the rows of a daily file to the `sales` table, creating it if it does not exist.
+
+  $ qsv to postgres 'env=DATABASE_URL' --mode append sales.csv
+
+Insert new rows & update existing rows with the same `id`, and index the `region` column
+and the `customer` & `order_date` columns.
+
+  $ qsv to postgres 'env=DATABASE_URL' --mode upsert --primary-key id \
+      --index region,customer+order_date sales.csv
+
 
 SQLITE
 Convert to sqlite db file. Will be created if it does not exist.
@@ -86,10 +96,48 @@ Print dump to stdout.
 
   $ qsv to sqlite --dump - file1.csv file2.csv
 
+Upsert the rows of a daily file into the `sales` table using the `id` primary key.
+
+  $ qsv to sqlite test.db --mode upsert --primary-key id sales.csv
+
+Also create an FTS5 full-text search table `sales_fts` over the `title` & `notes` columns.
+
+  $ qsv to sqlite test.db --mode upsert --primary-key id --fts title,notes sales.csv
+
+  $ sqlite3 test.db "SELECT * FROM sales WHERE rowid IN \
+      (SELECT rowid FROM sales_fts WHERE sales_fts MATCH 'refund')"
+
+LOAD MODES
+By default, postgres & sqlite tables are created, dropped (--drop) or evolved (--evolve) and
+loaded by the conversion. To keep a database current with new files, use --mode:
+
+  replace - drop the tables and load them from scratch. Same as --drop.
+  append  - insert the rows into the existing tables. Tables that do not exist are created.
+            With --evolve, columns that are missing from an existing table are added.
+  upsert  - same as append, except that rows with the same --primary-key as an existing
+            row replace it. If a file has several rows with the same key, the last one wins.
+
+append & upsert first load the files into staging tables (a temporary sqlite database, or a
+temporary `qsv_staging_<pid>` postgres schema), and then insert them into the tables in a
+single transaction. They cannot be used with --dump.
+
+--primary-key, --index and --fts are applied to every loaded table that has the columns.
+
 
 XLSX
 Convert to new xlsx file.
 
+The column types are taken from the stats cache of each input if it's current, so integers,
+floats, dates, datetimes and true/false values are written as native Excel cells with
+number formats instead of text. Otherwise, all the cells are written as text, unless
+--infer-types or --data-dictionary is set - which run `stats` to infer the column types,
+writing its stats cache files (<input>.stats.csv & <input>.stats.csv.data.jsonl) next to
+each input. Values that don't match their column type are written as text.
+
+Each sheet has a bold, frozen header row, an autofilter and column widths fitted to the
+data. Inputs larger than 64MB are written in constant memory mode, flushing each row to
+disk as it's written, so their column widths are not fitted.
+
 Example:
 
 Load `file1.csv` and `file2.csv' into xlsx file.
@@ -106,6 +154,10 @@ Load files listed in the 'ourdata.infile-list' into xlsx file.
 
     $ qsv to xlsx output.xlsx ourdata.infile-list
 
+Also add a "Data Dictionary" sheet describing the columns of every sheet.
+
+    $ qsv to xlsx output.xlsx --data-dictionary file1.csv file2.csv
+
 DATAPACKAGE
 Generate a datapackage, which contains stats and information about what is in the CSV files.
 
@@ -152,8 +204,24 @@ To options:
     -i --pipe              Allow piping from stdin (using `-`) or from a named pipe.
     -p --separator <arg>   For xlsx, use this character to help truncate xlsx sheet names.
                            Defaults to space.
+    --infer-types          For xlsx, run `stats` to infer the column types of the inputs
+                           without a current stats cache. Note that this writes the stats
+                           cache files next to the inputs.
+    --data-dictionary      For xlsx, add a "Data Dictionary" sheet with the type, nullcount,
+                           cardinality, min/max & other summary statistics of every column.
+                           Implies --infer-types.
     -j, --jobs <arg>       The number of jobs to run in parallel.
                            When not set, the number of jobs is set to the number of CPUs detected.
+    --mode <mode>          How to load the data into the tables - replace, append or upsert.
+                           See LOAD MODES above. (postgres/sqlite only).
+    --primary-key <cols>   The comma-separated key columns of the tables. A unique index is
+                           created on them. Required for --mode upsert. (postgres/sqlite only).
+    --index <cols>         Create an index on each of these comma-separated columns.
+                           Join columns with + for a multi-column index,
+                           e.g. "region,customer+order_date". (postgres/sqlite only).
+    --fts <cols>           Create an FTS5 full-text search table named <table>_fts over
+                           these comma-separated text columns. It is rebuilt after every
+                           load. (sqlite only).
                            
 Common options:
     -h, --help             Display this message
@@ -161,17 +229,26 @@ Common options:
                            Must be a single character. (default: ,)
 "#;
 
-use std::{io::Write, path::PathBuf};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use chrono::{Datelike, Timelike};
 use csvs_convert::{
-    csvs_to_postgres_with_options, csvs_to_sqlite_with_options, csvs_to_xlsx_with_options,
-    make_datapackage, DescribeOptions, Options,
+    csvs_to_postgres_with_options, csvs_to_sqlite_with_options, make_datapackage, DescribeOptions,
+    Options,
 };
-use log::debug;
+use log::{debug, warn};
+use qsv_dateparser::parse_with_preference;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
 use serde::Deserialize;
 
 use crate::{
-    config::{self, Delimiter},
+    cmd::stats::StatsData,
+    config::{self, Config, Delimiter},
+    select::SelectColumns,
     util,
     util::process_input,
     CliError, CliResult,
@@ -180,27 +257,128 @@ use crate::{
 #[allow(dead_code)]
 #[derive(Deserialize)]
 struct Args {
-    cmd_postgres:       bool,
-    arg_postgres:       Option<String>,
-    cmd_sqlite:         bool,
-    arg_sqlite:         Option<String>,
-    cmd_xlsx:           bool,
-    arg_xlsx:           Option<String>,
-    cmd_datapackage:    bool,
-    arg_datapackage:    Option<String>,
-    arg_input:          Vec<PathBuf>,
-    flag_delimiter:     Option<Delimiter>,
-    flag_schema:        Option<String>,
-    flag_separator:     Option<String>,
-    flag_dump:          bool,
-    flag_drop:          bool,
-    flag_evolve:        bool,
-    flag_stats:         bool,
-    flag_stats_csv:     Option<String>,
-    flag_jobs:          Option<usize>,
-    flag_print_package: bool,
-    flag_quiet:         bool,
-    flag_pipe:          bool,
+    cmd_postgres:         bool,
+    arg_postgres:         Option<String>,
+    cmd_sqlite:           bool,
+    arg_sqlite:           Option<String>,
+    cmd_xlsx:             bool,
+    arg_xlsx:             Option<String>,
+    cmd_datapackage:      bool,
+    arg_datapackage:      Option<String>,
+    arg_input:            Vec<PathBuf>,
+    flag_delimiter:       Option<Delimiter>,
+    flag_schema:          Option<String>,
+    flag_separator:       Option<String>,
+    flag_dump:            bool,
+    flag_drop:            bool,
+    flag_evolve:          bool,
+    flag_stats:           bool,
+    flag_stats_csv:       Option<String>,
+    flag_jobs:            Option<usize>,
+    flag_print_package:   bool,
+    flag_quiet:           bool,
+    flag_pipe:            bool,
+    flag_mode:            Option<String>,
+    flag_primary_key:     Option<String>,
+    flag_index:           Option<String>,
+    flag_fts:             Option<String>,
+    flag_infer_types:     bool,
+    flag_data_dictionary: bool,
+}
+
+/// How the data is loaded into existing postgres & sqlite tables.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LoadMode {
+    Replace,
+    Append,
+    Upsert,
+}
+
+impl FromStr for LoadMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "replace" => Ok(LoadMode::Replace),
+            "append" => Ok(LoadMode::Append),
+            "upsert" => Ok(LoadMode::Upsert),
+            _ => Err(format!(
+                "Invalid load mode: {s}. Valid modes are: replace, append, upsert."
+            )),
+        }
+    }
+}
+
+/// The --mode, --primary-key, --index & --fts options, which qsv applies itself
+/// after csvs_convert has loaded the data.
+struct DbOptions {
+    mode:        Option<LoadMode>,
+    primary_key: Vec<String>,
+    indexes:     Vec<Vec<String>>,
+    fts:         Vec<String>,
+}
+
+impl DbOptions {
+    fn new(args: &Args) -> CliResult<Self> {
+        let mode = match args.flag_mode {
+            Some(ref mode) => match LoadMode::from_str(mode) {
+                Ok(mode) => Some(mode),
+                Err(e) => return fail_incorrectusage_clierror!("{e}"),
+            },
+            None => None,
+        };
+        let db_options = DbOptions {
+            mode,
+            primary_key: split_columns(args.flag_primary_key.as_deref(), ','),
+            indexes: split_columns(args.flag_index.as_deref(), ',')
+                .iter()
+                .map(|index| split_columns(Some(index), '+'))
+                .collect(),
+            fts: split_columns(args.flag_fts.as_deref(), ','),
+        };
+
+        if db_options.is_set() {
+            if !(args.cmd_postgres || args.cmd_sqlite) {
+                return fail_incorrectusage_clierror!(
+                    "--mode, --primary-key, --index and --fts only work with postgres and sqlite."
+                );
+            }
+            if args.flag_dump {
+                return fail_incorrectusage_clierror!(
+                    "--mode, --primary-key, --index and --fts cannot be used with --dump."
+                );
+            }
+        }
+        if db_options.mode == Some(LoadMode::Upsert) && db_options.primary_key.is_empty() {
+            return fail_incorrectusage_clierror!("--mode upsert requires --primary-key.");
+        }
+        if args.cmd_postgres && !db_options.fts.is_empty() {
+            return fail_incorrectusage_clierror!("--fts only works with sqlite.");
+        }
+        Ok(db_options)
+    }
+
+    fn is_set(&self) -> bool {
+        self.mode.is_some()
+            || !self.primary_key.is_empty()
+            || !self.indexes.is_empty()
+            || !self.fts.is_empty()
+    }
+
+    /// append & upsert load the data into staging tables first
+    fn uses_staging(&self) -> bool {
+        matches!(self.mode, Some(LoadMode::Append | LoadMode::Upsert))
+    }
+}
+
+fn split_columns(columns: Option<&str>, separator: char) -> Vec<String> {
+    columns
+        .unwrap_or_default()
+        .split(separator)
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .map(String::from)
+        .collect()
 }
 
 impl From<csvs_convert::Error> for CliError {
@@ -215,20 +393,33 @@ impl From<csvs_convert::DescribeError> for CliError {
     }
 }
 
+impl From<rusqlite::Error> for CliError {
+    fn from(err: rusqlite::Error) -> CliError {
+        CliError::Other(format!("SQLite error: {err}"))
+    }
+}
+
+impl From<postgres::Error> for CliError {
+    fn from(err: postgres::Error) -> CliError {
+        CliError::Other(format!("Postgres error: {err}"))
+    }
+}
+
 static EMPTY_STDIN_ERRMSG: &str =
     "No data on stdin. Need to add connection string as first argument then the input CSVs";
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     debug!("'to' command running");
+    let db_options = DbOptions::new(&args)?;
     let mut options = Options::builder()
         .delimiter(args.flag_delimiter.map(config::Delimiter::as_byte))
-        .schema(args.flag_schema.unwrap_or_default())
-        .seperator(args.flag_separator.unwrap_or_else(|| " ".into()))
+        .schema(args.flag_schema.clone().unwrap_or_default())
+        .seperator(args.flag_separator.clone().unwrap_or_else(|| " ".into()))
         .evolve(args.flag_evolve)
         .stats(args.flag_stats)
         .pipe(args.flag_pipe)
-        .stats_csv(args.flag_stats_csv.unwrap_or_default())
+        .stats_csv(args.flag_stats_csv.clone().unwrap_or_default())
         .drop(args.flag_drop)
         .threads(util::njobs(args.flag_jobs))
         .build();
@@ -244,11 +435,51 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             options.dump_file = args.arg_postgres.expect("checked above");
             output = csvs_to_postgres_with_options(String::new(), arg_input, options)?;
         } else {
-            output = csvs_to_postgres_with_options(
-                args.arg_postgres.expect("checked above"),
+            let connection_string = args.arg_postgres.expect("checked above");
+            let target_schema = match args.flag_schema {
+                Some(ref schema) if !schema.is_empty() => schema.clone(),
+                _ => "public".to_string(),
+            };
+            let staging_schema = if db_options.uses_staging() {
+                let staging_schema = format!("qsv_staging_{}", std::process::id());
+                options.schema.clone_from(&staging_schema);
+                options.drop = true;
+                options.evolve = false;
+                Some(staging_schema)
+            } else {
+                if db_options.mode == Some(LoadMode::Replace) {
+                    options.drop = true;
+                }
+                None
+            };
+            output = match csvs_to_postgres_with_options(
+                connection_string.clone(),
                 arg_input,
                 options,
-            )?;
+            ) {
+                Ok(output) => output,
+                Err(e) => {
+                    // the staging schema may have been created before the load failed
+                    if let Some(ref staging_schema) = staging_schema {
+                        if let Err(drop_err) =
+                            drop_postgres_schema(&connection_string, staging_schema)
+                        {
+                            wwarn!("Cannot drop the staging schema {staging_schema}: {drop_err}");
+                        }
+                    }
+                    return Err(e.into());
+                },
+            };
+            if db_options.is_set() {
+                finish_postgres(
+                    &connection_string,
+                    staging_schema.as_deref(),
+                    &target_schema,
+                    &loaded_tables(&output),
+                    &db_options,
+                    args.flag_evolve,
+                )?;
+            }
         }
         debug!("conversion to postgres complete");
     } else if args.cmd_sqlite {
@@ -258,19 +489,52 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             options.dump_file = args.arg_sqlite.expect("checked above");
             output = csvs_to_sqlite_with_options(String::new(), arg_input, options)?;
         } else {
-            output = csvs_to_sqlite_with_options(
-                args.arg_sqlite.expect("checked above"),
-                arg_input,
-                options,
-            )?;
+            let sqlite_path = args.arg_sqlite.expect("checked above");
+            let staging_path = if db_options.uses_staging() {
+                options.drop = true;
+                options.evolve = false;
+                Some(tmpdir.path().join("qsv_staging.db"))
+            } else {
+                if db_options.mode == Some(LoadMode::Replace) {
+                    options.drop = true;
+                }
+                None
+            };
+            let load_path = staging_path.as_ref().map_or_else(
+                || sqlite_path.clone(),
+                |path| path.to_string_lossy().to_string(),
+            );
+            output = csvs_to_sqlite_with_options(load_path, arg_input, options)?;
+            if db_options.is_set() {
+                finish_sqlite(
+                    Path::new(&sqlite_path),
+                    staging_path.as_deref(),
+                    &loaded_tables(&output),
+                    &db_options,
+                    args.flag_evolve,
+                )?;
+            }
         }
         debug!("conversion to sqlite complete");
     } else if args.cmd_xlsx {
         debug!("converting to xlsx");
         arg_input = process_input(arg_input, &tmpdir, EMPTY_STDIN_ERRMSG)?;
 
-        output =
-            csvs_to_xlsx_with_options(args.arg_xlsx.expect("checked above"), arg_input, options)?;
+        output = write_xlsx(
+            &args.arg_xlsx.clone().expect("checked above"),
+            &arg_input,
+            &args,
+        )?;
+        // the field summary is built from the stats write_xlsx already has.
+        // Only describe the inputs again if the full datapackage is needed
+        if args.flag_print_package || args.flag_stats || args.flag_stats_csv.is_some() {
+            let describe_options = DescribeOptions::builder()
+                .delimiter(options.delimiter)
+                .stats(options.stats)
+                .threads(options.threads)
+                .stats_csv(options.stats_csv.clone());
+            output = make_datapackage(arg_input, PathBuf::new(), &describe_options.build())?;
+        }
         debug!("conversion to xlsx complete");
     } else if args.cmd_datapackage {
         debug!("creating datapackage");
@@ -357,3 +621,767 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     Ok(())
 }
+
+/// the names of the tables loaded by csvs_convert
+fn loaded_tables(output: &serde_json::Value) -> Vec<String> {
+    output["resources"]
+        .as_array()
+        .map(|resources| {
+            resources
+                .iter()
+                .filter_map(|resource| resource["name"].as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// quotes an identifier for postgres & sqlite
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_list<S: AsRef<str>>(idents: &[S]) -> String {
+    idents
+        .iter()
+        .map(|ident| quote_ident(ident.as_ref()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// the ON CONFLICT clause of an upsert. The same syntax works for postgres & sqlite
+fn upsert_clause(columns: &[String], primary_key: &[String]) -> String {
+    let updates: Vec<String> = columns
+        .iter()
+        .filter(|column| !primary_key.contains(column))
+        .map(|column| format!("{col} = excluded.{col}", col = quote_ident(column)))
+        .collect();
+    if updates.is_empty() {
+        format!(" ON CONFLICT ({}) DO NOTHING", quote_list(primary_key))
+    } else {
+        format!(
+            " ON CONFLICT ({}) DO UPDATE SET {}",
+            quote_list(primary_key),
+            updates.join(", ")
+        )
+    }
+}
+
+/// the unique index on the --primary-key columns. It's also required by ON CONFLICT
+fn primary_key_index(table: &str, table_ref: &str, primary_key: &[String]) -> String {
+    format!(
+        "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {table_ref} ({})",
+        quote_ident(&format!("{table}_pk_idx")),
+        quote_list(primary_key)
+    )
+}
+
+/// the CREATE INDEX statements for the --primary-key & --index options.
+/// Indexes on columns the table does not have are skipped.
+fn index_statements(
+    table: &str,
+    table_ref: &str,
+    columns: &[String],
+    db_options: &DbOptions,
+) -> Vec<String> {
+    let has_columns = |index: &[String]| index.iter().all(|column| columns.contains(column));
+
+    let mut statements = Vec::new();
+    if !db_options.primary_key.is_empty() && has_columns(&db_options.primary_key) {
+        statements.push(primary_key_index(table, table_ref, &db_options.primary_key));
+    }
+    for index in &db_options.indexes {
+        if has_columns(index) {
+            statements.push(format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {table_ref} ({})",
+                quote_ident(&format!("{table}_{}_idx", index.join("_"))),
+                quote_list(index)
+            ));
+        } else {
+            warn!("Table '{table}' does not have the index column/s {index:?}. Skipping index.");
+        }
+    }
+    statements
+}
+
+/// checks that every table has the --primary-key columns needed to upsert
+fn check_primary_key(table: &str, columns: &[String], primary_key: &[String]) -> CliResult<()> {
+    match primary_key.iter().find(|column| !columns.contains(column)) {
+        Some(column) => {
+            fail_clierror!("Table '{table}' does not have primary key column '{column}'.")
+        },
+        None => Ok(()),
+    }
+}
+
+fn sqlite_columns(
+    conn: &rusqlite::Connection,
+    schema: &str,
+    table: &str,
+) -> CliResult<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!(
+        "PRAGMA {schema}.table_info({})",
+        quote_ident(table)
+    ))?;
+    let columns = stmt
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<(String, String)>, _>>()?;
+    Ok(columns)
+}
+
+/// moves the staged tables into the sqlite database when appending or upserting,
+/// and creates the indexes & full-text search tables
+fn finish_sqlite(
+    sqlite_path: &Path,
+    staging_path: Option<&Path>,
+    tables: &[String],
+    db_options: &DbOptions,
+    evolve: bool,
+) -> CliResult<()> {
+    let mut conn = rusqlite::Connection::open(sqlite_path)?;
+    if let Some(staging_path) = staging_path {
+        conn.execute(
+            "ATTACH DATABASE ?1 AS staging",
+            [staging_path.to_string_lossy().to_string()],
+        )?;
+    }
+
+    let tx = conn.transaction()?;
+    for table in tables {
+        let qtable = quote_ident(table);
+
+        if staging_path.is_some() {
+            let staging_columns = sqlite_columns(&tx, "staging", table)?;
+            let target_columns = sqlite_columns(&tx, "main", table)?;
+            if target_columns.is_empty() {
+                // create the table with the column types inferred by csvs_convert
+                let create_sql: String = tx.query_row(
+                    "SELECT sql FROM staging.sqlite_master WHERE type = 'table' AND name = ?1",
+                    [table],
+                    |row| row.get(0),
+                )?;
+                tx.execute_batch(&create_sql)?;
+            } else if evolve {
+                for (column, column_type) in &staging_columns {
+                    if !target_columns.iter().any(|(name, _)| name == column) {
+                        tx.execute_batch(&format!(
+                            "ALTER TABLE main.{qtable} ADD COLUMN {} {column_type}",
+                            quote_ident(column)
+                        ))?;
+                    }
+                }
+            }
+
+            let columns: Vec<String> = staging_columns.into_iter().map(|(name, _)| name).collect();
+            let column_list = quote_list(&columns);
+            let mut insert_sql = format!(
+                "INSERT INTO main.{qtable} ({column_list}) SELECT {column_list} FROM \
+                 staging.{qtable}"
+            );
+            if db_options.mode == Some(LoadMode::Upsert) {
+                check_primary_key(table, &columns, &db_options.primary_key)?;
+                tx.execute_batch(&primary_key_index(table, &qtable, &db_options.primary_key))?;
+                // "WHERE true" avoids a parsing ambiguity of INSERT ... SELECT ... ON CONFLICT
+                insert_sql.push_str(" WHERE true");
+                insert_sql.push_str(&upsert_clause(&columns, &db_options.primary_key));
+            }
+            tx.execute_batch(&insert_sql)?;
+        }
+
+        let columns: Vec<String> = sqlite_columns(&tx, "main", table)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        for statement in index_statements(table, &qtable, &columns, db_options) {
+            tx.execute_batch(&statement)?;
+        }
+
+        if !db_options.fts.is_empty() {
+            if db_options.fts.iter().all(|column| columns.contains(column)) {
+                let fts_table = quote_ident(&format!("{table}_fts"));
+                tx.execute_batch(&format!(
+                    "DROP TABLE IF EXISTS {fts_table};
+                     CREATE VIRTUAL TABLE {fts_table} USING fts5({}, content='{}');
+                     INSERT INTO {fts_table}({fts_table}) VALUES('rebuild');",
+                    quote_list(&db_options.fts),
+                    table.replace('\'', "''")
+                ))?;
+            } else {
+                warn!(
+                    "Table '{table}' does not have the full-text search column/s {:?}. Skipping \
+                     full-text search table.",
+                    db_options.fts
+                );
+            }
+        }
+    }
+    tx.commit()?;
+
+    if staging_path.is_some() {
+        conn.execute_batch("DETACH DATABASE staging")?;
+    }
+    Ok(())
+}
+
+/// gets the connection string, reading it from an environment variable with `env=MY_ENV_VAR`
+pub fn postgres_connection_string(connection_string: &str) -> CliResult<String> {
+    match connection_string.strip_prefix("env=") {
+        Some(env_var) => match std::env::var(env_var) {
+            Ok(connection_string) => Ok(connection_string),
+            Err(_) => fail_clierror!("Environment variable {env_var} is not set."),
+        },
+        None => Ok(connection_string.to_string()),
+    }
+}
+
+fn postgres_columns(
+    tx: &mut postgres::Transaction<'_>,
+    schema: &str,
+    table: &str,
+) -> CliResult<Vec<(String, String)>> {
+    let rows = tx.query(
+        "SELECT column_name::text, data_type::text FROM information_schema.columns WHERE \
+         table_schema = $1 AND table_name = $2 ORDER BY ordinal_position",
+        &[&schema, &table],
+    )?;
+    Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+}
+
+/// moves the staged tables into the target schema when appending or upserting,
+/// and creates the indexes. The staging schema is always dropped.
+fn finish_postgres(
+    connection_string: &str,
+    staging_schema: Option<&str>,
+    target_schema: &str,
+    tables: &[String],
+    db_options: &DbOptions,
+    evolve: bool,
+) -> CliResult<()> {
+    let mut client = postgres::Client::connect(
+        &postgres_connection_string(connection_string)?,
+        postgres::NoTls,
+    )?;
+
+    let result = load_postgres_tables(
+        &mut client,
+        staging_schema,
+        target_schema,
+        tables,
+        db_options,
+        evolve,
+    );
+    if let Some(staging_schema) = staging_schema {
+        client.batch_execute(&format!(
+            "DROP SCHEMA IF EXISTS {} CASCADE",
+            quote_ident(staging_schema)
+        ))?;
+    }
+    result
+}
+
+/// drops a schema and its tables, on a new connection
+fn drop_postgres_schema(connection_string: &str, schema: &str) -> CliResult<()> {
+    let mut client = postgres::Client::connect(
+        &postgres_connection_string(connection_string)?,
+        postgres::NoTls,
+    )?;
+    client.batch_execute(&format!(
+        "DROP SCHEMA IF EXISTS {} CASCADE",
+        quote_ident(schema)
+    ))?;
+    Ok(())
+}
+
+fn load_postgres_tables(
+    client: &mut postgres::Client,
+    staging_schema: Option<&str>,
+    target_schema: &str,
+    tables: &[String],
+    db_options: &DbOptions,
+    evolve: bool,
+) -> CliResult<()> {
+    let qschema = quote_ident(target_schema);
+
+    let mut tx = client.transaction()?;
+    tx.batch_execute(&format!("CREATE SCHEMA IF NOT EXISTS {qschema}"))?;
+    for table in tables {
+        let table_ref = format!("{qschema}.{}", quote_ident(table));
+
+        if let Some(staging_schema) = staging_schema {
+            let staging_ref = format!("{}.{}", quote_ident(staging_schema), quote_ident(table));
+            let staging_columns = postgres_columns(&mut tx, staging_schema, table)?;
+            let target_columns = postgres_columns(&mut tx, target_schema, table)?;
+            if target_columns.is_empty() {
+                tx.batch_execute(&format!("CREATE TABLE {table_ref} (LIKE {staging_ref})"))?;
+            } else if evolve {
+                for (column, data_type) in &staging_columns {
+                    if !target_columns.iter().any(|(name, _)| name == column) {
+                        tx.batch_execute(&format!(
+                            "ALTER TABLE {table_ref} ADD COLUMN {} {data_type}",
+                            quote_ident(column)
+                        ))?;
+                    }
+                }
+            }
+
+            let columns: Vec<String> = staging_columns.into_iter().map(|(name, _)| name).collect();
+            let column_list = quote_list(&columns);
+            let insert_sql = if db_options.mode == Some(LoadMode::Upsert) {
+                check_primary_key(table, &columns, &db_options.primary_key)?;
+                tx.batch_execute(&primary_key_index(
+                    table,
+                    &table_ref,
+                    &db_options.primary_key,
+                ))?;
+                // a row can only be upserted once per statement, so only keep the
+                // last row of each key. ctid follows the load order of the staging table
+                let key_list = quote_list(&db_options.primary_key);
+                format!(
+                    "INSERT INTO {table_ref} ({column_list}) SELECT DISTINCT ON ({key_list}) \
+                     {column_list} FROM {staging_ref} ORDER BY {key_list}, ctid DESC{}",
+                    upsert_clause(&columns, &db_options.primary_key)
+                )
+            } else {
+                format!(
+                    "INSERT INTO {table_ref} ({column_list}) SELECT {column_list} FROM \
+                     {staging_ref}"
+                )
+            };
+            tx.batch_execute(&insert_sql)?;
+        }
+
+        let columns: Vec<String> = postgres_columns(&mut tx, target_schema, table)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        for statement in index_statements(table, &table_ref, &columns, db_options) {
+            tx.batch_execute(&statement)?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// the max number of rows in an xlsx sheet, including the header row
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+/// the max length of an xlsx sheet name
+const XLSX_MAX_SHEETNAME_LEN: usize = 31;
+/// integers with more digits than this lose precision as excel numbers
+const XLSX_MAX_INTEGER_DIGITS: usize = 15;
+/// inputs larger than this are written in constant memory mode
+const XLSX_CONSTANT_MEMORY_SIZE: u64 = 64 * 1024 * 1024;
+
+impl From<XlsxError> for CliError {
+    fn from(err: XlsxError) -> CliError {
+        CliError::Other(format!("XLSX error: {err}"))
+    }
+}
+
+/// the excel cell type of a column, inferred from its stats
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum CellKind {
+    Text,
+    Integer,
+    Float,
+    Date,
+    DateTime,
+    Boolean,
+}
+
+impl CellKind {
+    fn from_stats(stats: &StatsData) -> Self {
+        let is_bool = |value: &Option<String>| {
+            value
+                .as_deref()
+                .is_some_and(|v| v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("false"))
+        };
+        match stats.r#type.as_str() {
+            "Integer" => CellKind::Integer,
+            "Float" => CellKind::Float,
+            "Date" => CellKind::Date,
+            "DateTime" => CellKind::DateTime,
+            "Boolean" | "String" if is_bool(&stats.min) && is_bool(&stats.max) => CellKind::Boolean,
+            _ => CellKind::Text,
+        }
+    }
+
+    /// the excel number format of the column's cells, if any
+    fn num_format(self, stats: Option<&StatsData>) -> Option<String> {
+        match self {
+            CellKind::Integer => Some("0".to_string()),
+            CellKind::Float => match stats.and_then(|stats| stats.max_precision) {
+                Some(0) => Some("0".to_string()),
+                Some(precision) => Some(format!("0.{}", "0".repeat(precision.min(15) as usize))),
+                None => None,
+            },
+            CellKind::Date => Some("yyyy-mm-dd".to_string()),
+            CellKind::DateTime => Some("yyyy-mm-dd hh:mm:ss".to_string()),
+            CellKind::Text | CellKind::Boolean => None,
+        }
+    }
+
+    /// the format of the column's cells
+    fn format(self, stats: Option<&StatsData>) -> Format {
+        self.num_format(stats)
+            .map_or_else(Format::new, |num_format| {
+                Format::new().set_num_format(num_format)
+            })
+    }
+
+    /// the Frictionless type of the column, for the field summary
+    const fn field_type(self) -> &'static str {
+        match self {
+            CellKind::Text => "string",
+            CellKind::Integer => "integer",
+            CellKind::Float => "number",
+            CellKind::Date => "date",
+            CellKind::DateTime => "datetime",
+            CellKind::Boolean => "boolean",
+        }
+    }
+}
+
+/// writes the CSV files to a new xlsx workbook, one sheet per file.
+/// Returns the field summary of every sheet, in the shape of a datapackage.
+fn write_xlsx(xlsx_path: &str, inputs: &[PathBuf], args: &Args) -> CliResult<serde_json::Value> {
+    let separator = args.flag_separator.as_deref().unwrap_or(" ");
+    let header_format = Format::new().set_bold();
+    let mut workbook = Workbook::new();
+    let mut sheet_names: Vec<String> = Vec::with_capacity(inputs.len());
+    let mut dictionary: Vec<(String, Vec<String>, Vec<StatsData>)> = Vec::new();
+    let mut resources: Vec<serde_json::Value> = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        let input_path = input.to_string_lossy().to_string();
+        let sheet_name = xlsx_sheet_name(input, separator, &sheet_names);
+        debug!("writing {input_path} to sheet {sheet_name}");
+
+        let rconfig = Config::new(Some(&input_path)).delimiter(args.flag_delimiter);
+        let mut rdr = rconfig.reader()?;
+        let headers: Vec<String> = rdr
+            .byte_headers()?
+            .iter()
+            .map(|header| String::from_utf8_lossy(header).to_string())
+            .collect();
+
+        let stats = xlsx_column_stats(&input_path, args, headers.len())?;
+        let kinds: Vec<CellKind> = if stats.is_empty() {
+            vec![CellKind::Text; headers.len()]
+        } else {
+            stats.iter().map(CellKind::from_stats).collect()
+        };
+        let formats: Vec<Format> = kinds
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| kind.format(stats.get(i)))
+            .collect();
+
+        // large inputs are flushed to disk a row at a time, instead of being kept in memory
+        let constant_memory = std::fs::metadata(input)
+            .is_ok_and(|metadata| metadata.len() > XLSX_CONSTANT_MEMORY_SIZE);
+        let worksheet = if constant_memory {
+            workbook.add_worksheet_with_constant_memory()
+        } else {
+            workbook.add_worksheet()
+        };
+        worksheet.set_name(&sheet_name)?;
+        for (col, header) in headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, header, &header_format)?;
+        }
+
+        let mut row: u32 = 0;
+        let mut record = csv::StringRecord::new();
+        while rdr.read_record(&mut record)? {
+            row += 1;
+            if row >= XLSX_MAX_ROWS {
+                return fail_clierror!(
+                    "{input_path} has more than {} rows, the maximum for an xlsx sheet.",
+                    XLSX_MAX_ROWS - 1
+                );
+            }
+            for (col, value) in record.iter().enumerate() {
+                if value.is_empty() {
+                    continue;
+                }
+                let kind = kinds.get(col).copied().unwrap_or(CellKind::Text);
+                let format = formats.get(col);
+                write_xlsx_cell(worksheet, row, col as u16, value, kind, format)?;
+            }
+        }
+
+        worksheet.set_freeze_panes(1, 0)?;
+        if !headers.is_empty() {
+            worksheet.autofilter(0, 0, row, (headers.len() - 1) as u16)?;
+        }
+        // autofit needs all the rows, which constant memory mode has already flushed
+        if !constant_memory {
+            worksheet.autofit();
+        }
+
+        let fields: Vec<serde_json::Value> = headers
+            .iter()
+            .zip(&kinds)
+            .enumerate()
+            .map(|(i, (header, kind))| {
+                serde_json::json!({
+                    "name": header,
+                    "type": kind.field_type(),
+                    "format": kind.num_format(stats.get(i)).unwrap_or_default(),
+                })
+            })
+            .collect();
+        resources.push(serde_json::json!({
+            "name": sheet_name,
+            "row_count": row,
+            "schema": {"fields": fields},
+        }));
+
+        sheet_names.push(sheet_name.clone());
+        if args.flag_data_dictionary {
+            dictionary.push((sheet_name, headers, stats));
+        }
+    }
+
+    if args.flag_data_dictionary {
+        let sheet_name = xlsx_unique_sheet_name("Data Dictionary".to_string(), &sheet_names);
+        write_data_dictionary(workbook.add_worksheet(), &sheet_name, &dictionary)?;
+    }
+
+    workbook.save(xlsx_path)?;
+    Ok(serde_json::json!({ "resources": resources }))
+}
+
+/// gets the stats of every column from the stats cache, running stats if it's not
+/// current and --infer-types or --data-dictionary is set.
+/// Returns an empty Vec if the stats are not available.
+fn xlsx_column_stats(input: &str, args: &Args, num_fields: usize) -> CliResult<Vec<StatsData>> {
+    let schema_args = util::SchemaArgs {
+        flag_enum_threshold:  0,
+        flag_ignore_case:     false,
+        flag_strict_dates:    false,
+        flag_pattern_columns: SelectColumns::parse("")?,
+        flag_dates_whitelist: "all".to_string(),
+        flag_prefer_dmy:      false,
+        flag_force:           false,
+        flag_stdout:          false,
+        flag_jobs:            Some(util::njobs(args.flag_jobs)),
+        flag_no_headers:      false,
+        flag_delimiter:       args.flag_delimiter,
+        arg_input:            Some(input.to_string()),
+        flag_memcheck:        false,
+    };
+    // StatsMode::Frequency only reads the stats cache, without running stats
+    let stats_mode = if args.flag_infer_types || args.flag_data_dictionary {
+        util::StatsMode::Schema
+    } else {
+        util::StatsMode::Frequency
+    };
+    let stats = match util::get_stats_records(&schema_args, stats_mode) {
+        Ok((_, stats)) => stats,
+        Err(e) => {
+            warn!("Cannot get the stats of {input}, writing all cells as text: {e}");
+            return Ok(Vec::new());
+        },
+    };
+    if stats.len() == num_fields {
+        Ok(stats)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// writes a value as a native excel cell of the column's kind.
+/// Values that don't fit the kind are written as text.
+fn write_xlsx_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &str,
+    kind: CellKind,
+    format: Option<&Format>,
+) -> CliResult<()> {
+    let format = match format {
+        Some(format) if kind != CellKind::Text => format,
+        _ => {
+            worksheet.write_string(row, col, value)?;
+            return Ok(());
+        },
+    };
+
+    match kind {
+        CellKind::Integer => {
+            let digits = value.trim_start_matches(['-', '+']);
+            // keep leading zeros (e.g. zip codes) & integers excel can't represent exactly
+            if (digits.len() == 1 || !digits.starts_with('0'))
+                && digits.len() <= XLSX_MAX_INTEGER_DIGITS
+            {
+                if let Ok(number) = value.parse::<i64>() {
+                    #[allow(clippy::cast_precision_loss)]
+                    worksheet.write_number_with_format(row, col, number as f64, format)?;
+                    return Ok(());
+                }
+            }
+        },
+        CellKind::Float => {
+            if let Ok(number) = value.parse::<f64>() {
+                if number.is_finite() {
+                    worksheet.write_number_with_format(row, col, number, format)?;
+                    return Ok(());
+                }
+            }
+        },
+        CellKind::Date | CellKind::DateTime => {
+            if let Some(datetime) = excel_datetime(value, kind == CellKind::Date) {
+                worksheet.write_datetime_with_format(row, col, &datetime, format)?;
+                return Ok(());
+            }
+        },
+        CellKind::Boolean => {
+            if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+                worksheet.write_boolean(row, col, value.eq_ignore_ascii_case("true"))?;
+                return Ok(());
+            }
+        },
+        CellKind::Text => {},
+    }
+    worksheet.write_string(row, col, value)?;
+    Ok(())
+}
+
+/// parses a date or datetime the way stats inferred it. Returns None if the value
+/// can't be parsed or is outside the range of excel dates
+fn excel_datetime(value: &str, date_only: bool) -> Option<ExcelDateTime> {
+    let parsed = parse_with_preference(value, false).ok()?.naive_utc();
+    let date = ExcelDateTime::from_ymd(
+        u16::try_from(parsed.year()).ok()?,
+        parsed.month() as u8,
+        parsed.day() as u8,
+    )
+    .ok()?;
+    if date_only {
+        return Some(date);
+    }
+    let seconds = f64::from(parsed.second()) + f64::from(parsed.nanosecond()) / 1_000_000_000.0;
+    date.and_hms(parsed.hour() as u16, parsed.minute() as u8, seconds)
+        .ok()
+}
+
+/// the sheet name of an input - its file stem, truncated to the xlsx limit
+/// at the last separator where possible, and made unique
+fn xlsx_sheet_name(input: &Path, separator: &str, used: &[String]) -> String {
+    let stem = input.file_stem().map_or_else(
+        || "Sheet".to_string(),
+        |stem| stem.to_string_lossy().to_string(),
+    );
+    // these characters are not allowed in sheet names
+    let mut name: String = stem
+        .chars()
+        .map(|c| {
+            if matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    name = name.trim_matches('\'').to_string();
+    if name.is_empty() {
+        name = "Sheet".to_string();
+    }
+
+    if name.chars().count() > XLSX_MAX_SHEETNAME_LEN {
+        let truncated: String = name.chars().take(XLSX_MAX_SHEETNAME_LEN).collect();
+        name = match truncated.rfind(separator) {
+            Some(pos) if !separator.is_empty() && pos > 0 => truncated[..pos].to_string(),
+            _ => truncated,
+        };
+    }
+    xlsx_unique_sheet_name(name, used)
+}
+
+/// sheet names are case-insensitive, so add a numeric suffix to duplicates
+fn xlsx_unique_sheet_name(name: String, used: &[String]) -> String {
+    let is_used = |candidate: &str| used.iter().any(|u| u.eq_ignore_ascii_case(candidate));
+    if !is_used(&name) {
+        return name;
+    }
+    let mut suffix = 2;
+    loop {
+        let suffix_str = format!("_{suffix}");
+        let base: String = name
+            .chars()
+            .take(XLSX_MAX_SHEETNAME_LEN - suffix_str.len())
+            .collect();
+        let candidate = format!("{base}{suffix_str}");
+        if !is_used(&candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// writes the "Data Dictionary" sheet - a row per column of every sheet
+fn write_data_dictionary(
+    worksheet: &mut Worksheet,
+    sheet_name: &str,
+    dictionary: &[(String, Vec<String>, Vec<StatsData>)],
+) -> CliResult<()> {
+    const DICTIONARY_HEADERS: [&str; 11] = [
+        "Sheet",
+        "Column",
+        "Type",
+        "Nullcount",
+        "Cardinality",
+        "Min",
+        "Max",
+        "Min Length",
+        "Max Length",
+        "Mean",
+        "Stddev",
+    ];
+
+    worksheet.set_name(sheet_name)?;
+    let header_format = Format::new().set_bold();
+    for (col, header) in DICTIONARY_HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    let mut row: u32 = 0;
+    for (sheet, headers, stats) in dictionary {
+        for (i, header) in headers.iter().enumerate() {
+            row += 1;
+            worksheet.write_string(row, 0, sheet)?;
+            worksheet.write_string(row, 1, header)?;
+            let Some(stats) = stats.get(i) else {
+                continue;
+            };
+            worksheet.write_string(row, 2, &stats.r#type)?;
+            #[allow(clippy::cast_precision_loss)]
+            {
+                worksheet.write_number(row, 3, stats.nullcount as f64)?;
+                worksheet.write_number(row, 4, stats.cardinality as f64)?;
+            }
+            if let Some(ref min) = stats.min {
+                worksheet.write_string(row, 5, min)?;
+            }
+            if let Some(ref max) = stats.max {
+                worksheet.write_string(row, 6, max)?;
+            }
+            #[allow(clippy::cast_precision_loss)]
+            for (col, value) in [
+                (7, stats.min_length.map(|len| len as f64)),
+                (8, stats.max_length.map(|len| len as f64)),
+                (9, stats.mean),
+                (10, stats.stddev),
+            ] {
+                if let Some(value) = value {
+                    worksheet.write_number(row, col, value)?;
+                }
+            }
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofilter(0, 0, row, (DICTIONARY_HEADERS.len() - 1) as u16)?;
+    worksheet.autofit();
+    Ok(())
+}
@@ -32,6 +32,26 @@ qsv supports a custom format - `currency`. This format will only accept a valid
       Negative amounts: ($100.00) or -$100.00
       Different styles: 1.000,00 (used in some countries for euros)
 
+Other custom formats can be defined in a TOML or JSON file with the --formats option,
+so the same in-house identifiers can be validated in every schema with "format" instead
+of repeating "pattern". Each format is backed by a regex, a checksum algorithm or both:
+
+  # formats.toml
+  [policy_number]
+  pattern = "^POL-[0-9]{8}$"
+
+  [card_number]
+  checksum = "luhn"
+
+  [lei]
+  pattern = "^[0-9A-Z]{18}[0-9]{2}$"
+  checksum = "iso7064-mod97"
+
+The checksum algorithms are "luhn", "iso7064-mod97" (ISO 7064 MOD 97-10, with letters
+as 10-35, e.g. LEIs) and "isbn" (ISBN-10 or ISBN-13). Spaces and hyphens are ignored
+by the checksums. Patterns are not anchored, like the "pattern" keyword.
+The same formats in JSON are {"policy_number": {"pattern": "^POL-[0-9]{8}$"}, ...}.
+
 qsv also supports a custom keyword - `dynamicEnum`. It allows for dynamic validation against a CSV.
 This is useful for validating against a set of values unknown at the time of schema creation or
 when the set of valid values is dynamic or too large to hardcode into the schema.
@@ -73,6 +93,26 @@ when the set of valid values is dynamic or too large to hardcode into the schema
 
 Only the first column of the CSV file is read and used for validation.
 
+As JSON Schema validates one row at a time, qsv also supports two custom top-level keywords
+for constraints that span rows - `uniqueKey` and `foreignKey`:
+
+  // the values of the id column must be unique
+  "uniqueKey": "id"
+
+  // the combination of the first_name & last_name columns must be unique,
+  // and so must the email column
+  "uniqueKey": [["first_name", "last_name"], "email"]
+
+  // the values of the state column must be in the code column of us_states.csv.
+  // "reference" is a URI with the same form as dynamicEnum. "referenceColumns" defaults
+  // to "columns". If "reference" is empty, the CSV being validated is the reference.
+  "foreignKey": {"columns": "state", "reference": "dathere://us_states.csv",
+                 "referenceColumns": "code"}
+
+"foreignKey" can also be an array of foreign key definitions. Rows where all the key
+columns are empty are not checked. Duplicate keys are reported with the row numbers of
+both offending records.
+
 You can create a JSON Schema file from a reference CSV file using the `qsv schema` command.
 Once the schema is created, you can fine-tune it to your needs and use it to validate other CSV
 files that have the same structure.
@@ -104,6 +144,68 @@ If piped from stdin, the filenames will use `stdin.csv` as the base filename. Fo
    * stdin.csv.invalid
    * stdin.csv.validation-errors.tsv
 
+FRICTIONLESS TABLE SCHEMA VALIDATION MODE:
+=========================================
+
+If the schema file is a Frictionless Data Table Schema (https://specs.frictionlessdata.io/table-schema/)
+instead of a JSON Schema - i.e. it has a "fields" array instead of a "properties" object - it is
+translated to an equivalent JSON Schema and validated as above, with the same output files.
+
+The field types string, number, integer, boolean, date, time, datetime, year, yearmonth,
+duration and geopoint are supported, along with their formats, the trueValues/falseValues,
+decimalChar, groupChar and bareNumber properties, and the required, unique, minLength,
+maxLength, minimum, maximum, pattern and enum constraints. The object, array, geojson and
+any types are validated as strings. Date formats other than "default" and "any" are
+strftime patterns (e.g. "%d/%m/%Y"), checked with the custom `datePattern` keyword.
+
+Values listed in "missingValues" are treated as nulls. It defaults to [""], so empty values
+are only nulls if "missingValues" is not declared, or lists "".
+
+As "primaryKey", "unique" and "foreignKeys" constraints span rows, they are checked
+separately as each row is read. Duplicate keys are reported with the row number of the
+first occurrence of the key. The "reference.resource" of a foreign key is the path of the
+CSV file with the referenced values. If it's empty, the key references the input CSV itself.
+
+You can create a Table Schema file from a reference CSV file using `qsv schema --format frictionless`.
+
+REPAIR MODE:
+===========
+
+With the --fix option, invalid records are coerced towards the schema before they're
+reported as invalid. Each field of an invalid record is trimmed, and depending on the
+field's definition in the schema:
+
+  * integer & number: currency symbols & ISO 4217 codes, thousands separators and
+    accounting-style parentheses are removed (e.g. "$1,234.00" -> 1234, "(5.5)" -> -5.5,
+    "1 234 EUR" -> 1234). Values with any other characters (e.g. "5kg") are not fixed.
+  * boolean: yes/no, y/n, t/f, on/off & true/false are matched case-insensitively
+  * "date" & "date-time" formats: dates are normalized to RFC 3339
+    (e.g. "03/04/2024" -> 2024-03-04). Set QSV_PREFER_DMY to parse dates as dmy.
+    Numbers are not taken as unix timestamps, so "12345" is not fixed.
+  * enum: string values are matched case-insensitively to the enum value
+
+If the coerced record is valid, it is counted as valid. The input with the repaired
+records is written to a ".fixed" file, and each change is logged in a "fix-log.tsv"
+file with the columns row_number, field, original & fixed. Records that are still
+invalid are left unchanged in the invalid output.
+
+REPORTS:
+=======
+
+For CI systems & code review tools, the --report option writes a report with one finding
+per failing cell, with its row number, field, failing schema keyword, error message and
+offending value. The report is written even if all records are valid:
+
+  * junit: a JUnit XML report, with a failing test case per finding
+           (<input>.validation-report.xml)
+  * sarif: a SARIF 2.1.0 log, with a result per finding. The line of each result assumes
+           each record is on one line. (<input>.validation-report.sarif)
+  * json:  a JSON report (<input>.validation-report.json)
+
+The --annotate option writes the input with an appended "_errors" column to a ".annotated"
+file. For invalid records, it lists the "field: error" messages separated by "; ".
+It is empty for valid records.
+
 RFC 4180 VALIDATION MODE:
 ========================
 
@@ -124,13 +226,24 @@ Usage:
 
 Validate arguments:
     <input>                    Input CSV file to validate. If not provided, will read from stdin.
-    <json-schema>              JSON Schema file to validate against. If not provided, `validate`
-                               will run in RFC 4180 validation mode. The file can be a local file
-                               or a URL (http and https schemes supported).
+    <json-schema>              JSON Schema or Frictionless Table Schema file to validate against.
+                               If not provided, `validate` will run in RFC 4180 validation mode.
+                               The file can be a local file or a URL (http and https schemes
+                               supported).
 
 Validate options:
     --trim                     Trim leading and trailing whitespace from fields before validating.
     --fail-fast                Stops on first error.
+    --fix                      Try to repair invalid records by coercing their values to the
+                               types, formats & enums of the schema. See REPAIR MODE above.
+    --formats <file>           TOML or JSON file with custom formats to register.
+                               TOML is used if the file has a .toml extension. TOML
+                               support is not available in qsvlite.
+                               See the custom formats section above.
+    --report <format>          Write a validation report for CI systems. One of junit, sarif
+                               or json. See REPORTS above.
+    --annotate                 Write the input with an appended "_errors" column.
+                               See REPORTS above.
     --valid <suffix>           Valid record output file suffix. [default: valid]
     --invalid <suffix>         Invalid record output file suffix. [default: invalid]
     --json                     When validating without a schema, return the RFC 4180 check
@@ -193,7 +306,7 @@ use std::{
     },
 };
 
-use ahash::{HashSet, HashSetExt};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use csv::ByteRecord;
 use indicatif::HumanCount;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
@@ -201,7 +314,7 @@ use indicatif::{ProgressBar, ProgressDrawTarget};
 use jsonschema::{
     output::BasicOutput,
     paths::{LazyLocation, Location},
-    Keyword, ValidationError, Validator,
+    Keyword, ValidationError, ValidationOptions, Validator,
 };
 use log::{debug, info, log_enabled};
 use qsv_currency::Currency;
@@ -238,6 +351,12 @@ static CKAN_API: OnceLock<String> = OnceLock::new();
 static CKAN_TOKEN: OnceLock<Option<String>> = OnceLock::new();
 static DELIMITER: OnceLock<Option<Delimiter>> = OnceLock::new();
 
+// the missing values of a JSON Schema - empty values are nulls
+const EMPTY_MISSING_VALUES: &[Vec<u8>] = &[Vec::new()];
+
+// separates the fields of a composite key
+const KEY_SEPARATOR: u8 = b'\x1f';
+
 /// write to stderr and log::error, using ValidationError
 macro_rules! fail_validation_error {
     ($($t:tt)*) => {{
@@ -258,6 +377,10 @@ macro_rules! fail_validation_error {
 struct Args {
     flag_trim:         bool,
     flag_fail_fast:    bool,
+    flag_fix:          bool,
+    flag_formats:      Option<String>,
+    flag_report:       Option<String>,
+    flag_annotate:     bool,
     flag_valid:        Option<String>,
     flag_invalid:      Option<String>,
     flag_json:         bool,
@@ -313,6 +436,178 @@ fn currency_format_checker(s: &str) -> bool {
     })
 }
 
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+enum ChecksumAlgorithm {
+    #[serde(rename = "luhn")]
+    Luhn,
+    #[serde(rename = "iso7064-mod97")]
+    Iso7064Mod97,
+    #[serde(rename = "isbn")]
+    Isbn,
+}
+
+/// a user-defined format, registered with --formats
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomFormatDef {
+    pattern:  Option<String>,
+    checksum: Option<ChecksumAlgorithm>,
+}
+
+struct CustomFormat {
+    pattern:  Option<regex::Regex>,
+    checksum: Option<ChecksumAlgorithm>,
+}
+
+impl CustomFormat {
+    fn is_valid(&self, s: &str) -> bool {
+        self.pattern.as_ref().is_none_or(|re| re.is_match(s))
+            && self.checksum.is_none_or(|algorithm| match algorithm {
+                ChecksumAlgorithm::Luhn => luhn_checksum(s),
+                ChecksumAlgorithm::Iso7064Mod97 => iso7064_mod97_checksum(s),
+                ChecksumAlgorithm::Isbn => isbn_checksum(s),
+            })
+    }
+}
+
+/// the characters checked by checksums, ignoring spaces & hyphens
+#[inline]
+fn checksum_chars(s: &str) -> impl Iterator<Item = char> + '_ {
+    s.chars().filter(|c| !matches!(c, ' ' | '-'))
+}
+
+fn luhn_checksum(s: &str) -> bool {
+    let mut sum = 0_u32;
+    let mut count = 0_usize;
+    for (i, c) in checksum_chars(s)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .enumerate()
+    {
+        let Some(digit) = c.to_digit(10) else {
+            return false;
+        };
+        sum += if i % 2 == 1 {
+            let doubled = digit * 2;
+            if doubled > 9 {
+                doubled - 9
+            } else {
+                doubled
+            }
+        } else {
+            digit
+        };
+        count += 1;
+    }
+    count > 1 && sum % 10 == 0
+}
+
+fn iso7064_mod97_checksum(s: &str) -> bool {
+    let mut remainder = 0_u32;
+    let mut count = 0_usize;
+    for c in checksum_chars(s) {
+        // digits are 0-9, letters are 10-35
+        let Some(value) = c.to_ascii_uppercase().to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+        count += 1;
+    }
+    count > 2 && remainder == 1
+}
+
+fn isbn_checksum(s: &str) -> bool {
+    let chars: Vec<char> = checksum_chars(s).collect();
+    match chars.len() {
+        10 => {
+            let mut sum = 0_u32;
+            for (i, c) in chars.iter().enumerate() {
+                let value = match c {
+                    'X' | 'x' if i == 9 => 10,
+                    _ => match c.to_digit(10) {
+                        Some(digit) => digit,
+                        None => return false,
+                    },
+                };
+                sum += value * (10 - i as u32);
+            }
+            sum % 11 == 0
+        },
+        13 => {
+            let mut sum = 0_u32;
+            for (i, c) in chars.iter().enumerate() {
+                let Some(digit) = c.to_digit(10) else {
+                    return false;
+                };
+                sum += if i % 2 == 0 { digit } else { digit * 3 };
+            }
+            sum % 10 == 0
+        },
+        _ => false,
+    }
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml_formats(
+    contents: &str,
+) -> Result<std::collections::BTreeMap<String, CustomFormatDef>, String> {
+    toml::from_str(contents).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml_formats(
+    _contents: &str,
+) -> Result<std::collections::BTreeMap<String, CustomFormatDef>, String> {
+    Err(
+        "TOML formats files require qsv to be built with the \"toml\" feature. Use a JSON formats \
+         file instead."
+            .to_string(),
+    )
+}
+
+/// loads the custom formats of the --formats file, compiling their patterns
+fn load_custom_formats(path: &str) -> CliResult<Vec<(String, CustomFormat)>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Other(format!("Cannot read formats file {path}: {e}")))?;
+    let is_toml = std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let format_defs: std::collections::BTreeMap<String, CustomFormatDef> = if is_toml {
+        parse_toml_formats(&contents)
+            .map_err(|e| CliError::Other(format!("Cannot parse formats file {path}: {e}")))?
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|e| CliError::Other(format!("Cannot parse formats file {path}: {e}")))?
+    };
+
+    let mut custom_formats = Vec::with_capacity(format_defs.len());
+    for (name, format_def) in format_defs {
+        if format_def.pattern.is_none() && format_def.checksum.is_none() {
+            return fail_clierror!("Custom format '{name}' needs a pattern and/or a checksum.");
+        }
+        let pattern = match format_def.pattern {
+            Some(pattern) => match regex::Regex::new(&pattern) {
+                Ok(re) => Some(re),
+                Err(e) => return fail_clierror!("Invalid pattern for custom format '{name}': {e}"),
+            },
+            None => None,
+        };
+        custom_formats.push((
+            name,
+            CustomFormat {
+                pattern,
+                checksum: format_def.checksum,
+            },
+        ));
+    }
+    Ok(custom_formats)
+}
+
 struct DynEnumValidator {
     dynenum_set: HashSet<String>,
 }
@@ -567,6 +862,940 @@ fn dyn_enum_validator_factory<'a>(
     }
 }
 
+/// Validates strings against a strftime pattern. Used for Frictionless Table Schema
+/// date, time and datetime fields with custom formats.
+struct DatePatternValidator {
+    pattern: String,
+}
+
+impl Keyword for DatePatternValidator {
+    #[inline]
+    fn validate<'instance>(
+        &self,
+        instance: &'instance Value,
+        instance_path: &LazyLocation,
+    ) -> Result<(), ValidationError<'instance>> {
+        if self.is_valid(instance) {
+            Ok(())
+        } else {
+            Err(ValidationError::custom(
+                Location::default(),
+                instance_path.into(),
+                instance,
+                format!(
+                    "{instance} does not match date pattern \"{}\"",
+                    self.pattern
+                ),
+            ))
+        }
+    }
+
+    #[inline]
+    fn is_valid(&self, instance: &Value) -> bool {
+        // like the "format" keyword, only strings are checked
+        if let Value::String(s) = instance {
+            matches_date_pattern(s, &self.pattern)
+        } else {
+            true
+        }
+    }
+}
+
+#[inline]
+fn matches_date_pattern(s: &str, pattern: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(s, pattern).is_ok()
+        || chrono::NaiveDateTime::parse_from_str(s, pattern).is_ok()
+        || chrono::NaiveTime::parse_from_str(s, pattern).is_ok()
+        || chrono::DateTime::parse_from_str(s, pattern).is_ok()
+}
+
+fn date_pattern_validator_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    if let Value::String(pattern) = value {
+        Ok(Box::new(DatePatternValidator {
+            pattern: pattern.clone(),
+        }))
+    } else {
+        Err(ValidationError::custom(
+            Location::default(),
+            location,
+            value,
+            "'datePattern' must be set to a strftime pattern.",
+        ))
+    }
+}
+
+/// The `uniqueKey` & `foreignKey` keywords span rows, so they're checked by the KeyChecker
+/// as rows are read. At the row level, they always pass.
+struct KeyConstraintValidator;
+
+impl Keyword for KeyConstraintValidator {
+    #[inline]
+    fn validate<'instance>(
+        &self,
+        _instance: &'instance Value,
+        _instance_path: &LazyLocation,
+    ) -> Result<(), ValidationError<'instance>> {
+        Ok(())
+    }
+
+    #[inline]
+    fn is_valid(&self, _instance: &Value) -> bool {
+        true
+    }
+}
+
+fn unique_key_validator_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    match parse_unique_keys(value) {
+        Ok(_) => Ok(Box::new(KeyConstraintValidator)),
+        Err(e) => Err(ValidationError::custom(
+            Location::default(),
+            location,
+            value,
+            e,
+        )),
+    }
+}
+
+fn foreign_key_validator_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    match parse_foreign_keys(value) {
+        Ok(_) => Ok(Box::new(KeyConstraintValidator)),
+        Err(e) => Err(ValidationError::custom(
+            Location::default(),
+            location,
+            value,
+            e,
+        )),
+    }
+}
+
+/// parses the `uniqueKey` keyword - a column, an array of columns (a composite key),
+/// or an array of keys
+fn parse_unique_keys(value: &Value) -> Result<Vec<UniqueKeyDef>, String> {
+    let invalid = || {
+        format!(
+            "'uniqueKey' must be a column name, an array of column names, or an array of keys: \
+             {value}"
+        )
+    };
+    let unique_key = |columns: Vec<String>| UniqueKeyDef {
+        kind: "uniqueKey".to_string(),
+        columns,
+    };
+
+    match value {
+        Value::String(col) => Ok(vec![unique_key(vec![col.clone()])]),
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_string) => {
+            Ok(vec![unique_key(string_or_array(Some(value)))])
+        },
+        Value::Array(items) if !items.is_empty() => items
+            .iter()
+            .map(|item| {
+                let columns = string_or_array(Some(item));
+                if columns.is_empty() {
+                    Err(invalid())
+                } else {
+                    Ok(unique_key(columns))
+                }
+            })
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+/// parses the `foreignKey` keyword - a foreign key definition or an array of them
+fn parse_foreign_keys(value: &Value) -> Result<Vec<ForeignKeyDef>, String> {
+    let defs = match value {
+        Value::Array(defs) => defs.iter().collect(),
+        _ => vec![value],
+    };
+
+    defs.into_iter()
+        .map(|def| {
+            let columns = string_or_array(def.get("columns"));
+            let reference = def.get("reference").and_then(Value::as_str);
+            let reference_columns = if def.get("referenceColumns").is_some() {
+                string_or_array(def.get("referenceColumns"))
+            } else {
+                columns.clone()
+            };
+            match reference {
+                Some(reference)
+                    if !columns.is_empty() && columns.len() == reference_columns.len() =>
+                {
+                    Ok(ForeignKeyDef {
+                        columns,
+                        reference: reference.to_string(),
+                        reference_columns,
+                    })
+                },
+                _ => Err(format!(
+                    "'foreignKey' must have a \"reference\" and the same number of \"columns\" \
+                     and \"referenceColumns\": {def}"
+                )),
+            }
+        })
+        .collect()
+}
+
+/// gets the `uniqueKey` & `foreignKey` constraints of a JSON Schema
+fn json_schema_key_constraints(schema: &Value) -> CliResult<KeyConstraints> {
+    let mut key_constraints = KeyConstraints::default();
+    if let Some(value) = schema.get("uniqueKey") {
+        key_constraints.unique_keys = parse_unique_keys(value)?;
+    }
+    if let Some(value) = schema.get("foreignKey") {
+        key_constraints.foreign_keys = parse_foreign_keys(value)?;
+    }
+    Ok(key_constraints)
+}
+
+/// Unique and foreign key constraints span rows, and cannot be expressed in
+/// JSON Schema, which validates one row at a time.
+#[derive(Default, Debug, PartialEq)]
+pub struct KeyConstraints {
+    unique_keys:  Vec<UniqueKeyDef>,
+    foreign_keys: Vec<ForeignKeyDef>,
+}
+
+impl KeyConstraints {
+    fn is_empty(&self) -> bool {
+        self.unique_keys.is_empty() && self.foreign_keys.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct UniqueKeyDef {
+    /// where the key was declared, e.g. "primaryKey"
+    kind:    String,
+    columns: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct ForeignKeyDef {
+    columns:           Vec<String>,
+    /// the CSV with the referenced values. Empty if the input references itself.
+    reference:         String,
+    reference_columns: Vec<String>,
+}
+
+/// the options of the JSON Schema validator, with qsv's custom formats & keywords.
+/// dynamicEnum is only added by `validate`, as it needs the lookup settings.
+fn validator_options() -> ValidationOptions {
+    Validator::options()
+        .with_format("currency", currency_format_checker)
+        .with_keyword("datePattern", date_pattern_validator_factory)
+        .with_keyword("uniqueKey", unique_key_validator_factory)
+        .with_keyword("foreignKey", foreign_key_validator_factory)
+        .should_validate_formats(true)
+}
+
+/// returns true if the schema is a Frictionless Table Schema rather than a JSON Schema
+pub fn is_table_schema(schema: &Value) -> bool {
+    schema.get("fields").is_some_and(Value::is_array) && schema.get("properties").is_none()
+}
+
+/// Table Schema properties like "primaryKey" can be a string or an array of strings
+fn string_or_array(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(vec)) => vec
+            .iter()
+            .filter_map(|v| v.as_str().map(ToString::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// builds a regex for numbers with custom decimal & group chars, optionally
+/// surrounded by non-numeric characters (bareNumber = false), e.g. "$1,000.50" or "95%"
+fn table_schema_number_pattern(field: &Value, allow_fraction: bool) -> String {
+    let decimal_char = field
+        .get("decimalChar")
+        .and_then(Value::as_str)
+        .unwrap_or(".");
+    let group_char = field.get("groupChar").and_then(Value::as_str).unwrap_or("");
+    let bare_number = field
+        .get("bareNumber")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    let digits = if group_char.is_empty() {
+        r"\d+".to_string()
+    } else {
+        format!(r"\d{{1,3}}(?:{}\d{{3}})*|\d+", regex::escape(group_char))
+    };
+    let mut pattern = format!("[+-]?(?:{digits})");
+    if allow_fraction {
+        pattern.push_str(&format!(
+            r"(?:{}\d+)?(?:[eE][+-]?\d+)?",
+            regex::escape(decimal_char)
+        ));
+    }
+    if bare_number {
+        format!("^{pattern}$")
+    } else {
+        format!("^[^0-9]*{pattern}[^0-9]*$")
+    }
+}
+
+/// Translates a Frictionless Table Schema into an equivalent JSON Schema.
+/// Also returns the key constraints that span rows, and the non-empty missing values.
+/// See https://specs.frictionlessdata.io/table-schema/
+pub fn table_schema_to_json_schema(
+    table_schema: &Value,
+) -> CliResult<(Value, KeyConstraints, Vec<String>)> {
+    let Some(fields) = table_schema.get("fields").and_then(Value::as_array) else {
+        return fail_clierror!("Table Schema missing 'fields' array");
+    };
+
+    let mut key_constraints = KeyConstraints::default();
+    let primary_key = string_or_array(table_schema.get("primaryKey"));
+    let mut properties = Map::with_capacity(fields.len());
+    let mut required_fields: Vec<Value> = Vec::with_capacity(fields.len());
+
+    for field in fields {
+        let Some(name) = field.get("name").and_then(Value::as_str) else {
+            return fail_clierror!("Table Schema field missing 'name': {field}");
+        };
+        let field_type = field
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("string");
+        let field_format = field
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or("default");
+        // older Table Schemas prefix strftime patterns with "fmt:"
+        let field_format = field_format.strip_prefix("fmt:").unwrap_or(field_format);
+        let constraints = field.get("constraints").and_then(Value::as_object);
+        let constraint = |key: &str| constraints.and_then(|c| c.get(key));
+
+        let mut prop = Map::new();
+        if let Some(desc) = field.get("description").or_else(|| field.get("title")) {
+            prop.insert("description".to_string(), desc.clone());
+        }
+
+        let json_type = match field_type {
+            "integer" | "year" => {
+                if field.get("bareNumber") == Some(&Value::Bool(false)) {
+                    prop.insert(
+                        "pattern".to_string(),
+                        Value::String(table_schema_number_pattern(field, false)),
+                    );
+                    "string"
+                } else {
+                    "integer"
+                }
+            },
+            "number" => {
+                if field.get("bareNumber") == Some(&Value::Bool(false))
+                    || field.get("decimalChar").is_some()
+                    || field.get("groupChar").is_some()
+                {
+                    prop.insert(
+                        "pattern".to_string(),
+                        Value::String(table_schema_number_pattern(field, true)),
+                    );
+                    "string"
+                } else {
+                    "number"
+                }
+            },
+            "boolean" => {
+                let mut true_values = string_or_array(field.get("trueValues"));
+                if true_values.is_empty() {
+                    true_values = vec!["true".into(), "True".into(), "TRUE".into(), "1".into()];
+                }
+                let mut false_values = string_or_array(field.get("falseValues"));
+                if false_values.is_empty() {
+                    false_values = vec!["false".into(), "False".into(), "FALSE".into(), "0".into()];
+                }
+                prop.insert(
+                    "enum".to_string(),
+                    Value::Array(
+                        true_values
+                            .into_iter()
+                            .chain(false_values)
+                            .map(Value::String)
+                            .collect(),
+                    ),
+                );
+                "string"
+            },
+            "date" | "time" | "datetime" => {
+                // csvs_convert writes the type name as the format, which is the default
+                let field_format = if field_format == "any" || field_format.contains('%') {
+                    field_format
+                } else {
+                    "default"
+                };
+                match (field_type, field_format) {
+                    (_, "any") => {},
+                    ("date", "default") => {
+                        prop.insert("format".to_string(), Value::String("date".to_string()));
+                    },
+                    ("datetime", "default") => {
+                        prop.insert("format".to_string(), Value::String("date-time".to_string()));
+                    },
+                    ("time", "default") => {
+                        prop.insert(
+                            "datePattern".to_string(),
+                            Value::String("%H:%M:%S".to_string()),
+                        );
+                    },
+                    (_, pattern) => {
+                        prop.insert(
+                            "datePattern".to_string(),
+                            Value::String(pattern.to_string()),
+                        );
+                    },
+                }
+                "string"
+            },
+            "yearmonth" => {
+                prop.insert(
+                    "pattern".to_string(),
+                    Value::String(r"^-?\d{4}-(0[1-9]|1[0-2])$".to_string()),
+                );
+                "string"
+            },
+            "duration" => {
+                prop.insert("format".to_string(), Value::String("duration".to_string()));
+                "string"
+            },
+            "geopoint" => {
+                let lon_lat = r"\s*-?\d+(\.\d+)?\s*,\s*-?\d+(\.\d+)?\s*";
+                match field_format {
+                    "default" => {
+                        prop.insert("pattern".to_string(), Value::String(format!("^{lon_lat}$")));
+                    },
+                    "array" => {
+                        prop.insert(
+                            "pattern".to_string(),
+                            Value::String(format!(r"^\s*\[{lon_lat}\]\s*$")),
+                        );
+                    },
+                    _ => {},
+                }
+                "string"
+            },
+            "string" => {
+                match field_format {
+                    "email" | "uri" | "uuid" => {
+                        prop.insert(
+                            "format".to_string(),
+                            Value::String(field_format.to_string()),
+                        );
+                    },
+                    "binary" => {
+                        prop.insert(
+                            "pattern".to_string(),
+                            Value::String("^[A-Za-z0-9+/]*={0,2}$".to_string()),
+                        );
+                    },
+                    _ => {},
+                }
+                "string"
+            },
+            // object, array, geojson & any
+            _ => "string",
+        };
+
+        let required = constraint("required").and_then(Value::as_bool) == Some(true)
+            || primary_key.iter().any(|pk| pk == name);
+
+        if let Some(Value::Array(enum_values)) = constraint("enum") {
+            let mut enum_list: Vec<Value> = enum_values
+                .iter()
+                .map(|v| match (json_type, v) {
+                    ("integer", Value::String(s)) => s
+                        .parse::<i64>()
+                        .map_or_else(|_| v.clone(), |i| Value::Number(Number::from(i))),
+                    ("number", Value::String(s)) => s
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(Number::from_f64)
+                        .map_or_else(|| v.clone(), Value::Number),
+                    ("string", Value::Number(_) | Value::Bool(_)) => Value::String(v.to_string()),
+                    _ => v.clone(),
+                })
+                .collect();
+            if !required {
+                enum_list.push(Value::Null);
+            }
+            prop.insert("enum".to_string(), Value::Array(enum_list));
+        } else if !required {
+            if let Some(Value::Array(enum_list)) = prop.get_mut("enum") {
+                enum_list.push(Value::Null);
+            }
+        }
+
+        if json_type == "string" {
+            for key in ["minLength", "maxLength"] {
+                if let Some(v) = constraint(key) {
+                    prop.insert(key.to_string(), v.clone());
+                }
+            }
+        }
+
+        for key in ["minimum", "maximum"] {
+            let Some(v) = constraint(key) else {
+                continue;
+            };
+            if json_type == "integer" || json_type == "number" {
+                let bound = match v {
+                    Value::String(s) => match s.parse::<f64>().ok().and_then(Number::from_f64) {
+                        Some(n) => Value::Number(n),
+                        None => {
+                            return fail_clierror!(
+                                "Table Schema field '{name}' has an invalid {key}: {v}"
+                            )
+                        },
+                    },
+                    _ => v.clone(),
+                };
+                prop.insert(key.to_string(), bound);
+            } else {
+                wwarn!("Ignoring {key} constraint of {field_type} field '{name}'.");
+            }
+        }
+
+        if let Some(Value::String(pattern)) = constraint("pattern") {
+            // Table Schema patterns have to match the whole value
+            let anchored = Value::String(format!("^(?:{pattern})$"));
+            if prop.contains_key("pattern") {
+                prop.insert("allOf".to_string(), json!([{ "pattern": anchored }]));
+            } else {
+                prop.insert("pattern".to_string(), anchored);
+            }
+        }
+
+        if constraint("unique").and_then(Value::as_bool) == Some(true) {
+            key_constraints.unique_keys.push(UniqueKeyDef {
+                kind:    "unique".to_string(),
+                columns: vec![name.to_string()],
+            });
+        }
+
+        let type_list = if required {
+            required_fields.push(Value::String(name.to_string()));
+            json!([json_type])
+        } else {
+            json!([json_type, "null"])
+        };
+        prop.insert("type".to_string(), type_list);
+
+        properties.insert(name.to_string(), Value::Object(prop));
+    }
+
+    if !primary_key.is_empty() {
+        key_constraints.unique_keys.push(UniqueKeyDef {
+            kind:    "primaryKey".to_string(),
+            columns: primary_key,
+        });
+    }
+
+    if let Some(Value::Array(foreign_keys)) = table_schema.get("foreignKeys") {
+        for fk in foreign_keys {
+            let columns = string_or_array(fk.get("fields"));
+            let reference = fk.get("reference");
+            let reference_columns = string_or_array(reference.and_then(|r| r.get("fields")));
+            if columns.is_empty() || columns.len() != reference_columns.len() {
+                return fail_clierror!(
+                    "Table Schema foreign key must have the same number of fields and reference \
+                     fields: {fk}"
+                );
+            }
+            key_constraints.foreign_keys.push(ForeignKeyDef {
+                columns,
+                reference: reference
+                    .and_then(|r| r.get("resource"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                reference_columns,
+            });
+        }
+    }
+
+    // missingValues defaults to [""], so empty values are only nulls if they're listed
+    let missing_values: Vec<String> = if table_schema.get("missingValues").is_some() {
+        string_or_array(table_schema.get("missingValues"))
+    } else {
+        vec![String::new()]
+    };
+
+    let json_schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": table_schema.get("title").cloned().unwrap_or_else(|| json!("Table Schema")),
+        "type": "object",
+        "properties": Value::Object(properties),
+        "required": Value::Array(required_fields),
+    });
+
+    Ok((json_schema, key_constraints, missing_values))
+}
+
+#[inline]
+fn is_missing_value(value: &[u8], missing_values: &[Vec<u8>]) -> bool {
+    missing_values.iter().any(|mv| mv == value)
+}
+
+/// builds the composite key of the `indices` fields of `record` in `key`.
+/// Returns false if all the fields are missing, as null keys are not checked.
+#[inline]
+fn build_composite_key(
+    record: &ByteRecord,
+    indices: &[usize],
+    missing_values: &[Vec<u8>],
+    key: &mut Vec<u8>,
+) -> bool {
+    key.clear();
+    let mut has_value = false;
+    for (i, idx) in indices.iter().enumerate() {
+        let field = record.get(*idx).unwrap_or_default();
+        has_value = has_value || !is_missing_value(field, missing_values);
+        if i > 0 {
+            key.push(KEY_SEPARATOR);
+        }
+        key.extend_from_slice(field);
+    }
+    has_value
+}
+
+fn display_composite_key(key: &[u8]) -> String {
+    String::from_utf8_lossy(key).replace(KEY_SEPARATOR as char, ", ")
+}
+
+fn column_indices(headers: &ByteRecord, columns: &[String], source: &str) -> CliResult<Vec<usize>> {
+    columns
+        .iter()
+        .map(|col| {
+            headers
+                .iter()
+                .position(|h| h == col.as_bytes())
+                .ok_or_else(|| CliError::Other(format!("Key column '{col}' not found in {source}")))
+        })
+        .collect()
+}
+
+struct UniqueKeyState {
+    kind:    String,
+    label:   String,
+    indices: Vec<usize>,
+    seen:    HashMap<Vec<u8>, u64>,
+}
+
+struct ForeignKeyState {
+    label:     String,
+    reference: String,
+    indices:   Vec<usize>,
+    values:    HashSet<Vec<u8>>,
+}
+
+/// gets the local path of a foreign key reference CSV, downloading and caching it
+/// if required. The reference has the same form as the dynamicEnum URI.
+#[cfg(not(feature = "lite"))]
+fn load_foreign_key_reference(uri: &str) -> CliResult<String> {
+    let (lookup_name, cache_age_secs) = parse_dynenum_uri(uri);
+    let opts = LookupTableOptions {
+        name: lookup_name,
+        uri: uri.to_string(),
+        cache_age_secs,
+        cache_dir: QSV_CACHE_DIR.get().unwrap().to_string(),
+        delimiter: DELIMITER.get().copied().flatten(),
+        ckan_api_url: CKAN_API.get().cloned(),
+        #[allow(clippy::redundant_closure_for_method_calls)]
+        ckan_token: CKAN_TOKEN.get().and_then(|t| t.clone()),
+        timeout_secs: TIMEOUT_SECS.load(Ordering::Relaxed),
+    };
+
+    match load_lookup_table(&opts) {
+        Ok(result) => Ok(result.filepath),
+        Err(e) => fail_clierror!("Error loading foreignKey reference {uri}: {e}"),
+    }
+}
+
+/// qsvlite only supports foreign key references on the local filesystem
+#[cfg(feature = "lite")]
+fn load_foreign_key_reference(uri: &str) -> CliResult<String> {
+    if std::path::Path::new(uri).exists() {
+        Ok(uri.to_string())
+    } else {
+        fail_clierror!("foreignKey reference {uri} not found.")
+    }
+}
+
+/// Checks the KeyConstraints of each record, in input order
+struct KeyChecker {
+    unique_keys:    Vec<UniqueKeyState>,
+    foreign_keys:   Vec<ForeignKeyState>,
+    missing_values: Vec<Vec<u8>>,
+    key:            Vec<u8>,
+}
+
+impl KeyChecker {
+    /// `rconfig` is the input, for self-referencing foreign keys. None if it can't be reread.
+    /// Keys whose fields are all `missing_values` are not checked.
+    fn new(
+        constraints: &KeyConstraints,
+        headers: &ByteRecord,
+        rconfig: Option<&Config>,
+        trim: bool,
+        missing_values: &[Vec<u8>],
+    ) -> CliResult<Self> {
+        let mut unique_keys = Vec::with_capacity(constraints.unique_keys.len());
+        for uk in &constraints.unique_keys {
+            unique_keys.push(UniqueKeyState {
+                kind:    uk.kind.clone(),
+                label:   uk.columns.join(","),
+                indices: column_indices(headers, &uk.columns, "input")?,
+                seen:    HashMap::new(),
+            });
+        }
+
+        let mut foreign_keys = Vec::with_capacity(constraints.foreign_keys.len());
+        let mut key = Vec::new();
+        for fk in &constraints.foreign_keys {
+            let ref_config = if fk.reference.is_empty() {
+                match rconfig {
+                    Some(rconfig) if !rconfig.is_stdin() => rconfig.clone(),
+                    _ => {
+                        return fail_incorrectusage_clierror!(
+                            "Self-referencing foreign keys are not supported when reading from \
+                             stdin."
+                        )
+                    },
+                }
+            } else {
+                let ref_path = load_foreign_key_reference(&fk.reference)?;
+                Config::new(Some(ref_path).as_ref())
+            };
+            let mut ref_rdr = ref_config.reader()?;
+            let ref_headers = ref_rdr.byte_headers()?.clone();
+            let reference = if fk.reference.is_empty() {
+                "input".to_string()
+            } else {
+                fk.reference.clone()
+            };
+            let ref_indices = column_indices(&ref_headers, &fk.reference_columns, &reference)?;
+
+            let mut values = HashSet::new();
+            let mut ref_record = ByteRecord::new();
+            while ref_rdr.read_byte_record(&mut ref_record)? {
+                if trim {
+                    ref_record.trim();
+                }
+                if build_composite_key(&ref_record, &ref_indices, missing_values, &mut key) {
+                    values.insert(key.clone());
+                }
+            }
+
+            foreign_keys.push(ForeignKeyState {
+                label: fk.columns.join(","),
+                reference,
+                indices: column_indices(headers, &fk.columns, "input")?,
+                values,
+            });
+        }
+
+        Ok(Self {
+            unique_keys,
+            foreign_keys,
+            missing_values: missing_values.to_vec(),
+            key,
+        })
+    }
+
+    /// returns the key constraint violations of the record.
+    /// The row number of the record is in its last field, at `header_len`.
+    fn check(&mut self, record: &ByteRecord, header_len: usize) -> Vec<Finding> {
+        let row_number_bytes = record.get(header_len).unwrap_or_default();
+        let row_number = atoi_simd::parse::<u64>(row_number_bytes).unwrap_or_default();
+        let mut findings: Vec<Finding> = Vec::new();
+
+        for uk in &mut self.unique_keys {
+            if !build_composite_key(record, &uk.indices, &self.missing_values, &mut self.key) {
+                continue;
+            }
+            if let Some(first_row) = uk.seen.get(&self.key) {
+                let value = display_composite_key(&self.key);
+                findings.push(Finding {
+                    row: row_number,
+                    field: uk.label.clone(),
+                    keyword: uk.kind.clone(),
+                    message: format!(
+                        "{} value \"{value}\" is not unique. It is also in row {first_row}.",
+                        uk.kind
+                    ),
+                    value,
+                });
+            } else {
+                uk.seen.insert(self.key.clone(), row_number);
+            }
+        }
+
+        for fk in &self.foreign_keys {
+            if build_composite_key(record, &fk.indices, &self.missing_values, &mut self.key)
+                && !fk.values.contains(&self.key)
+            {
+                let value = display_composite_key(&self.key);
+                findings.push(Finding {
+                    row: row_number,
+                    field: fk.label.clone(),
+                    keyword: "foreignKey".to_string(),
+                    message: format!(
+                        "foreign key value \"{value}\" not found in {}.",
+                        fk.reference
+                    ),
+                    value,
+                });
+            }
+        }
+
+        findings
+    }
+}
+
+/// a validation error of a cell, for --report & --annotate
+struct Finding {
+    row:     u64,
+    field:   String,
+    keyword: String,
+    message: String,
+    value:   String,
+}
+
+impl Finding {
+    /// the validation-errors.tsv entry of the finding
+    fn error_line(&self) -> String {
+        format!("{}\t{}\t{}", self.row, self.field, self.message)
+    }
+}
+
+/// Validates records against a Frictionless Table Schema, the same way `validate` does.
+/// Used by `from datapackage`, whose resources are checked by position against the
+/// schema's fields.
+#[cfg(all(feature = "to", feature = "feature_capable"))]
+pub struct TableSchemaValidator {
+    field_names:    Vec<String>,
+    missing_values: Vec<Vec<u8>>,
+    header_types:   Vec<(String, JSONtypes)>,
+    validator:      Validator,
+    key_checker:    Option<KeyChecker>,
+    record:         ByteRecord,
+}
+
+#[cfg(all(feature = "to", feature = "feature_capable"))]
+impl TableSchemaValidator {
+    pub fn new(table_schema: &Value) -> CliResult<Self> {
+        let (json_schema, mut key_constraints, missing_values) =
+            table_schema_to_json_schema(table_schema)?;
+        let missing_values: Vec<Vec<u8>> =
+            missing_values.into_iter().map(String::into_bytes).collect();
+        let field_names: Vec<String> = table_schema["fields"]
+            .as_array()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|field| field["name"].as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let headers: ByteRecord = field_names.iter().collect();
+
+        let validator = match validator_options().build(&json_schema) {
+            Ok(validator) => validator,
+            Err(e) => return fail_clierror!("Cannot compile the Table Schema. error: {e}"),
+        };
+        let _ = NULL_TYPE.get_or_init(|| Value::String("null".to_string()));
+        let header_types = get_json_types(&headers, &json_schema)?;
+
+        // foreign keys reference other resources of the data package, not CSV files
+        if !key_constraints.foreign_keys.is_empty() {
+            wwarn!("Ignoring the foreignKeys of the Table Schema, as they are not supported.");
+            key_constraints.foreign_keys.clear();
+        }
+        let key_checker = if key_constraints.is_empty() {
+            None
+        } else {
+            Some(KeyChecker::new(
+                &key_constraints,
+                &headers,
+                None,
+                false,
+                &missing_values,
+            )?)
+        };
+
+        Ok(Self {
+            field_names,
+            missing_values,
+            header_types,
+            validator,
+            key_checker,
+            record: ByteRecord::new(),
+        })
+    }
+
+    /// the names of the schema's fields, in order
+    pub fn field_names(&self) -> &[String] {
+        &self.field_names
+    }
+
+    /// true if the value is one of the schema's missingValues
+    pub fn is_missing(&self, value: &str) -> bool {
+        is_missing_value(value.as_bytes(), &self.missing_values)
+    }
+
+    /// validates a record with one field per schema field. Rows are numbered from 1.
+    /// Returns the validation errors, as "<field>: <message>".
+    pub fn validate_row(&mut self, row: u64, record: &csv::StringRecord) -> Vec<String> {
+        let header_len = self.header_types.len();
+        self.record.clear();
+        for value in record {
+            self.record.push_field(value.as_bytes());
+        }
+        self.record
+            .push_field(itoa::Buffer::new().format(row).as_bytes());
+
+        let mut findings = match to_json_instance(
+            &self.header_types,
+            header_len,
+            &self.record,
+            &self.missing_values,
+        ) {
+            Ok(instance) if self.validator.is_valid(&instance) => Vec::new(),
+            _ => record_findings(
+                &self.header_types,
+                header_len,
+                &self.record,
+                &self.missing_values,
+                &self.validator,
+            ),
+        };
+        if let Some(ref mut checker) = self.key_checker {
+            findings.extend(checker.check(&self.record, header_len));
+        }
+        findings
+            .into_iter()
+            .map(|finding| format!("{}: {}", finding.field, finding.message))
+            .collect()
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -584,6 +1813,19 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let mut rdr = rconfig.reader()?;
 
+    if args.flag_fix && args.arg_json_schema.is_none() {
+        return fail_incorrectusage_clierror!("--fix requires a JSON Schema or Table Schema.");
+    }
+    let report_format = match args.flag_report {
+        Some(ref report) => Some(report.parse::<ReportFormat>()?),
+        None => None,
+    };
+    if (report_format.is_some() || args.flag_annotate) && args.arg_json_schema.is_none() {
+        return fail_incorrectusage_clierror!(
+            "--report and --annotate require a JSON Schema or Table Schema."
+        );
+    }
+
     // if no JSON Schema supplied, only let csv reader RFC4180-validate csv file
     if args.arg_json_schema.is_none() {
         // just read csv file and let csv reader report problems
@@ -842,27 +2084,26 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         })
         .unwrap();
 
-    // parse and compile supplied JSON Schema
-    let (schema_json, schema_compiled): (Value, Validator) =
+    // parse supplied schema. Frictionless Table Schemas are translated to JSON Schema
+    let (schema_json, key_constraints, missing_values): (Value, KeyConstraints, Vec<Vec<u8>>) =
         // safety: we know the schema is_some() because we checked above
         match load_json(&args.arg_json_schema.unwrap()) {
             Ok(s) => {
                 // parse JSON string
                 let mut s_slice = s.as_bytes().to_vec();
-                match simd_json::serde::from_slice(&mut s_slice) {
+                match simd_json::serde::from_slice::<Value>(&mut s_slice) {
+                    Ok(json) if is_table_schema(&json) => {
+                        let (json_schema, key_constraints, missing_values) =
+                            table_schema_to_json_schema(&json)?;
+                        (
+                            json_schema,
+                            key_constraints,
+                            missing_values.into_iter().map(String::into_bytes).collect(),
+                        )
+                    },
                     Ok(json) => {
-                        // compile JSON Schema
-                        match Validator::options()
-                            .with_format("currency", currency_format_checker)
-                            .with_keyword("dynamicEnum", dyn_enum_validator_factory)
-                            .should_validate_formats(true)
-                            .build(&json)
-                        {
-                            Ok(schema) => (json, schema),
-                            Err(e) => {
-                                return fail_clierror!("Cannot compile JSONschema. error: {e}");
-                            },
-                        }
+                        let key_constraints = json_schema_key_constraints(&json)?;
+                        (json, key_constraints, EMPTY_MISSING_VALUES.to_vec())
                     },
                     Err(e) => {
                         return fail_clierror!("Unable to parse JSONschema. error: {e}");
@@ -874,6 +2115,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             },
         };
 
+    // compile JSON Schema
+    let mut validator_options =
+        validator_options().with_keyword("dynamicEnum", dyn_enum_validator_factory);
+    if let Some(ref formats_path) = args.flag_formats {
+        for (name, custom_format) in load_custom_formats(formats_path)? {
+            validator_options =
+                validator_options.with_format(name, move |s: &str| custom_format.is_valid(s));
+        }
+    }
+    let schema_compiled: Validator = match validator_options.build(&schema_json) {
+        Ok(schema) => schema,
+        Err(e) => {
+            return fail_clierror!("Cannot compile JSONschema. error: {e}");
+        },
+    };
+
     if log::log_enabled!(log::Level::Debug) {
         // only log if debug is enabled
         // as it can be quite large and expensive to deserialize the schema
@@ -886,6 +2143,33 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // get JSON types for each column in CSV file
     let header_types = get_json_types(&headers, &schema_json)?;
 
+    let field_fixers = if args.flag_fix {
+        Some(get_field_fixers(
+            &headers,
+            &schema_json,
+            rconfig.get_dmy_preference(),
+        ))
+    } else {
+        None
+    };
+    let collect_findings = args.flag_report.is_some() || args.flag_annotate;
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut fix_log: Vec<String> = Vec::new();
+    let mut fixed_records: HashMap<u64, ByteRecord> = HashMap::new();
+
+    // unique & foreign keys are checked sequentially, as they span rows
+    let mut key_checker = if key_constraints.is_empty() {
+        None
+    } else {
+        Some(KeyChecker::new(
+            &key_constraints,
+            &headers,
+            Some(&rconfig),
+            args.flag_trim,
+            &missing_values,
+        )?)
+    };
+
     // how many rows read and processed as batches
     let mut row_number: u64 = 0;
     // how many invalid rows found
@@ -936,20 +2220,92 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         batch
             .par_iter()
             .with_min_len(1024)
-            .map(|record| do_json_validation(&header_types, header_len, record, &schema_compiled))
+            .map(|record| {
+                let result = do_json_validation(
+                    &header_types,
+                    header_len,
+                    record,
+                    &missing_values,
+                    &schema_compiled,
+                );
+                match (&result, &field_fixers) {
+                    (Some(_), Some(fixers)) => {
+                        match fix_record(
+                            fixers,
+                            &header_types,
+                            header_len,
+                            record,
+                            &missing_values,
+                            &schema_compiled,
+                        ) {
+                            Some(fixed) => (None, Some(fixed)),
+                            None => (result, None),
+                        }
+                    },
+                    _ => (result, None),
+                }
+            })
             .collect_into_vec(&mut validation_results);
 
         // write to validation error report, but keep Vec<bool> to gen valid/invalid files later
         // because Rayon collect() guarantees original order, we can sequentially append results
         // to vector with each batch
-        for result in &validation_results {
-            if let Some(validation_error_msg) = result {
+        for ((result, fixed), record) in validation_results.iter().zip(batch.iter()) {
+            let mut error_msg = result.clone();
+            let record = fixed
+                .as_ref()
+                .map_or(record, |(fixed_record, _)| fixed_record);
+            let key_findings = match key_checker {
+                Some(ref mut checker) => checker.check(record, header_len),
+                None => Vec::new(),
+            };
+            if !key_findings.is_empty() {
+                let key_errors = key_findings
+                    .iter()
+                    .map(Finding::error_line)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                error_msg = Some(match error_msg {
+                    Some(msg) => format!("{msg}\n{key_errors}"),
+                    None => key_errors,
+                });
+            }
+            if collect_findings && error_msg.is_some() {
+                if result.is_some() {
+                    findings.extend(record_findings(
+                        &header_types,
+                        header_len,
+                        record,
+                        &missing_values,
+                        &schema_compiled,
+                    ));
+                }
+                findings.extend(key_findings);
+            }
+
+            if let Some(validation_error_msg) = error_msg {
                 invalid_count += 1;
                 valid_flags.push(false);
 
-                validation_error_messages.push(validation_error_msg.to_string());
+                validation_error_messages.push(validation_error_msg);
             } else {
                 valid_flags.push(true);
+
+                if let Some((fixed_record, fixes)) = fixed {
+                    let fixed_row_number =
+                        atoi_simd::parse::<u64>(fixed_record.get(header_len).unwrap_or_default())
+                            .unwrap_or_default();
+                    for fix in fixes {
+                        fix_log.push(format!(
+                            "{fixed_row_number}\t{}\t{}\t{}",
+                            header_types[fix.column].0, fix.original, fix.fixed
+                        ));
+                    }
+                    fixed_records.insert(
+                        fixed_row_number,
+                        fixed_record.iter().take(header_len).collect(),
+                    );
+                }
             }
         }
 
@@ -974,6 +2330,39 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         util::finish_progress(&progress);
     }
 
+    let input_path = args
+        .arg_input
+        .clone()
+        .unwrap_or_else(|| "stdin.csv".to_string());
+
+    if !fixed_records.is_empty() {
+        write_fixed_records(&rconfig, &fixed_records, &headers, &input_path)?;
+        write_fix_log(&input_path, fix_log)?;
+        if !args.flag_quiet {
+            winfo!(
+                "{} records repaired. Repaired input written to {input_path}.fixed",
+                HumanCount(fixed_records.len() as u64)
+            );
+        }
+    }
+
+    if let Some(report_format) = report_format {
+        let report_path = write_report(
+            report_format,
+            &findings,
+            &input_path,
+            row_number,
+            invalid_count,
+            rconfig.no_headers,
+        )?;
+        if !args.flag_quiet {
+            winfo!("Validation report written to {report_path}");
+        }
+    }
+    if args.flag_annotate {
+        write_annotated_records(&rconfig, &findings, &headers, &input_path)?;
+    }
+
     if invalid_count == 0 {
         // no invalid records found
         // see if we need to pass all valid records to output
@@ -991,8 +2380,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
             let mut rdr = rconfig.reader()?;
             let mut record = csv::ByteRecord::new();
+            let mut valid_row_num: u64 = 0;
             while rdr.read_byte_record(&mut record)? {
-                valid_wtr.write_byte_record(&record)?;
+                valid_row_num += 1;
+                valid_wtr
+                    .write_byte_record(fixed_records.get(&valid_row_num).unwrap_or(&record))?;
             }
             valid_wtr.flush()?;
             // return 1 as an exitcode and the number of valid rows to stderr
@@ -1003,11 +2395,6 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // if 100% invalid, valid file isn't needed, but this is rare so OK creating empty file.
         woutinfo!("Writing invalid/valid/error files...");
 
-        let input_path = args
-            .arg_input
-            .clone()
-            .unwrap_or_else(|| "stdin.csv".to_string());
-
         write_error_report(&input_path, validation_error_messages)?;
 
         let valid_suffix = args.flag_valid.unwrap_or_else(|| "valid".to_string());
@@ -1016,6 +2403,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         split_invalid_records(
             &rconfig,
             &valid_flags[..],
+            &fixed_records,
             &headers,
             &input_path,
             &valid_suffix,
@@ -1048,6 +2436,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 fn split_invalid_records(
     rconfig: &Config,
     valid_flags: &[bool],
+    fixed_records: &HashMap<u64, ByteRecord>,
     headers: &ByteRecord,
     input_path: &str,
     valid_suffix: &str,
@@ -1081,7 +2470,12 @@ fn split_invalid_records(
         let is_valid = valid_flags[split_row_num - 1];
 
         if is_valid {
-            valid_wtr.write_byte_record(&record)?;
+            // repaired records are written with their fixes
+            valid_wtr.write_byte_record(
+                fixed_records
+                    .get(&(split_row_num as u64))
+                    .unwrap_or(&record),
+            )?;
         } else {
             invalid_wtr.write_byte_record(&record)?;
         }
@@ -1093,6 +2487,238 @@ fn split_invalid_records(
     Ok(())
 }
 
+#[derive(Clone, Copy)]
+enum ReportFormat {
+    Junit,
+    Sarif,
+    Json,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "junit" => Ok(ReportFormat::Junit),
+            "sarif" => Ok(ReportFormat::Sarif),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(CliError::IncorrectUsage(format!(
+                "Invalid --report format: {s}. Must be one of junit, sarif or json."
+            ))),
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// writes the --report file, returning its path
+fn write_report(
+    report_format: ReportFormat,
+    findings: &[Finding],
+    input_path: &str,
+    record_count: u64,
+    invalid_count: u64,
+    no_headers: bool,
+) -> CliResult<String> {
+    let input_filename = std::path::Path::new(input_path).file_name().map_or_else(
+        || input_path.to_string(),
+        |f| f.to_string_lossy().into_owned(),
+    );
+
+    let (report_path, report) = match report_format {
+        ReportFormat::Junit => {
+            let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            let suite_name = xml_escape(&format!("qsv validate {input_filename}"));
+            xml.push_str(&format!(
+                "<testsuites name=\"{suite_name}\" tests=\"{0}\" failures=\"{0}\">\n<testsuite \
+                 name=\"{suite_name}\" tests=\"{0}\" failures=\"{0}\">\n",
+                findings.len()
+            ));
+            for finding in findings {
+                xml.push_str(&format!(
+                    "  <testcase classname=\"{}\" name=\"row {} {}\">\n    <failure type=\"{}\" \
+                     message=\"{}\">value: {}</failure>\n  </testcase>\n",
+                    xml_escape(&input_filename),
+                    finding.row,
+                    xml_escape(&finding.field),
+                    xml_escape(&finding.keyword),
+                    xml_escape(&finding.message),
+                    xml_escape(&finding.value),
+                ));
+            }
+            xml.push_str("</testsuite>\n</testsuites>\n");
+            (format!("{input_path}.validation-report.xml"), xml)
+        },
+        ReportFormat::Sarif => {
+            // the header row is on the first line
+            let line_offset = u64::from(!no_headers);
+            let mut rule_ids: Vec<&str> = findings.iter().map(|f| f.keyword.as_str()).collect();
+            rule_ids.sort_unstable();
+            rule_ids.dedup();
+            let rules: Vec<Value> = rule_ids.iter().map(|id| json!({ "id": id })).collect();
+            let results: Vec<Value> = findings
+                .iter()
+                .map(|f| {
+                    json!({
+                        "ruleId": f.keyword,
+                        "level": "error",
+                        "message": {"text": format!("{}: {}", f.field, f.message)},
+                        "locations": [{
+                            "physicalLocation": {
+                                "artifactLocation": {"uri": input_path},
+                                "region": {"startLine": f.row + line_offset}
+                            }
+                        }],
+                        "properties": {"row": f.row, "field": f.field, "value": f.value}
+                    })
+                })
+                .collect();
+            let sarif = json!({
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "qsv validate",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "informationUri": "https://github.com/dathere/qsv",
+                            "rules": rules
+                        }
+                    },
+                    "results": results
+                }]
+            });
+            (
+                format!("{input_path}.validation-report.sarif"),
+                serde_json::to_string_pretty(&sarif)?,
+            )
+        },
+        ReportFormat::Json => {
+            let json_findings: Vec<Value> = findings
+                .iter()
+                .map(|f| {
+                    json!({
+                        "row": f.row,
+                        "field": f.field,
+                        "keyword": f.keyword,
+                        "message": f.message,
+                        "value": f.value,
+                    })
+                })
+                .collect();
+            let report = json!({
+                "input": input_path,
+                "valid": invalid_count == 0,
+                "record_count": record_count,
+                "invalid_count": invalid_count,
+                "findings": json_findings
+            });
+            (
+                format!("{input_path}.validation-report.json"),
+                serde_json::to_string_pretty(&report)?,
+            )
+        },
+    };
+
+    std::fs::write(&report_path, report)?;
+    Ok(report_path)
+}
+
+/// writes the input with an appended "_errors" column to an ".annotated" file
+fn write_annotated_records(
+    rconfig: &Config,
+    findings: &[Finding],
+    headers: &ByteRecord,
+    input_path: &str,
+) -> CliResult<()> {
+    let mut row_errors: HashMap<u64, Vec<String>> = HashMap::new();
+    for finding in findings {
+        row_errors
+            .entry(finding.row)
+            .or_default()
+            .push(format!("{}: {}", finding.field, finding.message));
+    }
+
+    let mut annotated_wtr =
+        Config::new(Some(input_path.to_owned() + ".annotated").as_ref()).writer()?;
+    if !rconfig.no_headers {
+        let mut annotated_headers = headers.clone();
+        annotated_headers.push_field(b"_errors");
+        annotated_wtr.write_byte_record(&annotated_headers)?;
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let mut record = csv::ByteRecord::new();
+    let mut annotated_row_num: u64 = 0;
+    while rdr.read_byte_record(&mut record)? {
+        annotated_row_num += 1;
+        let errors = row_errors
+            .get(&annotated_row_num)
+            .map(|errors| errors.join("; "))
+            .unwrap_or_default();
+        record.push_field(errors.as_bytes());
+        annotated_wtr.write_byte_record(&record)?;
+    }
+    annotated_wtr.flush()?;
+
+    Ok(())
+}
+
+/// writes the input with the repaired records to a ".fixed" file
+fn write_fixed_records(
+    rconfig: &Config,
+    fixed_records: &HashMap<u64, ByteRecord>,
+    headers: &ByteRecord,
+    input_path: &str,
+) -> CliResult<()> {
+    let mut fixed_wtr = Config::new(Some(input_path.to_owned() + ".fixed").as_ref()).writer()?;
+    if !rconfig.no_headers {
+        fixed_wtr.write_byte_record(headers)?;
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let mut record = csv::ByteRecord::new();
+    let mut fixed_row_num: u64 = 0;
+    while rdr.read_byte_record(&mut record)? {
+        fixed_row_num += 1;
+        fixed_wtr.write_byte_record(fixed_records.get(&fixed_row_num).unwrap_or(&record))?;
+    }
+    fixed_wtr.flush()?;
+
+    Ok(())
+}
+
+fn write_fix_log(input_path: &str, fix_log: Vec<String>) -> CliResult<()> {
+    let wtr_capacitys = env::var("QSV_WTR_BUFFER_CAPACITY")
+        .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string());
+    let wtr_buffer_size: usize = wtr_capacitys.parse().unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY);
+
+    let output_file = File::create(input_path.to_owned() + ".fix-log.tsv")?;
+    let mut output_writer = BufWriter::with_capacity(wtr_buffer_size, output_file);
+
+    output_writer.write_all(b"row_number\tfield\toriginal\tfixed\n")?;
+    for fix in fix_log {
+        output_writer.write_all(fix.as_bytes())?;
+        output_writer.write_all(b"\n")?;
+    }
+    output_writer.flush()?;
+
+    Ok(())
+}
+
 fn write_error_report(input_path: &str, validation_error_messages: Vec<String>) -> CliResult<()> {
     let wtr_capacitys = env::var("QSV_WTR_BUFFER_CAPACITY")
         .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string());
@@ -1124,13 +2750,14 @@ fn do_json_validation(
     header_types: &[(String, JSONtypes)],
     header_len: usize,
     record: &ByteRecord,
+    missing_values: &[Vec<u8>],
     schema_compiled: &Validator,
 ) -> Option<String> {
     // safety: row number was added as last column. We can unwrap safely since we know its there
     let row_number_string = simdutf8::basic::from_utf8(record.get(header_len).unwrap()).unwrap();
 
     validate_json_instance(
-        &(match to_json_instance(header_types, header_len, record) {
+        &(match to_json_instance(header_types, header_len, record, missing_values) {
             Ok(obj) => obj,
             Err(e) => {
                 return Some(format!("{row_number_string}\t<RECORD>\t{e}"));
@@ -1160,57 +2787,386 @@ fn to_json_instance(
     header_types: &[(String, JSONtypes)],
     header_len: usize,
     record: &ByteRecord,
+    missing_values: &[Vec<u8>],
 ) -> CliResult<Value> {
     let mut json_object_map = Map::with_capacity(header_len);
 
     for ((key, json_type), value) in header_types.iter().zip(record.iter()) {
-        if value.is_empty() {
-            json_object_map.insert(key.clone(), Value::Null);
-            continue;
-        }
+        json_object_map.insert(
+            key.clone(),
+            to_json_value(key, json_type, value, missing_values)?,
+        );
+    }
+
+    Ok(Value::Object(json_object_map))
+}
+
+#[inline]
+fn to_json_value(
+    key: &str,
+    json_type: &JSONtypes,
+    value: &[u8],
+    missing_values: &[Vec<u8>],
+) -> CliResult<Value> {
+    if is_missing_value(value, missing_values) {
+        return Ok(Value::Null);
+    }
 
-        let json_value = match json_type {
-            JSONtypes::String => match simdutf8::basic::from_utf8(value) {
-                Ok(v) => Value::String(v.to_owned()),
-                Err(_) => Value::String(String::from_utf8_lossy(value).into_owned()),
+    let json_value = match json_type {
+        JSONtypes::String => match simdutf8::basic::from_utf8(value) {
+            Ok(v) => Value::String(v.to_owned()),
+            Err(_) => Value::String(String::from_utf8_lossy(value).into_owned()),
+        },
+        JSONtypes::Number => match fast_float2::parse(value) {
+            Ok(float) => Value::Number(Number::from_f64(float).unwrap_or_else(|| Number::from(0))),
+            Err(_) => {
+                return fail_clierror!(
+                    "Can't cast into Number. key: {key}, value: {}",
+                    String::from_utf8_lossy(value)
+                )
             },
-            JSONtypes::Number => match fast_float2::parse(value) {
-                Ok(float) => {
-                    Value::Number(Number::from_f64(float).unwrap_or_else(|| Number::from(0)))
-                },
-                Err(_) => {
-                    return fail_clierror!(
-                        "Can't cast into Number. key: {key}, value: {}",
-                        String::from_utf8_lossy(value)
-                    )
-                },
+        },
+        JSONtypes::Integer => match atoi_simd::parse::<i64>(value) {
+            Ok(int) => Value::Number(Number::from(int)),
+            Err(_) => {
+                return fail_clierror!(
+                    "Can't cast into Integer. key: {key}, value: {}",
+                    String::from_utf8_lossy(value)
+                )
             },
-            JSONtypes::Integer => match atoi_simd::parse::<i64>(value) {
-                Ok(int) => Value::Number(Number::from(int)),
-                Err(_) => {
-                    return fail_clierror!(
-                        "Can't cast into Integer. key: {key}, value: {}",
-                        String::from_utf8_lossy(value)
-                    )
-                },
+        },
+        JSONtypes::Boolean => match value {
+            b"true" | b"1" => Value::Bool(true),
+            b"false" | b"0" => Value::Bool(false),
+            _ => {
+                return fail_clierror!(
+                    "Can't cast into Boolean. key: {key}, value: {}",
+                    String::from_utf8_lossy(value)
+                )
             },
-            JSONtypes::Boolean => match value {
-                b"true" | b"1" => Value::Bool(true),
-                b"false" | b"0" => Value::Bool(false),
-                _ => {
-                    return fail_clierror!(
-                        "Can't cast into Boolean. key: {key}, value: {}",
-                        String::from_utf8_lossy(value)
-                    )
-                },
+        },
+        JSONtypes::Unsupported => unreachable!("we should never get an unsupported JSON type"),
+    };
+
+    Ok(json_value)
+}
+
+/// returns a finding for each failing cell of an invalid record.
+/// Unlike do_json_validation, cells that can't be cast into their JSON type are
+/// reported individually, and the rest of the record is still validated.
+fn record_findings(
+    header_types: &[(String, JSONtypes)],
+    header_len: usize,
+    record: &ByteRecord,
+    missing_values: &[Vec<u8>],
+    schema_compiled: &Validator,
+) -> Vec<Finding> {
+    let row_number =
+        atoi_simd::parse::<u64>(record.get(header_len).unwrap_or_default()).unwrap_or_default();
+    let cell_value = |field: &str| {
+        header_types
+            .iter()
+            .position(|(key, _)| key == field)
+            .and_then(|i| record.get(i))
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .unwrap_or_default()
+    };
+
+    let mut findings = Vec::new();
+    let mut json_object_map = Map::with_capacity(header_len);
+    for ((key, json_type), value) in header_types.iter().zip(record.iter()) {
+        match to_json_value(key, json_type, value, missing_values) {
+            Ok(json_value) => {
+                json_object_map.insert(key.clone(), json_value);
             },
-            JSONtypes::Unsupported => unreachable!("we should never get an unsupported JSON type"),
+            Err(e) => findings.push(Finding {
+                row:     row_number,
+                field:   key.clone(),
+                keyword: "type".to_string(),
+                message: e.to_string(),
+                value:   String::from_utf8_lossy(value).into_owned(),
+            }),
+        }
+    }
+    let cast_failures = findings.len();
+
+    if let BasicOutput::Invalid(errors) = schema_compiled
+        .apply(&Value::Object(json_object_map))
+        .basic()
+    {
+        for e in &errors {
+            let instance_location = e.instance_location().to_string();
+            let field = instance_location.trim_start_matches('/');
+            let field_name = field.replace("~1", "/").replace("~0", "~");
+            let keyword_location = e.keyword_location().to_string();
+            let keyword = keyword_location.rsplit('/').next().unwrap_or_default();
+            // cells that couldn't be cast are missing from the instance,
+            // so skip the schema errors about them
+            if keyword == "required"
+                && findings[..cast_failures].iter().any(|f| {
+                    e.error_description()
+                        .to_string()
+                        .contains(&format!("\"{}\"", f.field))
+                })
+            {
+                continue;
+            }
+            findings.push(Finding {
+                row:     row_number,
+                field:   field.to_string(),
+                keyword: keyword.to_string(),
+                message: e.error_description().to_string(),
+                value:   cell_value(&field_name),
+            });
+        }
+    }
+
+    findings
+}
+
+/// how to coerce the values of a column towards its schema definition in --fix mode
+struct FieldFixer {
+    integer:     bool,
+    number:      bool,
+    boolean:     bool,
+    date:        bool,
+    datetime:    bool,
+    enum_values: Vec<String>,
+    prefer_dmy:  bool,
+}
+
+struct FieldFix {
+    column:   usize,
+    original: String,
+    fixed:    String,
+}
+
+fn get_field_fixers(headers: &ByteRecord, schema: &Value, prefer_dmy: bool) -> Vec<FieldFixer> {
+    let schema_properties = schema.get("properties");
+
+    headers
+        .iter()
+        .map(|header| {
+            let field_def = simdutf8::basic::from_utf8(header)
+                .ok()
+                .and_then(|key| schema_properties.and_then(|props| props.get(key)))
+                .unwrap_or(&Value::Null);
+            let types: Vec<&str> = match field_def.get("type") {
+                Some(Value::String(t)) => vec![t.as_str()],
+                Some(Value::Array(vec)) => vec.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            let format = field_def.get("format").and_then(Value::as_str);
+
+            FieldFixer {
+                integer: types.contains(&"integer"),
+                number: types.contains(&"number"),
+                boolean: types.contains(&"boolean"),
+                date: format == Some("date"),
+                datetime: format == Some("date-time"),
+                enum_values: field_def
+                    .get("enum")
+                    .and_then(Value::as_array)
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|v| v.as_str().map(ToString::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                prefer_dmy,
+            }
+        })
+        .collect()
+}
+
+/// strips currency symbols & codes, thousands separators & accounting-style parentheses
+/// from a number. Returns None if anything else is left, so "5kg" is not fixed.
+fn coerce_number(value: &str, integer: bool) -> Option<String> {
+    let (mut negative, value) = match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        Some(inner) => (true, inner.trim()),
+        None => (false, value),
+    };
+    // the minus sign can be before or after the currency, e.g. "-$5" or "$-5"
+    let mut value = strip_currency(value);
+    if let Some(unsigned) = value.strip_prefix('-') {
+        negative = !negative;
+        value = strip_currency(unsigned);
+    }
+
+    let mut number = String::with_capacity(value.len() + 1);
+    if negative {
+        number.push('-');
+    }
+    let mut chars = value.chars().peekable();
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        // thousands separators are only removed between the digits of the integer part
+        if matches!(c, ',' | '_' | ' ' | '\'' | '\u{a0}' | '\u{202f}') {
+            if number.contains('.')
+                || !previous.is_some_and(|p: char| p.is_ascii_digit())
+                || !chars.peek().is_some_and(char::is_ascii_digit)
+            {
+                return None;
+            }
+        } else {
+            number.push(c);
+        }
+        previous = Some(c);
+    }
+
+    let parsed = fast_float2::parse::<f64, _>(number.as_bytes())
+        .ok()
+        .filter(|n: &f64| n.is_finite())?;
+    if !integer {
+        return Some(number);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let i64_max = i64::MAX as f64;
+    if parsed.fract() == 0.0 && parsed.abs() < i64_max {
+        Some((parsed as i64).to_string())
+    } else {
+        None
+    }
+}
+
+/// strips a currency symbol or ISO 4217 code before or after an amount,
+/// e.g. "$5", "USD 5", "R$ 5" or "5 €"
+fn strip_currency(value: &str) -> &str {
+    let is_symbol = |c: char| {
+        matches!(
+            c,
+            '$' | '¢' | '£' | '¤' | '¥' | '֏' | '؋' | '৲' | '৳' | '฿' | '៛' | '﷼'
+        ) || ('\u{20a0}'..='\u{20cf}').contains(&c)
+    };
+
+    let value = value.trim();
+    let code_len = value.len()
+        - value
+            .trim_start_matches(|c: char| c.is_ascii_uppercase())
+            .len();
+    let value = match value[code_len..].trim_start_matches(is_symbol) {
+        // a symbol, with an optional country prefix (e.g. "US$")
+        rest if rest.len() < value.len() - code_len && code_len <= 3 => rest,
+        rest if code_len == 3 => rest,
+        _ => value,
+    };
+
+    let value = value.trim();
+    let code_len = value.len()
+        - value
+            .trim_end_matches(|c: char| c.is_ascii_uppercase())
+            .len();
+    let amount = &value[..value.len() - code_len];
+    let value = match amount.trim_end_matches(is_symbol) {
+        rest if rest.len() < amount.len() && code_len == 0 => rest,
+        _ if code_len == 3 => amount,
+        _ => value,
+    };
+    value.trim()
+}
+
+fn coerce_boolean(value: &str) -> Option<String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "on" | "1" => Some("true".to_string()),
+        "false" | "f" | "no" | "n" | "off" | "0" => Some("false".to_string()),
+        _ => None,
+    }
+}
+
+/// qsv_dateparser parses numbers as unix timestamps, so only values with digits that
+/// aren't numbers are fixed as dates
+fn is_date_like(value: &str) -> bool {
+    value.bytes().any(|b| b.is_ascii_digit())
+        && fast_float2::parse::<f64, _>(value.as_bytes()).is_err()
+}
+
+impl FieldFixer {
+    /// returns the coerced value, if it's different from the original
+    fn fix(&self, value: &str) -> Option<String> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let fixed = if self.integer || self.number {
+            coerce_number(trimmed, !self.number)
+        } else if self.boolean {
+            coerce_boolean(trimmed)
+        } else if (self.date || self.datetime) && is_date_like(trimmed) {
+            qsv_dateparser::parse_with_preference(trimmed, self.prefer_dmy)
+                .ok()
+                .map(|dt| {
+                    if self.date {
+                        dt.format("%Y-%m-%d").to_string()
+                    } else {
+                        dt.to_rfc3339()
+                    }
+                })
+        } else if self.enum_values.is_empty() {
+            None
+        } else {
+            self.enum_values
+                .iter()
+                .find(|v| v.eq_ignore_ascii_case(trimmed))
+                .cloned()
         };
 
-        json_object_map.insert(key.clone(), json_value);
+        match fixed {
+            Some(fixed) if fixed != value => Some(fixed),
+            None if trimmed != value => Some(trimmed.to_string()),
+            _ => None,
+        }
     }
+}
 
-    Ok(Value::Object(json_object_map))
+/// tries to repair an invalid record. Returns the repaired record (with its row number)
+/// and the fixes made if the repaired record is valid.
+fn fix_record(
+    fixers: &[FieldFixer],
+    header_types: &[(String, JSONtypes)],
+    header_len: usize,
+    record: &ByteRecord,
+    missing_values: &[Vec<u8>],
+    schema_compiled: &Validator,
+) -> Option<(ByteRecord, Vec<FieldFix>)> {
+    let mut fixes = Vec::new();
+    let mut fixed_record = ByteRecord::with_capacity(record.as_slice().len(), record.len());
+
+    for (i, field) in record.iter().enumerate() {
+        let fix = if i < header_len && !is_missing_value(field, missing_values) {
+            fixers
+                .get(i)
+                .and_then(|fixer| fixer.fix(&String::from_utf8_lossy(field)))
+        } else {
+            None
+        };
+        if let Some(fixed) = fix {
+            fixed_record.push_field(fixed.as_bytes());
+            fixes.push(FieldFix {
+                column: i,
+                original: String::from_utf8_lossy(field).into_owned(),
+                fixed,
+            });
+        } else {
+            fixed_record.push_field(field);
+        }
+    }
+
+    if fixes.is_empty()
+        || do_json_validation(
+            header_types,
+            header_len,
+            &fixed_record,
+            missing_values,
+            schema_compiled,
+        )
+        .is_some()
+    {
+        None
+    } else {
+        Some((fixed_record, fixes))
+    }
 }
 
 /// get JSON types for each column in CSV file
@@ -1346,7 +3302,7 @@ mod tests_for_csv_to_json_conversion {
         record.trim();
 
         assert_eq!(
-            to_json_instance(&header_types, headers.len(), &record)
+            to_json_instance(&header_types, headers.len(), &record, EMPTY_MISSING_VALUES)
                 .expect("can't convert csv to json instance"),
             json!({
                 "A": "hello",
@@ -1379,6 +3335,7 @@ mod tests_for_csv_to_json_conversion {
             &header_types,
             headers.len(),
             &rdr.byte_records().next().unwrap().unwrap(),
+            EMPTY_MISSING_VALUES,
         );
         assert!(&result.is_err());
         let error = result.err().unwrap().to_string();
@@ -1509,7 +3466,8 @@ mod tests_for_schema_validation {
 
         let record = &rdr.byte_records().next().unwrap().unwrap();
 
-        let instance = to_json_instance(&header_types, headers.len(), record).unwrap();
+        let instance =
+            to_json_instance(&header_types, headers.len(), record, EMPTY_MISSING_VALUES).unwrap();
 
         let result = validate_json_instance(&instance, &compiled_schema());
 
@@ -1528,7 +3486,8 @@ mod tests_for_schema_validation {
 
         let record = &rdr.byte_records().next().unwrap().unwrap();
 
-        let instance = to_json_instance(&header_types, headers.len(), record).unwrap();
+        let instance =
+            to_json_instance(&header_types, headers.len(), record, EMPTY_MISSING_VALUES).unwrap();
 
         let result = validate_json_instance(&instance, &compiled_schema());
 
@@ -1597,7 +3556,8 @@ fn test_validate_currency_email_dynamicenum_validator() {
 
     let record = &rdr.byte_records().next().unwrap().unwrap();
 
-    let instance = to_json_instance(&header_types, headers.len(), record).unwrap();
+    let instance =
+        to_json_instance(&header_types, headers.len(), record, EMPTY_MISSING_VALUES).unwrap();
 
     let compiled_schema = Validator::options()
         .with_format("currency", currency_format_checker)
@@ -1626,7 +3586,8 @@ fn test_validate_currency_email_dynamicenum_validator() {
 
     let record = &rdr.byte_records().next().unwrap().unwrap();
 
-    let instance = to_json_instance(&header_types, headers.len(), record).unwrap();
+    let instance =
+        to_json_instance(&header_types, headers.len(), record, EMPTY_MISSING_VALUES).unwrap();
 
     let compiled_schema = Validator::options()
         .with_format("currency", currency_format_checker)
@@ -1675,7 +3636,8 @@ fn test_validate_currency_email_dynamicenum_validator() {
 
     for (i, record) in rdr.byte_records().enumerate() {
         let record = record.unwrap();
-        let instance = to_json_instance(&header_types, headers.len(), &record).unwrap();
+        let instance =
+            to_json_instance(&header_types, headers.len(), &record, EMPTY_MISSING_VALUES).unwrap();
 
         let result = validate_json_instance(&instance, &compiled_schema);
 
@@ -1785,3 +3747,171 @@ fn test_dyn_enum_validator() {
         unreachable!("Expected an error, but validation succeeded.");
     };
 }
+
+#[test]
+fn test_date_pattern_validator() {
+    let schema = json!({"datePattern": "%d/%m/%Y", "type": "string"});
+    let validator = jsonschema::options()
+        .with_keyword("datePattern", date_pattern_validator_factory)
+        .build(&schema)
+        .unwrap();
+
+    assert!(validator.is_valid(&json!("31/12/2024")));
+    assert!(!validator.is_valid(&json!("2024-12-31")));
+    assert!(!validator.is_valid(&json!("31/13/2024")));
+}
+
+#[test]
+fn test_table_schema_to_json_schema() {
+    let table_schema = json!({
+        "fields": [
+            {"name": "id", "type": "integer"},
+            {"name": "active", "type": "boolean", "trueValues": ["Y"], "falseValues": ["N"]},
+            {"name": "born", "type": "date", "format": "%d/%m/%Y"},
+            {"name": "code", "type": "string",
+                "constraints": {"required": true, "pattern": "[A-Z]{3}", "unique": true}},
+            {"name": "parent", "type": "integer"}
+        ],
+        "primaryKey": "id",
+        "foreignKeys": [
+            {"fields": "parent", "reference": {"resource": "", "fields": "id"}}
+        ],
+        "missingValues": ["", "NA"]
+    });
+    assert!(is_table_schema(&table_schema));
+
+    let (json_schema, key_constraints, missing_values) =
+        table_schema_to_json_schema(&table_schema).unwrap();
+    assert!(!is_table_schema(&json_schema));
+
+    assert_eq!(
+        json_schema["properties"]["id"],
+        json!({"type": ["integer"]})
+    );
+    assert_eq!(
+        json_schema["properties"]["active"],
+        json!({"type": ["string", "null"], "enum": ["Y", "N", null]})
+    );
+    assert_eq!(
+        json_schema["properties"]["born"],
+        json!({"type": ["string", "null"], "datePattern": "%d/%m/%Y"})
+    );
+    assert_eq!(
+        json_schema["properties"]["code"],
+        json!({"type": ["string"], "pattern": "^(?:[A-Z]{3})$"})
+    );
+    assert_eq!(json_schema["required"], json!(["id", "code"]));
+
+    assert_eq!(
+        key_constraints,
+        KeyConstraints {
+            unique_keys:  vec![
+                UniqueKeyDef {
+                    kind:    "unique".to_string(),
+                    columns: vec!["code".to_string()],
+                },
+                UniqueKeyDef {
+                    kind:    "primaryKey".to_string(),
+                    columns: vec!["id".to_string()],
+                },
+            ],
+            foreign_keys: vec![ForeignKeyDef {
+                columns:           vec!["parent".to_string()],
+                reference:         String::new(),
+                reference_columns: vec!["id".to_string()],
+            }],
+        }
+    );
+    assert_eq!(missing_values, vec![String::new(), "NA".to_string()]);
+}
+
+#[test]
+fn test_json_schema_key_constraints() {
+    let schema = json!({
+        "uniqueKey": [["first", "last"], "email"],
+        "foreignKey": [
+            {"columns": "state", "reference": "states.csv", "referenceColumns": "code"},
+            {"columns": ["manager"], "reference": ""}
+        ]
+    });
+    let key_constraints = json_schema_key_constraints(&schema).unwrap();
+    assert_eq!(
+        key_constraints.unique_keys,
+        vec![
+            UniqueKeyDef {
+                kind:    "uniqueKey".to_string(),
+                columns: vec!["first".to_string(), "last".to_string()],
+            },
+            UniqueKeyDef {
+                kind:    "uniqueKey".to_string(),
+                columns: vec!["email".to_string()],
+            },
+        ]
+    );
+    assert_eq!(
+        key_constraints.foreign_keys,
+        vec![
+            ForeignKeyDef {
+                columns:           vec!["state".to_string()],
+                reference:         "states.csv".to_string(),
+                reference_columns: vec!["code".to_string()],
+            },
+            ForeignKeyDef {
+                columns:           vec!["manager".to_string()],
+                reference:         String::new(),
+                reference_columns: vec!["manager".to_string()],
+            },
+        ]
+    );
+
+    assert!(json_schema_key_constraints(&json!({"uniqueKey": []})).is_err());
+    assert!(json_schema_key_constraints(&json!({"foreignKey": {"columns": "a"}})).is_err());
+}
+
+#[test]
+fn test_field_fixer() {
+    let schema = json!({
+        "properties": {
+            "amount": {"type": ["number", "null"]},
+            "count": {"type": "integer"},
+            "active": {"type": "boolean"},
+            "born": {"type": "string", "format": "date"},
+            "color": {"type": "string", "enum": ["Red", "Green"]},
+            "name": {"type": "string"}
+        }
+    });
+    let headers = ByteRecord::from(vec!["amount", "count", "active", "born", "color", "name"]);
+    let fixers = get_field_fixers(&headers, &schema, false);
+
+    assert_eq!(fixers[0].fix("$1,234.50"), Some("1234.50".to_string()));
+    assert_eq!(fixers[0].fix("(5.5)"), Some("-5.5".to_string()));
+    assert_eq!(fixers[0].fix("-USD 10"), Some("-10".to_string()));
+    assert_eq!(fixers[0].fix("12.5"), None);
+    assert_eq!(fixers[0].fix("abc"), None);
+    assert_eq!(fixers[1].fix("1,000.00"), Some("1000".to_string()));
+    assert_eq!(fixers[1].fix("1.5"), None);
+    assert_eq!(fixers[2].fix("Yes"), Some("true".to_string()));
+    assert_eq!(fixers[2].fix(" N "), Some("false".to_string()));
+    assert_eq!(fixers[3].fix("03/04/2024"), Some("2024-03-04".to_string()));
+    assert_eq!(fixers[4].fix("GREEN"), Some("Green".to_string()));
+    assert_eq!(fixers[4].fix("Blue"), None);
+    assert_eq!(fixers[5].fix("  padded "), Some("padded".to_string()));
+}
+
+#[test]
+fn test_checksums() {
+    assert!(luhn_checksum("4111 1111 1111 1111"));
+    assert!(luhn_checksum("79927398713"));
+    assert!(!luhn_checksum("79927398710"));
+    assert!(!luhn_checksum("7992739871a"));
+
+    // LEI & ISO 7064 examples
+    assert!(iso7064_mod97_checksum("5493001KJTIIGC8Y1R12"));
+    assert!(!iso7064_mod97_checksum("5493001KJTIIGC8Y1R13"));
+
+    assert!(isbn_checksum("0-306-40615-2"));
+    assert!(isbn_checksum("080442957X"));
+    assert!(isbn_checksum("978-0-306-40615-7"));
+    assert!(!isbn_checksum("978-0-306-40615-6"));
+    assert!(!isbn_checksum("12345"));
+}
@@ -1,16 +1,27 @@
+#[cfg(feature = "compression")]
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
 use std::{
     env, fs,
-    io::{self, Read},
+    io::{self, BufRead, Read},
     path::{Path, PathBuf},
-    sync::atomic::{AtomicBool, Ordering},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use log::{debug, info, warn};
 use qsv_sniffer::{SampleSize, Sniffer};
 use serde::de::{Deserialize, Deserializer, Error};
 
+#[cfg(feature = "compression")]
+use crate::seekable::{BlockFormat, BlockMap};
 use crate::{
     index::Indexed,
+    seekable::{self, BlockReader, InputFile},
     select::{SelectColumns, Selection},
     util, CliResult,
 };
@@ -29,6 +40,11 @@ const NO_INDEX_WARNING_FILESIZE: u64 = 100_000_000; // 100MB
 // so we don't have to keep checking if the index has been created
 static AUTO_INDEXED: AtomicBool = AtomicBool::new(false);
 
+// the block maps of block-compressed inputs, by path. Commands open the input once
+// per job, so this saves rescanning the block headers every time.
+#[cfg(feature = "compression")]
+static BLOCK_MAPS: OnceLock<Mutex<HashMap<PathBuf, Option<Arc<BlockMap>>>>> = OnceLock::new();
+
 pub static SPONSOR_MESSAGE: &str = r#"sponsored by datHere - Data Infrastructure Engineering (https://qsv.datHere.com)
 Need a UI & more advanced data-wrangling? Upgrade to qsv pro (https://qsvpro.datHere.com)
 "#;
@@ -74,6 +90,185 @@ impl<'de> Deserialize<'de> for Delimiter {
     }
 }
 
+/// Compression represents the compression codecs `Config` can transparently
+/// decode on input and, for some of them, encode on output.
+///
+/// It is determined by the file extension (e.g. `.csv.gz`), or by the magic
+/// bytes at the start of the stream when reading from <stdin>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Snappy,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Returns the compression indicated by the extension of a lowercased path.
+    // we already lowercased the path_str, so allow this false positive lint
+    #[allow(clippy::case_sensitive_file_extension_comparisons)]
+    pub fn from_extension(path_str: &str) -> Compression {
+        if path_str.ends_with(".sz") {
+            Compression::Snappy
+        } else if path_str.ends_with(".gz") {
+            Compression::Gzip
+        } else if path_str.ends_with(".zst") {
+            Compression::Zstd
+        } else if path_str.ends_with(".bz2") {
+            Compression::Bzip2
+        } else if path_str.ends_with(".xz") {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns the compression indicated by the first bytes of a stream.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&[0xff, 0x06, 0x00, 0x00, b's', b'N', b'a', b'P', b'p', b'Y']) {
+            Compression::Snappy
+        } else if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if bytes.starts_with(b"BZh")
+            && bytes.get(3).is_some_and(|b| (b'1'..=b'9').contains(b))
+        {
+            // the block size digit keeps a CSV starting with "BZh" from being mistaken for bzip2
+            Compression::Bzip2
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    /// The file extension of the compression, including the leading dot.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Snappy => ".sz",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+            Compression::Bzip2 => ".bz2",
+            Compression::Xz => ".xz",
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Compression::None => "uncompressed",
+            Compression::Snappy => "snappy",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    pub const fn is_compressed(self) -> bool {
+        !matches!(self, Compression::None)
+    }
+
+    /// Wraps a reader with the decoder for this compression.
+    pub fn decoder<R: Read + Send + 'static>(
+        self,
+        rdr: R,
+    ) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+        Ok(match self {
+            Compression::None => Box::new(rdr),
+            Compression::Snappy => Box::new(snap::read::FrameDecoder::new(rdr)),
+            #[cfg(feature = "compression")]
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(rdr)),
+            #[cfg(feature = "compression")]
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(rdr)?),
+            #[cfg(feature = "compression")]
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(rdr)),
+            #[cfg(feature = "compression")]
+            Compression::Xz => Box::new(liblzma::read::XzDecoder::new_multi_decoder(rdr)),
+            #[cfg(not(feature = "compression"))]
+            Compression::Gzip | Compression::Zstd | Compression::Bzip2 | Compression::Xz => {
+                return Err(self.not_compiled_in());
+            },
+        })
+    }
+
+    /// Wraps a writer with the encoder for this compression.
+    /// Only snappy, gzip and zstd output is supported.
+    pub fn encoder<W: io::Write + 'static>(
+        self,
+        wtr: W,
+    ) -> io::Result<Box<dyn io::Write + 'static>> {
+        Ok(match self {
+            Compression::None => Box::new(wtr),
+            Compression::Snappy => Box::new(snap::write::FrameEncoder::new(wtr)),
+            #[cfg(feature = "compression")]
+            Compression::Gzip => Box::new(flate2::write::GzEncoder::new(
+                wtr,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "compression")]
+            Compression::Zstd => Box::new(zstd::stream::write::Encoder::new(wtr, 0)?.auto_finish()),
+            #[cfg(not(feature = "compression"))]
+            Compression::Gzip | Compression::Zstd => return Err(self.not_compiled_in()),
+            Compression::Bzip2 | Compression::Xz => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "Cannot write {} compressed output. Use a .gz, .zst or .sz extension \
+                         instead.",
+                        self.name()
+                    ),
+                ));
+            },
+        })
+    }
+
+    /// The error for a codec that is only available with the `compression` feature.
+    #[cfg(not(feature = "compression"))]
+    fn not_compiled_in(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} compression is not supported by this qsv binary. It requires qsv to be built \
+                 with the \"compression\" feature.",
+                self.name()
+            ),
+        )
+    }
+}
+
+/// DataFormat represents the non-CSV file formats that `Config` reads as a
+/// CSV record stream, converting them with Polars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    Parquet,
+    Ipc,
+    Jsonl,
+}
+
+impl DataFormat {
+    /// Returns the data format indicated by a lowercased file extension.
+    pub fn from_extension(file_extension: &str) -> Option<DataFormat> {
+        match file_extension {
+            "parquet" | "pqt" => Some(DataFormat::Parquet),
+            "arrow" | "ipc" | "feather" => Some(DataFormat::Ipc),
+            "jsonl" | "ndjson" => Some(DataFormat::Jsonl),
+            _ => None,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            DataFormat::Parquet => "Parquet",
+            DataFormat::Ipc => "Arrow IPC",
+            DataFormat::Jsonl => "JSONL",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub path:              Option<PathBuf>, // None implies <stdin>
@@ -93,7 +288,8 @@ pub struct Config {
     pub autoindex_size:    u64,
     prefer_dmy:            bool,
     pub comment:           Option<u8>,
-    snappy:                bool, // flag to enable snappy compression/decompression
+    compression:           Compression, // compression to decode/encode, by file extension
+    data_format:           Option<DataFormat>, // non-CSV input to read as CSV, by file extension
     pub read_buffer:       u32,
     pub write_buffer:      u32,
     pub skip_format_check: bool,
@@ -121,7 +317,10 @@ impl Config {
     ///
     /// This function initializes a `Config` with the following behavior:
     /// - Uses env var `QSV_DEFAULT_DELIMITER` for default delimiter, or ',' if not set
-    /// - Determines delimiter and Snappy compression based on file extension.
+    /// - Determines delimiter and compression (snappy, gzip, zstd, bzip2, xz) based on file
+    ///   extension.
+    /// - Recognizes Parquet (.parquet), Arrow IPC (.arrow/.ipc) and JSONL (.jsonl) inputs, which
+    ///   are read as a CSV record stream.
     /// - Supports sniffing delimiter and preamble rows if `QSV_SNIFF_DELIMITER` or
     ///   `QSV_SNIFF_PREAMBLE` is set.
     /// - Sets comment character from `QSV_COMMENT_CHAR` environment variable.
@@ -149,8 +348,8 @@ impl Config {
             || util::get_envvar_flag("QSV_SNIFF_PREAMBLE");
         let mut skip_format_check = true;
         let mut format_error = None;
-        let (path, mut delim, snappy) = match path {
-            None => (None, default_delim, false),
+        let (path, mut delim, compression, data_format) = match path {
+            None => (None, default_delim, Compression::None, None),
             // WIP: support remote files; currently only http(s) is supported
             // Some(ref s) if s.starts_with("http") && Url::parse(s).is_ok() => {
             //     let mut snappy = false;
@@ -167,14 +366,21 @@ impl Config {
             //     util::download_file()
             //     (Some(PathBuf::from(s)), delim, snappy)
             // },
-            Some(s) if s == "-" => (None, default_delim, false),
+            Some(s) if s == "-" => (None, default_delim, Compression::None, None),
             Some(ref s) => {
                 let path = PathBuf::from(s);
-                let (file_extension, delim, snappy) = get_delim_by_extension(&path, default_delim);
+                let (file_extension, delim, compression) =
+                    get_delim_by_extension(&path, default_delim);
+                let data_format = if compression.is_compressed() {
+                    None
+                } else {
+                    DataFormat::from_extension(&file_extension)
+                };
                 skip_format_check = sniff || util::get_envvar_flag("QSV_SKIP_FORMAT_CHECK");
                 if !skip_format_check {
                     format_error = match file_extension.as_str() {
                         "csv" | "tsv" | "tab" | "ssv" => None,
+                        _ if data_format.is_some() => None,
                         ext => Some(format!(
                             "{} is using an unsupported file format: {ext}. Set \
                              QSV_SKIP_FORMAT_CHECK to skip input format checking.",
@@ -182,7 +388,7 @@ impl Config {
                         )),
                     };
                 }
-                (Some(path), delim, snappy || file_extension.ends_with("sz"))
+                (Some(path), delim, compression, data_format)
             },
         };
         let sniff = util::get_envvar_flag("QSV_SNIFF_DELIMITER")
@@ -193,7 +399,7 @@ impl Config {
         };
         let no_headers = util::get_envvar_flag("QSV_NO_HEADERS");
         let mut preamble = 0_u64;
-        if sniff && path.is_some() {
+        if sniff && path.is_some() && data_format.is_none() {
             let sniff_path = path.as_ref().unwrap().to_str().unwrap();
 
             match Sniffer::new()
@@ -237,7 +443,8 @@ impl Config {
                 .unwrap_or(0),
             prefer_dmy: util::get_envvar_flag("QSV_PREFER_DMY"),
             comment,
-            snappy,
+            compression,
+            data_format,
             read_buffer: std::env::var("QSV_RDR_BUFFER_CAPACITY")
                 .unwrap_or_else(|_| DEFAULT_RDR_BUFFER_CAPACITY.to_string())
                 .parse()
@@ -356,9 +563,83 @@ impl Config {
         self.path.is_none()
     }
 
-    #[cfg(feature = "polars")]
-    pub const fn is_snappy(&self) -> bool {
-        self.snappy
+    pub const fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    pub const fn data_format(&self) -> Option<DataFormat> {
+        self.data_format
+    }
+
+    /// Returns an error if the input is compressed or not a CSV, for commands that need
+    /// to seek into the file.
+    fn check_seekable(&self) -> io::Result<()> {
+        match self.path {
+            Some(ref p) if self.data_format.is_some() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is a {} file, but this command requires a seekable CSV file. Convert it \
+                     to CSV first.",
+                    p.display(),
+                    self.data_format.unwrap().name()
+                ),
+            )),
+            Some(ref p) if self.compression.is_compressed() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is {} compressed, but this command requires a seekable file. Decompress \
+                     it first.",
+                    p.display(),
+                    self.compression.name()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a reader of the decompressed data that supports random access, if the
+    /// input is a block-compressed file (BGZF or the zstd seekable format).
+    /// The block map is only read once per input.
+    #[cfg(feature = "compression")]
+    pub fn block_reader(&self) -> io::Result<Option<BlockReader<fs::File>>> {
+        let format = match self.compression {
+            Compression::Gzip => BlockFormat::Bgzf,
+            Compression::Zstd => BlockFormat::Zstd,
+            _ => return Ok(None),
+        };
+        let Some(ref p) = self.path else {
+            return Ok(None);
+        };
+        let mut file = fs::File::open(p)?;
+        let block_maps = BLOCK_MAPS.get_or_init(|| Mutex::new(HashMap::new()));
+        let cached = block_maps
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(p)
+            .cloned();
+        let block_map = if let Some(block_map) = cached {
+            block_map
+        } else {
+            let block_map = BlockMap::read(&mut file, format)?.map(Arc::new);
+            block_maps
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .insert(p.clone(), block_map.clone());
+            block_map
+        };
+        Ok(block_map.map(|map| BlockReader::new(file, map)))
+    }
+
+    /// Without the `compression` feature, gzip & zstd files can't be decoded, block-compressed
+    /// or not.
+    #[cfg(not(feature = "compression"))]
+    pub fn block_reader(&self) -> io::Result<Option<BlockReader<fs::File>>> {
+        match self.compression {
+            Compression::Gzip | Compression::Zstd if self.path.is_some() => {
+                Err(self.compression.not_compiled_in())
+            },
+            _ => Ok(None),
+        }
     }
 
     #[inline]
@@ -426,7 +707,9 @@ impl Config {
         }
     }
 
-    pub fn reader_file(&self) -> io::Result<csv::Reader<fs::File>> {
+    /// Returns a reader of the input file that can seek. Block-compressed files are
+    /// decompressed block by block, other compressed & non-CSV files are rejected.
+    pub fn reader_file(&self) -> io::Result<csv::Reader<InputFile>> {
         match self.path {
             None => Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -438,8 +721,11 @@ impl Config {
                         io::ErrorKind::InvalidInput,
                         self.format_error.clone().unwrap(),
                     ))
+                } else if let Some(block_rdr) = self.block_reader()? {
+                    Ok(self.from_reader(InputFile::Blocks(block_rdr)))
                 } else {
-                    fs::File::open(p).map(|f| self.from_reader(f))
+                    self.check_seekable()?;
+                    fs::File::open(p).map(|f| self.from_reader(InputFile::Plain(f)))
                 }
             },
         }
@@ -449,10 +735,9 @@ impl Config {
     pub fn reader_file_stdin(&self) -> io::Result<csv::Reader<Box<dyn SeekRead + 'static>>> {
         Ok(match self.path {
             None => {
-                // Create a buffer in memory for stdin
+                // Create a buffer in memory for stdin, decompressing it if need be
                 let mut buffer: Vec<u8> = Vec::new();
-                let stdin = io::stdin();
-                stdin.lock().read_to_end(&mut buffer)?;
+                self.io_reader()?.read_to_end(&mut buffer)?;
                 self.from_reader(Box::new(io::Cursor::new(buffer)))
             },
             Some(ref p) => {
@@ -462,7 +747,19 @@ impl Config {
                         self.format_error.clone().unwrap(),
                     ));
                 }
-                self.from_reader(Box::new(fs::File::open(p)?))
+                if let Some(block_rdr) = self.block_reader()? {
+                    // block-compressed files can seek without decompressing everything
+                    self.from_reader(Box::new(block_rdr))
+                } else if self.compression.is_compressed() || self.data_format.is_some() {
+                    // compressed & non-CSV files are not seekable, so they're decompressed
+                    // or converted to an anonymous temp file, rather than into memory
+                    let mut tmpfile = tempfile::tempfile()?;
+                    io::copy(&mut self.io_reader()?, &mut tmpfile)?;
+                    io::Seek::rewind(&mut tmpfile)?;
+                    self.from_reader(Box::new(tmpfile))
+                } else {
+                    self.from_reader(Box::new(fs::File::open(p)?))
+                }
             },
         })
     }
@@ -475,10 +772,12 @@ impl Config {
     ///
     /// # Behavior
     ///
-    /// - If the file is Snappy-compressed, the function returns immediately w/o creating an index.
+    /// - If the file is compressed, but not block-compressed, the function returns immediately w/o
+    ///   creating an index.
     /// - If `self.path` is `None`, the function returns without action.
     /// - The function creates an index file using `util::idx_path()` to determine index file path.
-    /// - It uses `csv_index::RandomAccessSimple::create()` to generate the index.
+    /// - It uses `csv_index::RandomAccessSimple::create()` to generate the index, or
+    ///   `seekable::create_index()` for block-compressed files.
     /// - If index creation is successful, it sets the `AUTO_INDEXED` atomic flag to `true`.
     ///
     /// # Errors
@@ -486,21 +785,35 @@ impl Config {
     /// While this function doesn't return any errors, it logs debug messages for both successful
     /// and failed index creation attempts.
     fn autoindex_file(&self) {
-        if self.snappy {
+        let Some(path_buf) = &self.path else { return };
+        if self.data_format.is_some() {
             return;
         }
 
-        let Some(path_buf) = &self.path else { return };
+        // compressed files can only be indexed if they're block-compressed
+        let block_rdr = if self.compression.is_compressed() {
+            let Ok(Some(block_rdr)) = self.block_reader() else {
+                return;
+            };
+            Some(block_rdr)
+        } else {
+            None
+        };
 
         let pidx = util::idx_path(Path::new(path_buf));
         let Ok(idxfile) = fs::File::create(pidx) else {
             return;
         };
-        let Ok(mut rdr) = self.reader_file() else {
-            return;
-        };
         let mut wtr = io::BufWriter::with_capacity(DEFAULT_WTR_BUFFER_CAPACITY, idxfile);
-        match csv_index::RandomAccessSimple::create(&mut rdr, &mut wtr) {
+        let created = if let Some(block_rdr) = block_rdr {
+            seekable::create_index(&mut self.from_reader(block_rdr), &mut wtr)
+        } else {
+            let Ok(mut rdr) = self.reader_file() else {
+                return;
+            };
+            csv_index::RandomAccessSimple::create(&mut rdr, &mut wtr).map_err(Into::into)
+        };
+        match created {
             Ok(()) => {
                 let Ok(()) = io::Write::flush(&mut wtr) else {
                     return;
@@ -516,11 +829,44 @@ impl Config {
     /// If so, return the CSV file handle and the index file handle. If not, return None.
     /// Unless the CSV's file size >= QSV_AUTOINDEX_SIZE, then we'll create an index automatically.
     /// This will also automatically update stale indices (i.e. the CSV is newer than the index )
-    pub fn index_files(&self) -> io::Result<Option<(csv::Reader<fs::File>, fs::File)>> {
+    pub fn index_files(&self) -> io::Result<Option<(csv::Reader<InputFile>, fs::File)>> {
         let mut data_modified = 0_u64;
         let data_fsize;
         let mut idx_path_work = PathBuf::new();
 
+        // non-CSV inputs are converted on the fly, so they cannot be indexed
+        if self.data_format.is_some() {
+            if self.idx_path.is_some() {
+                self.check_seekable()?;
+            }
+            return Ok(None);
+        }
+
+        // compressed files cannot be indexed, so we fall back to reading them sequentially,
+        // unless an index was explicitly requested. Block-compressed files are the exception,
+        // as their index records virtual offsets into the blocks.
+        let block_map = if self.compression.is_compressed() {
+            if let Some(block_rdr) = self.block_reader()? {
+                Some(block_rdr.block_map())
+            } else {
+                if self.idx_path.is_some() {
+                    self.check_seekable()?;
+                }
+                return Ok(None);
+            }
+        } else {
+            None
+        };
+        let open_csv_file = |p: &Path| -> io::Result<InputFile> {
+            let file = fs::File::open(p)?;
+            Ok(match block_map {
+                Some(ref block_map) => {
+                    InputFile::Blocks(BlockReader::new(file, Arc::clone(block_map)))
+                },
+                None => InputFile::Plain(file),
+            })
+        };
+
         // the auto_indexed flag is set when an index is created automatically with
         // autoindex_file(). We use this flag to avoid checking if the index exists every
         // time this function is called. If the index was already auto-indexed, we can just
@@ -529,7 +875,7 @@ impl Config {
 
         let (csv_file, mut idx_file) = if auto_indexed {
             (
-                fs::File::open(self.path.clone().unwrap())?,
+                open_csv_file(&self.path.clone().unwrap())?,
                 fs::File::open(util::idx_path(&self.path.clone().unwrap()))?,
             )
         } else {
@@ -541,7 +887,7 @@ impl Config {
                         "Cannot use <stdin> with indexes",
                     ));
                 },
-                (Some(p), Some(ip)) => (fs::File::open(p)?, fs::File::open(ip)?),
+                (Some(p), Some(ip)) => (open_csv_file(p)?, fs::File::open(ip)?),
                 (Some(p), &None) => {
                     // We generally don't want to report an error here, since we're
                     // passively trying to find an index.
@@ -551,12 +897,9 @@ impl Config {
                     let idx_file = match fs::File::open(&idx_path_work) {
                         Err(_) => {
                             // the index file doesn't exist
-                            if self.snappy {
-                                // cannot index snappy compressed files
-                                return Ok(None);
-                            } else if self.autoindex_size > 0 && data_fsize >= self.autoindex_size {
-                                // if CSV file size >= QSV_AUTOINDEX_SIZE, and
-                                // its not a snappy file, create an index automatically
+                            if self.autoindex_size > 0 && data_fsize >= self.autoindex_size {
+                                // if CSV file size >= QSV_AUTOINDEX_SIZE,
+                                // create an index automatically
                                 self.autoindex_file();
                                 fs::File::open(&idx_path_work)?
                             } else if data_fsize >= NO_INDEX_WARNING_FILESIZE {
@@ -579,7 +922,7 @@ impl Config {
                         },
                         Ok(f) => f,
                     };
-                    (fs::File::open(p)?, idx_file)
+                    (open_csv_file(p)?, idx_file)
                 },
             }
         };
@@ -602,24 +945,48 @@ impl Config {
     /// Unless QSV_AUTOINDEX is set, in which case, we'll recreate the
     /// stale index automatically
     #[inline]
-    pub fn indexed(&self) -> CliResult<Option<Indexed<fs::File, fs::File>>> {
+    pub fn indexed(&self) -> CliResult<Option<Indexed<InputFile, fs::File>>> {
         match self.index_files()? {
             None => Ok(None),
-            Some((r, i)) => Ok(Some(Indexed::open(r, i)?)),
+            Some((r, i)) => {
+                let block_map = r.get_ref().block_map();
+                Ok(Some(Indexed::open(r, i)?.block_map(block_map)))
+            },
         }
     }
 
     pub fn io_reader(&self) -> io::Result<Box<dyn io::Read + Send + 'static>> {
         Ok(match self.path {
-            None => Box::new(io::stdin()),
+            None => {
+                // peek at the start of stdin to detect compressed input
+                let mut stdin =
+                    io::BufReader::with_capacity(self.read_buffer as usize, io::stdin());
+                let compression = Compression::from_magic_bytes(stdin.fill_buf()?);
+                if compression.is_compressed() {
+                    info!("decoding {}-compressed stdin", compression.name());
+                }
+                compression.decoder(stdin)?
+            },
+            Some(ref p) if self.data_format.is_some() => {
+                // safety: we just checked that data_format is Some
+                let data_format = self.data_format.unwrap();
+                info!(
+                    "reading {} file as CSV: {}",
+                    data_format.name(),
+                    p.display()
+                );
+                data_format_reader(p, data_format, self.delimiter)?
+            },
             Some(ref p) => match fs::File::open(p) {
                 Ok(x) => {
-                    if self.snappy {
-                        info!("decoding snappy-compressed file: {}", p.display());
-                        Box::new(snap::read::FrameDecoder::new(x))
-                    } else {
-                        Box::new(x)
+                    if self.compression.is_compressed() {
+                        info!(
+                            "decoding {}-compressed file: {}",
+                            self.compression.name(),
+                            p.display()
+                        );
                     }
+                    self.compression.decoder(x)?
                 },
                 Err(err) => {
                     let msg = format!("failed to open {}: {}", p.display(), err);
@@ -652,11 +1019,11 @@ impl Config {
                 if p_str == "sink" {
                     // sink is /dev/null
                     Box::new(io::sink())
-                } else if self.snappy {
-                    info!("writing snappy-compressed file: {p:?}");
-                    Box::new(snap::write::FrameEncoder::new(fs::File::create(p)?))
                 } else {
-                    Box::new(fs::File::create(p)?)
+                    if self.compression.is_compressed() {
+                        info!("writing {}-compressed file: {p:?}", self.compression.name());
+                    }
+                    self.compression.encoder(fs::File::create(p)?)?
                 }
             },
         })
@@ -681,6 +1048,198 @@ impl Config {
     }
 }
 
+/// Reads a Parquet, Arrow IPC or JSONL file as a CSV record stream.
+///
+/// Polars converts the file to CSV on a separate thread, a batch of rows (or an Arrow IPC
+/// record batch) at a time, sending the CSV data over a bounded channel. So only one batch
+/// of rows and a few chunks of CSV are in memory, regardless of the size of the file.
+#[cfg(feature = "polars")]
+fn data_format_reader(
+    path: &Path,
+    data_format: DataFormat,
+    delimiter: u8,
+) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+    use std::sync::mpsc;
+
+    use polars::{
+        export::arrow::io::ipc::read::{read_file_metadata, FileReader},
+        prelude::{
+            CsvWriter, DataFrame, IdxSize, JsonLineReader, LazyFileListReader, LazyFrame,
+            LazyJsonLineReader, PolarsResult, ScanArgsIpc, ScanArgsParquet, SerReader, SerWriter,
+        },
+    };
+
+    // the number of rows converted at a time
+    const BATCH_ROWS: usize = 65_536;
+
+    struct ChannelWriter(mpsc::SyncSender<io::Result<Vec<u8>>>);
+
+    impl io::Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            // the send only fails if the reader was dropped, e.g. `slice` stopped reading early
+            self.0
+                .send(Ok(buf.to_vec()))
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct ChannelReader {
+        rx:    mpsc::Receiver<io::Result<Vec<u8>>>,
+        chunk: Vec<u8>,
+        pos:   usize,
+    }
+
+    impl io::Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            while self.pos >= self.chunk.len() {
+                match self.rx.recv() {
+                    Ok(Ok(chunk)) => {
+                        self.chunk = chunk;
+                        self.pos = 0;
+                    },
+                    Ok(Err(e)) => return Err(e),
+                    // the conversion is done
+                    Err(_) => return Ok(0),
+                }
+            }
+            let n = (&self.chunk[self.pos..]).read(buf)?;
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    fn write_batch(
+        wtr: &mut ChannelWriter,
+        df: &mut DataFrame,
+        include_header: bool,
+        delimiter: u8,
+    ) -> PolarsResult<()> {
+        CsvWriter::new(wtr)
+            .include_header(include_header)
+            .with_separator(delimiter)
+            .finish(df)
+    }
+
+    if !path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("failed to open {}: file not found", path.display()),
+        ));
+    }
+
+    let (tx, rx) = mpsc::sync_channel(4);
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        let convert = || -> PolarsResult<()> {
+            let mut wtr = ChannelWriter(tx.clone());
+            if data_format == DataFormat::Jsonl {
+                // JSONL can't seek to a row, so the lines are parsed a batch at a time.
+                // The schema is inferred from all the lines, so no column is dropped
+                // because it's missing from the first lines
+                let schema = LazyJsonLineReader::new(&path)
+                    .with_infer_schema_length(None)
+                    .finish()?
+                    .collect_schema()?;
+                let mut lines = io::BufReader::new(fs::File::open(&path)?);
+                let mut batch: Vec<u8> = Vec::new();
+                let mut include_header = true;
+                loop {
+                    batch.clear();
+                    let mut rows = 0;
+                    while rows < BATCH_ROWS && lines.read_until(b'\n', &mut batch)? > 0 {
+                        rows += 1;
+                    }
+                    if rows == 0 {
+                        if include_header {
+                            let mut df = DataFrame::empty_with_schema(&schema);
+                            write_batch(&mut wtr, &mut df, true, delimiter)?;
+                        }
+                        return Ok(());
+                    }
+                    let mut df = JsonLineReader::new(io::Cursor::new(batch.as_slice()))
+                        .with_schema(schema.clone())
+                        .finish()?;
+                    write_batch(&mut wtr, &mut df, include_header, delimiter)?;
+                    include_header = false;
+                }
+            }
+
+            if data_format == DataFormat::Ipc {
+                // the record batches of the file are read one at a time
+                let mut file = fs::File::open(&path)?;
+                let metadata = read_file_metadata(&mut file)?;
+                let schema = metadata.schema.clone();
+                let mut include_header = true;
+                for batch in FileReader::new(file, metadata, None, None) {
+                    let mut df = DataFrame::try_from((batch?, schema.as_ref()))?;
+                    write_batch(&mut wtr, &mut df, include_header, delimiter)?;
+                    include_header = false;
+                }
+                // an empty file is still written, for its header
+                if include_header {
+                    let mut df = LazyFrame::scan_ipc(&path, ScanArgsIpc::default())?
+                        .limit(0)
+                        .collect()?;
+                    write_batch(&mut wtr, &mut df, true, delimiter)?;
+                }
+                return Ok(());
+            }
+
+            // the slices are pushed down to the scan, so only the row groups
+            // of each slice are read
+            let lf = LazyFrame::scan_parquet(&path, ScanArgsParquet::default())?;
+            let mut offset = 0_i64;
+            loop {
+                let mut df = lf.clone().slice(offset, BATCH_ROWS as IdxSize).collect()?;
+                let rows = df.height();
+                // an empty file is still written, for its header
+                if rows > 0 || offset == 0 {
+                    write_batch(&mut wtr, &mut df, offset == 0, delimiter)?;
+                }
+                if rows < BATCH_ROWS {
+                    return Ok(());
+                }
+                offset += rows as i64;
+            }
+        };
+        if let Err(e) = convert() {
+            let _ = tx.send(Err(io::Error::other(format!(
+                "cannot read {} as {}: {e}",
+                path.display(),
+                data_format.name()
+            ))));
+        }
+    });
+
+    Ok(Box::new(ChannelReader {
+        rx,
+        chunk: Vec::new(),
+        pos: 0,
+    }))
+}
+
+#[cfg(not(feature = "polars"))]
+fn data_format_reader(
+    path: &Path,
+    data_format: DataFormat,
+    _delimiter: u8,
+) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "Reading {} as CSV requires the polars feature, which this qsv binary was not built \
+             with. Convert {} to CSV first.",
+            data_format.name(),
+            path.display()
+        ),
+    ))
+}
+
 /// Determines the delimiter and compression status based on the file extension.
 ///
 /// # Arguments
@@ -693,27 +1252,27 @@ impl Config {
 /// A tuple containing:
 /// * `String` - The lowercase file extension.
 /// * `u8` - The determined delimiter.
-/// * `bool` - Whether the file is Snappy-compressed.
+/// * `Compression` - The compression of the file.
 ///
 /// # Details
 ///
 /// This function examines the file extension to determine:
 /// 1. The appropriate delimiter (tab for .tsv/.tab, semicolon for .ssv, comma for .csv).
-/// 2. Whether the file is Snappy-compressed (indicated by a .sz extension).
-/// 3. For Snappy-compressed files, it checks the extension before .sz to determine the delimiter.
+/// 2. Whether the file is compressed (indicated by a .sz, .gz, .zst, .bz2 or .xz extension).
+/// 3. For compressed files, it checks the extension before the compression extension to determine
+///    the delimiter.
 ///
 /// If the file extension doesn't match known types, it returns the default delimiter.
-pub fn get_delim_by_extension(path: &Path, default_delim: u8) -> (String, u8, bool) {
+pub fn get_delim_by_extension(path: &Path, default_delim: u8) -> (String, u8, Compression) {
     let path_str = path.to_str().unwrap_or_default().to_ascii_lowercase();
 
-    // we already lowercased the path_str, so allow this false positive lint
-    #[allow(clippy::case_sensitive_file_extension_comparisons)]
-    let snappy = path_str.ends_with(".sz");
+    let compression = Compression::from_extension(&path_str);
 
-    // Get the extension before .sz if it's a snappy file, otherwise get the normal extension
-    let file_extension = if snappy {
+    // Get the extension before the compression extension if it's a compressed file,
+    // otherwise get the normal extension
+    let file_extension = if compression.is_compressed() {
         path_str
-            .strip_suffix(".sz")
+            .strip_suffix(compression.extension())
             .and_then(|s| s.split('.').last())
             .unwrap_or("")
             .to_string()
@@ -732,7 +1291,7 @@ pub fn get_delim_by_extension(path: &Path, default_delim: u8) -> (String, u8, bo
         _ => default_delim,
     };
 
-    (file_extension, delim, snappy)
+    (file_extension, delim, compression)
 }
 
 #[cfg(test)]
@@ -744,65 +1303,124 @@ mod tests {
     #[test]
     fn test_csv_extension() {
         let path = PathBuf::from("test.csv");
-        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
         assert_eq!(ext, "csv");
         assert_eq!(delim, b',');
-        assert!(!snappy);
+        assert_eq!(compression, Compression::None);
     }
 
     #[test]
     fn test_tsv_extension() {
         let path = PathBuf::from("test.tsv");
-        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
         assert_eq!(ext, "tsv");
         assert_eq!(delim, b'\t');
-        assert!(!snappy);
+        assert_eq!(compression, Compression::None);
     }
 
     #[test]
     fn test_ssv_extension() {
         let path = PathBuf::from("test.ssv");
-        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
         assert_eq!(ext, "ssv");
         assert_eq!(delim, b';');
-        assert!(!snappy);
+        assert_eq!(compression, Compression::None);
     }
 
     #[test]
     fn test_snappy_csv_extension() {
         let path = PathBuf::from("test.csv.sz");
-        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
         assert_eq!(ext, "csv");
         assert_eq!(delim, b',');
-        assert!(snappy);
+        assert_eq!(compression, Compression::Snappy);
     }
 
     #[test]
     fn test_snappy_tsv_extension() {
         let path = PathBuf::from("test.tsv.sz");
-        let (ext, delim, snappy) = get_delim_by_extension(&path, b',');
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
         assert_eq!(ext, "tsv");
         assert_eq!(delim, b'\t');
-        assert!(snappy);
+        assert_eq!(compression, Compression::Snappy);
+    }
+
+    #[test]
+    fn test_gzip_csv_extension() {
+        let path = PathBuf::from("test.csv.gz");
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
+        assert_eq!(ext, "csv");
+        assert_eq!(delim, b',');
+        assert_eq!(compression, Compression::Gzip);
+    }
+
+    #[test]
+    fn test_zstd_ssv_extension() {
+        let path = PathBuf::from("test.SSV.ZST");
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
+        assert_eq!(ext, "ssv");
+        assert_eq!(delim, b';');
+        assert_eq!(compression, Compression::Zstd);
+    }
+
+    #[test]
+    fn test_bzip2_xz_tsv_extension() {
+        let path = PathBuf::from("test.tab.bz2");
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
+        assert_eq!(ext, "tab");
+        assert_eq!(delim, b'\t');
+        assert_eq!(compression, Compression::Bzip2);
+
+        let path = PathBuf::from("test.tsv.xz");
+        let (ext, delim, compression) = get_delim_by_extension(&path, b',');
+        assert_eq!(ext, "tsv");
+        assert_eq!(delim, b'\t');
+        assert_eq!(compression, Compression::Xz);
+    }
+
+    #[test]
+    fn test_compression_magic_bytes() {
+        assert_eq!(
+            Compression::from_magic_bytes(&[0x1f, 0x8b, 0x08, 0x00]),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_magic_bytes(&[0x28, 0xb5, 0x2f, 0xfd, 0x04]),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_magic_bytes(b"BZh91AY&SY"),
+            Compression::Bzip2
+        );
+        assert_eq!(
+            Compression::from_magic_bytes(&[0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00]),
+            Compression::Xz
+        );
+        assert_eq!(
+            Compression::from_magic_bytes(b"\xff\x06\x00\x00sNaPpY"),
+            Compression::Snappy
+        );
+        assert_eq!(Compression::from_magic_bytes(b"a,b,c\n"), Compression::None);
+        assert_eq!(Compression::from_magic_bytes(b""), Compression::None);
     }
 
     #[test]
     fn test_unknown_extension() {
         let path = PathBuf::from("test.unknown");
         let default_delim = b'|';
-        let (ext, delim, snappy) = get_delim_by_extension(&path, default_delim);
+        let (ext, delim, compression) = get_delim_by_extension(&path, default_delim);
         assert_eq!(ext, "unknown");
         assert_eq!(delim, default_delim);
-        assert!(!snappy);
+        assert_eq!(compression, Compression::None);
     }
 
     #[test]
     fn test_no_extension() {
         let path = PathBuf::from("test");
         let default_delim = b',';
-        let (ext, delim, snappy) = get_delim_by_extension(&path, default_delim);
+        let (ext, delim, compression) = get_delim_by_extension(&path, default_delim);
         assert_eq!(ext, "");
         assert_eq!(delim, default_delim);
-        assert!(!snappy);
+        assert_eq!(compression, Compression::None);
     }
 }
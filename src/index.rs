@@ -1,13 +1,15 @@
-use std::{io, ops};
+use std::{io, ops, sync::Arc};
 
 use csv_index::RandomAccessSimple;
 
-use crate::CliResult;
+use crate::{seekable::BlockMap, CliResult};
 
 /// Indexed composes a CSV reader with a simple random access index.
 pub struct Indexed<R, I> {
-    csv_rdr: csv::Reader<R>,
-    idx:     RandomAccessSimple<I>,
+    csv_rdr:   csv::Reader<R>,
+    idx:       RandomAccessSimple<I>,
+    // set for block-compressed CSVs, whose index records virtual offsets
+    block_map: Option<Arc<BlockMap>>,
 }
 
 impl<R, I> ops::Deref for Indexed<R, I> {
@@ -30,9 +32,18 @@ impl<R: io::Read + io::Seek, I: io::Read + io::Seek> Indexed<R, I> {
         Ok(Indexed {
             csv_rdr,
             idx: RandomAccessSimple::open(idx_rdr)?,
+            block_map: None,
         })
     }
 
+    /// Sets the block map used to resolve the virtual offsets in the index of a
+    /// block-compressed CSV.
+    #[must_use]
+    pub fn block_map(mut self, block_map: Option<Arc<BlockMap>>) -> Indexed<R, I> {
+        self.block_map = block_map;
+        self
+    }
+
     /// Return the number of records (not including the header record) in this
     /// index.
     #[inline]
@@ -58,7 +69,10 @@ impl<R: io::Read + io::Seek, I: io::Read + io::Seek> Indexed<R, I> {
         if self.csv_rdr.has_headers() {
             i += 1;
         }
-        let pos = self.idx.get(i)?;
+        let mut pos = self.idx.get(i)?;
+        if let Some(ref block_map) = self.block_map {
+            pos.set_byte(block_map.real_offset(pos.byte())?);
+        }
         self.csv_rdr.seek(pos)?;
         Ok(())
     }
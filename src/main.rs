@@ -59,6 +59,7 @@ mod config;
 mod index;
 mod lookup;
 mod odhtcache;
+mod seekable;
 mod select;
 mod util;
 
@@ -125,6 +126,7 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str(
         "    fill        Fill empty values
+    fixedwidth  Convert fixed-width files to/from CSV
     fixlengths  Makes all records have same length
     flatten     Show one field per line
     fmt         Format CSV output (change field delimiter)\n",
@@ -135,6 +137,9 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str("    frequency   Show frequency tables\n");
 
+    #[cfg(all(feature = "to", feature = "feature_capable"))]
+    enabled_commands.push_str("    from        Export SQLite/Postgres data or datapackages\n");
+
     #[cfg(all(feature = "geocode", not(feature = "lite")))]
     enabled_commands
         .push_str("    geocode     Geocodes a location against the Geonames cities database.\n");
@@ -152,7 +157,8 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str(
         "    json        Convert JSON to CSV
-    jsonl       Convert newline-delimited JSON files to CSV\n",
+    jsonl       Convert newline-delimited JSON files to CSV
+    keys        Find candidate keys & functional dependencies\n",
     );
 
     #[cfg(all(feature = "lens", feature = "feature_capable"))]
@@ -183,6 +189,7 @@ fn main() -> QsvExitCode {
     safenames   Modify a CSV's header names to db-safe names
     sample      Randomly sample CSV data
     schema      Generate JSON Schema from CSV data
+    schemadiff  Compare two schemas for breaking changes
     search      Search CSV data with a regex
     searchset   Search CSV data with a regex set
     select      Select, re-order, duplicate or drop columns
@@ -359,12 +366,15 @@ enum Command {
     #[cfg(all(feature = "fetch", feature = "feature_capable"))]
     FetchPost,
     Fill,
+    FixedWidth,
     FixLengths,
     Flatten,
     Fmt,
     #[cfg(all(feature = "foreach", not(feature = "lite")))]
     ForEach,
     Frequency,
+    #[cfg(all(feature = "to", feature = "feature_capable"))]
+    From,
     #[cfg(all(feature = "geocode", feature = "feature_capable"))]
     Geocode,
     Headers,
@@ -376,6 +386,7 @@ enum Command {
     JoinP,
     Json,
     Jsonl,
+    Keys,
     #[cfg(all(feature = "lens", feature = "feature_capable"))]
     Lens,
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
@@ -395,6 +406,7 @@ enum Command {
     Safenames,
     Sample,
     Schema,
+    SchemaDiff,
     Search,
     SearchSet,
     Select,
@@ -457,10 +469,13 @@ impl Command {
             #[cfg(all(feature = "foreach", not(feature = "lite")))]
             Command::ForEach => cmd::foreach::run(argv),
             Command::Fill => cmd::fill::run(argv),
+            Command::FixedWidth => cmd::fixedwidth::run(argv),
             Command::FixLengths => cmd::fixlengths::run(argv),
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
             Command::Frequency => cmd::frequency::run(argv),
+            #[cfg(all(feature = "to", feature = "feature_capable"))]
+            Command::From => cmd::from::run(argv),
             #[cfg(all(feature = "geocode", feature = "feature_capable"))]
             Command::Geocode => cmd::geocode::run(argv),
             Command::Headers => cmd::headers::run(argv),
@@ -476,6 +491,7 @@ impl Command {
             Command::JoinP => cmd::joinp::run(argv),
             Command::Json => cmd::json::run(argv),
             Command::Jsonl => cmd::jsonl::run(argv),
+            Command::Keys => cmd::keys::run(argv),
             #[cfg(all(feature = "lens", feature = "feature_capable"))]
             Command::Lens => cmd::lens::run(argv),
             #[cfg(all(feature = "luau", feature = "feature_capable"))]
@@ -495,6 +511,7 @@ impl Command {
             Command::Safenames => cmd::safenames::run(argv),
             Command::Sample => cmd::sample::run(argv),
             Command::Schema => cmd::schema::run(argv),
+            Command::SchemaDiff => cmd::schemadiff::run(argv),
             Command::Search => cmd::search::run(argv),
             Command::SearchSet => cmd::searchset::run(argv),
             Command::Select => cmd::select::run(argv),
@@ -95,6 +95,7 @@ mod config;
 mod index;
 mod lookup;
 mod odhtcache;
+mod seekable;
 mod select;
 mod util;
 
@@ -34,6 +34,7 @@ static COMMAND_LIST: &str = r#"
     extdedup    Remove duplicates rows from an arbitrarily large text file
     extsort     Sort arbitrarily large text file
     fill        Fill empty values
+    fixedwidth  Convert fixed-width files to/from CSV
     fixlengths  Makes all records have same length
     flatten     Show one field per line
     fmt         Format CSV output (change field delimiter)
@@ -45,6 +46,7 @@ static COMMAND_LIST: &str = r#"
     join        Join CSV files
     json        Convert JSON to CSV
     jsonl       Convert newline-delimited JSON files to CSV
+    keys        Find candidate keys & functional dependencies
     partition   Partition CSV data based on a column value
     pro         Interact with the qsv pro API
     pseudo      Pseudonymise the values of a column
@@ -54,6 +56,7 @@ static COMMAND_LIST: &str = r#"
     safenames   Modify a CSV's header names to db-safe names
     sample      Randomly sample CSV data
     schema      Generate JSON Schema from CSV data
+    schemadiff  Compare two schemas for breaking changes
     search      Search CSV data with a regex
     searchset   Search CSV data with a regex set
     select      Select, re-order, duplicate or drop columns
@@ -74,6 +77,7 @@ mod cmd;
 mod config;
 mod index;
 mod odhtcache;
+mod seekable;
 mod select;
 mod util;
 
@@ -240,6 +244,7 @@ enum Command {
     ExtDedup,
     ExtSort,
     Fill,
+    FixedWidth,
     FixLengths,
     Flatten,
     Fmt,
@@ -251,6 +256,7 @@ enum Command {
     Join,
     Json,
     Jsonl,
+    Keys,
     Partition,
     Pro,
     Pseudo,
@@ -260,6 +266,7 @@ enum Command {
     Safenames,
     Sample,
     Schema,
+    SchemaDiff,
     Search,
     SearchSet,
     Select,
@@ -306,6 +313,7 @@ impl Command {
             Command::ExtDedup => cmd::extdedup::run(argv),
             Command::ExtSort => cmd::extsort::run(argv),
             Command::Fill => cmd::fill::run(argv),
+            Command::FixedWidth => cmd::fixedwidth::run(argv),
             Command::FixLengths => cmd::fixlengths::run(argv),
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
@@ -321,6 +329,7 @@ impl Command {
             Command::Join => cmd::join::run(argv),
             Command::Json => cmd::json::run(argv),
             Command::Jsonl => cmd::jsonl::run(argv),
+            Command::Keys => cmd::keys::run(argv),
             Command::Partition => cmd::partition::run(argv),
             Command::Pro => cmd::pro::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
@@ -330,6 +339,7 @@ impl Command {
             Command::Safenames => cmd::safenames::run(argv),
             Command::Sample => cmd::sample::run(argv),
             Command::Schema => cmd::schema::run(argv),
+            Command::SchemaDiff => cmd::schemadiff::run(argv),
             Command::Search => cmd::search::run(argv),
             Command::SearchSet => cmd::searchset::run(argv),
             Command::Select => cmd::select::run(argv),
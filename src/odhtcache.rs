@@ -1,5 +1,10 @@
 // blatantly copied from https://github.com/race604/dedup/blob/master/src/cache.rs
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
 
 use log::debug;
 use odht::{Config, FxHashFn, HashTableOwned};
@@ -8,6 +13,10 @@ struct ExtDedupConfig;
 
 const CHUNK_SIZE: usize = 127;
 
+/// magic bytes identifying an extdedup state file
+const STATE_MAGIC: &[u8; 8] = b"QSVDEDUP";
+const STATE_VERSION: u32 = 1;
+
 impl Config for ExtDedupConfig {
     type EncodedKey = [u8; CHUNK_SIZE + 1];
     type EncodedValue = [u8; 1];
@@ -88,6 +97,127 @@ impl ExtDedupCache {
         };
     }
 
+    /// Loads a cache previously saved with `save`. `key_spec` describes how the keys
+    /// were built (e.g. the column selection), and has to match the one the state
+    /// was saved with, as keys built differently can never match.
+    /// The hash table of the state is loaded into memory, so it has to fit in
+    /// `memo_limit` bytes (0 for no limit).
+    pub fn load(path: &Path, key_spec: &str, memo_limit: u64) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut rdr = io::BufReader::new(file);
+
+        let mut magic = [0_u8; 8];
+        rdr.read_exact(&mut magic)?;
+        if &magic != STATE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not an extdedup state file.", path.display()),
+            ));
+        }
+        let mut u32_buf = [0_u8; 4];
+        rdr.read_exact(&mut u32_buf)?;
+        let version = u32::from_le_bytes(u32_buf);
+        if version != STATE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported extdedup state file version {version}."),
+            ));
+        }
+        rdr.read_exact(&mut u32_buf)?;
+        let mut saved_key_spec = vec![0_u8; u32::from_le_bytes(u32_buf) as usize];
+        rdr.read_exact(&mut saved_key_spec)?;
+        if saved_key_spec != key_spec.as_bytes() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "State file {} was built with keys \"{}\", not \"{key_spec}\".",
+                    path.display(),
+                    String::from_utf8_lossy(&saved_key_spec)
+                ),
+            ));
+        }
+
+        let header_len = (STATE_MAGIC.len() + 4 + 4 + saved_key_spec.len()) as u64;
+        let table_len = file_len.saturating_sub(header_len);
+        if memo_limit != 0 && table_len > memo_limit {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                format!(
+                    "The state's hash table needs {table_len} bytes, more than the memory limit \
+                     of {memo_limit} bytes."
+                ),
+            ));
+        }
+        let mut raw_table = Vec::with_capacity(table_len as usize);
+        rdr.read_to_end(&mut raw_table)?;
+        let disk = if raw_table.is_empty() {
+            None
+        } else {
+            match HashTableOwned::<ExtDedupConfig>::from_raw_bytes(&raw_table) {
+                Ok(table) => Some(table),
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Corrupt extdedup state file {}: {e}", path.display()),
+                    ))
+                },
+            }
+        };
+        debug!(
+            "Loaded extdedup state with {} entries",
+            disk.as_ref().map_or(0, HashTableOwned::len)
+        );
+
+        let mut cache = ExtDedupCache::new(memo_limit);
+        cache.disk = disk;
+        Ok(cache)
+    }
+
+    /// Saves the cache so it can be reloaded with `load`, with the hash table sized
+    /// to the number of entries it holds.
+    /// The file is replaced atomically, so an interrupted run leaves the
+    /// previous state intact.
+    pub fn save(&mut self, path: &Path, key_spec: &str) -> io::Result<()> {
+        self.compact();
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let tmp_file = tempfile::NamedTempFile::new_in(dir)?;
+        {
+            let mut wtr = io::BufWriter::new(tmp_file.as_file());
+            wtr.write_all(STATE_MAGIC)?;
+            wtr.write_all(&STATE_VERSION.to_le_bytes())?;
+            wtr.write_all(&(key_spec.len() as u32).to_le_bytes())?;
+            wtr.write_all(key_spec.as_bytes())?;
+            if let Some(ref disk) = self.disk {
+                wtr.write_all(disk.raw_bytes())?;
+            }
+            wtr.flush()?;
+        }
+        tmp_file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Rebuilds the on-disk hash table so its capacity matches the number of
+    /// entries it holds, as it's created with room for 1 million entries.
+    fn compact(&mut self) {
+        if !self.memo.is_empty() {
+            self.dump_to_disk();
+        }
+        if let Some(ref disk) = self.disk {
+            let mut compacted =
+                HashTableOwned::<ExtDedupConfig>::with_capacity(disk.len().max(1), 95);
+            for (key, value) in disk.iter() {
+                compacted.insert(&key, &value);
+            }
+            debug!("Compacted extdedup state to {} entries", compacted.len());
+            self.disk = Some(compacted);
+        }
+    }
+
     fn insert_on_disk(&mut self, item: &str) -> bool {
         let disk = self.disk.get_or_insert_with(|| {
             debug!("Create new disk cache");
@@ -124,6 +254,161 @@ impl ExtDedupCache {
     }
 }
 
+struct DedupGroupConfig;
+
+impl Config for DedupGroupConfig {
+    type EncodedKey = [u8; 16];
+    type EncodedValue = [u8; 32];
+    type H = FxHashFn;
+    type Key = [u8; 16];
+    type Value = DedupGroup;
+
+    #[inline]
+    fn encode_key(k: &Self::Key) -> Self::EncodedKey {
+        *k
+    }
+
+    #[inline]
+    fn encode_value(v: &Self::Value) -> Self::EncodedValue {
+        let mut encoded = [0_u8; 32];
+        encoded[..8].copy_from_slice(&v.kept_rowno.to_le_bytes());
+        encoded[8..16].copy_from_slice(&v.kept_score.to_le_bytes());
+        encoded[16..24].copy_from_slice(&v.count.to_le_bytes());
+        encoded[24..].copy_from_slice(&v.group_id.to_le_bytes());
+        encoded
+    }
+
+    #[inline]
+    fn decode_key(k: &Self::EncodedKey) -> Self::Key {
+        *k
+    }
+
+    #[inline]
+    fn decode_value(v: &Self::EncodedValue) -> Self::Value {
+        // safety: the slices are exactly 8 bytes long
+        DedupGroup {
+            kept_rowno: u64::from_le_bytes(v[..8].try_into().unwrap()),
+            kept_score: f64::from_le_bytes(v[8..16].try_into().unwrap()),
+            count:      u64::from_le_bytes(v[16..24].try_into().unwrap()),
+            group_id:   u64::from_le_bytes(v[24..].try_into().unwrap()),
+        }
+    }
+}
+
+/// A group of records sharing the same dedup key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DedupGroup {
+    /// the row number of the record that survives
+    pub kept_rowno: u64,
+    /// the score of the surviving record, as ranked by the keep policy
+    pub kept_score: f64,
+    /// the number of records in the group
+    pub count:      u64,
+    /// the id of the group, assigned once the group has a duplicate.
+    /// 0 if the key is unique.
+    pub group_id:   u64,
+}
+
+/// Tracks the surviving record of every dedup key in an odht hash table.
+/// Keys are stored as 128-bit xxh3 hashes, so memory use does not depend
+/// on the length of the keys.
+pub struct ExtDedupGroups {
+    table:         HashTableOwned<DedupGroupConfig>,
+    next_group_id: u64,
+    memo_limit:    u64,
+}
+
+/// the initial capacity of the groups table when the number of rows is unknown.
+/// The table grows as needed.
+const DEFAULT_GROUPS_CAPACITY: usize = 16_384;
+/// the bytes used by an entry of the groups table: the key, the value and
+/// one byte of odht metadata
+const GROUP_ENTRY_SIZE: u64 = 16 + 32 + 1;
+
+impl ExtDedupGroups {
+    /// Creates a table for about `capacity_hint` keys (0 if unknown) that can
+    /// use at most `memo_limit` bytes of memory (0 for no limit).
+    pub fn new(capacity_hint: usize, memo_limit: u64) -> Self {
+        let memo_limit = if memo_limit == 0 {
+            u64::MAX
+        } else {
+            memo_limit
+        };
+        // don't preallocate more than what fits in the memory limit, leaving room
+        // for odht rounding the number of slots up to a power of two
+        let max_capacity = usize::try_from(memo_limit / GROUP_ENTRY_SIZE / 4)
+            .unwrap_or(usize::MAX)
+            .max(1);
+        let capacity = if capacity_hint == 0 {
+            DEFAULT_GROUPS_CAPACITY
+        } else {
+            capacity_hint
+        };
+        Self {
+            table: HashTableOwned::<DedupGroupConfig>::with_capacity(
+                capacity.min(max_capacity),
+                95,
+            ),
+            next_group_id: 1,
+            memo_limit,
+        }
+    }
+
+    /// Records an occurrence of `item` at `rowno`. `replaces` is called with the
+    /// scores of the current survivor and the new record, and returns true if the
+    /// new record should survive instead.
+    /// Fails once the table needs more memory than the limit it was created with.
+    pub fn observe<F>(&mut self, item: &str, rowno: u64, score: f64, replaces: F) -> io::Result<()>
+    where
+        F: Fn(f64, f64) -> bool,
+    {
+        let key = ExtDedupGroups::item_to_key(item);
+        let group = match self.table.get(&key) {
+            Some(mut group) => {
+                group.count += 1;
+                if group.group_id == 0 {
+                    group.group_id = self.next_group_id;
+                    self.next_group_id += 1;
+                }
+                if replaces(group.kept_score, score) {
+                    group.kept_rowno = rowno;
+                    group.kept_score = score;
+                }
+                group
+            },
+            None => DedupGroup {
+                kept_rowno: rowno,
+                kept_score: score,
+                count:      1,
+                group_id:   0,
+            },
+        };
+        self.table.insert(&key, &group);
+
+        if self.table.raw_bytes().len() as u64 > self.memo_limit {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                format!(
+                    "The table of {} dedup keys needs more than the memory limit of {} bytes.",
+                    self.table.len(),
+                    self.memo_limit
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn get(&self, item: &str) -> Option<DedupGroup> {
+        self.table.get(&ExtDedupGroups::item_to_key(item))
+    }
+
+    #[inline]
+    fn item_to_key(item: &str) -> [u8; 16] {
+        xxhash_rust::xxh3::xxh3_128(item.as_bytes()).to_le_bytes()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -152,6 +437,62 @@ mod tests {
         assert!(cache.disk.unwrap().len() > 0);
     }
 
+    #[test]
+    fn test_save_load_state() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let state_path = state_dir.path().join("state.qsvdedup");
+
+        let mut cache = ExtDedupCache::new(0);
+        assert!(cache.insert("hello"));
+        assert!(cache.insert(&"x".repeat(300)));
+        cache.save(&state_path, "id").unwrap();
+        // the table is sized to its 4 entries, not the initial 1 million
+        assert!(fs::metadata(&state_path).unwrap().len() < 16 * 1024);
+
+        let cache = ExtDedupCache::load(&state_path, "id", 0).unwrap();
+        assert!(cache.contains("hello"));
+        assert!(cache.contains(&"x".repeat(300)));
+        assert!(!cache.contains("world"));
+
+        // a state built with different keys is rejected
+        assert!(ExtDedupCache::load(&state_path, "name", 0).is_err());
+        // as is a state that doesn't fit in the memory limit
+        let err = ExtDedupCache::load(&state_path, "id", 64).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+    }
+
+    #[test]
+    fn test_dedup_groups() {
+        let mut groups = ExtDedupGroups::new(0, 0);
+        // keep the record with the highest score
+        let replaces = |kept: f64, candidate: f64| candidate > kept;
+        groups.observe("a", 1, 1.0, replaces).unwrap();
+        groups.observe("b", 2, 5.0, replaces).unwrap();
+        groups.observe("a", 3, 3.0, replaces).unwrap();
+        groups.observe("a", 4, 2.0, replaces).unwrap();
+
+        let a = groups.get("a").unwrap();
+        assert_eq!(a.kept_rowno, 3);
+        assert_eq!(a.count, 3);
+        assert_eq!(a.group_id, 1);
+
+        let b = groups.get("b").unwrap();
+        assert_eq!(b.kept_rowno, 2);
+        assert_eq!(b.count, 1);
+        assert_eq!(b.group_id, 0);
+
+        assert!(groups.get("c").is_none());
+    }
+
+    #[test]
+    fn test_dedup_groups_memory_limit() {
+        let mut groups = ExtDedupGroups::new(0, 64 * 1024);
+        let replaces = |kept: f64, candidate: f64| candidate > kept;
+        let res = (0..100_000_u64)
+            .try_for_each(|rowno| groups.observe(&rowno.to_string(), rowno, 0.0, replaces));
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::OutOfMemory);
+    }
+
     fn rand_string(len: usize) -> String {
         thread_rng()
             .sample_iter(&Alphanumeric)
@@ -0,0 +1,521 @@
+// Config only finds block-compressed inputs with the "compression" feature,
+// as they can't be decoded without it.
+#![cfg_attr(not(feature = "compression"), allow(dead_code))]
+
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    sync::Arc,
+};
+
+use csv_index::RandomAccessSimple;
+
+use crate::CliResult;
+
+// BGZF blocks are gzip members whose header carries a "BC" extra subfield
+// with the compressed size of the block
+const BGZF_HEADER_LEN: usize = 18;
+
+// the zstd seekable format stores its seek table in a skippable frame
+// at the end of the file, ending with a 9 byte footer
+const ZSTD_SKIPPABLE_MAGIC: u32 = 0x184D_2A5E;
+const ZSTD_SEEKABLE_MAGIC: u32 = 0x8F92_EAB1;
+const ZSTD_SEEK_TABLE_FOOTER_LEN: u64 = 9;
+
+/// The block-compressed formats that support random access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockFormat {
+    /// Blocked gzip, as written by `bgzip`.
+    Bgzf,
+    /// The zstd seekable format, as written by `zstd --seekable` or `t2sz`.
+    Zstd,
+}
+
+/// A block that can be decompressed independently of the others.
+#[derive(Clone, Copy, Debug)]
+struct Block {
+    coffset: u64, // offset of the block in the compressed file
+    clen:    u64,
+    uoffset: u64, // offset of the block's data in the decompressed stream
+    ulen:    u64,
+}
+
+/// BlockMap maps the blocks of a block-compressed file to their offsets in
+/// the decompressed stream.
+///
+/// Indexes of block-compressed files record virtual offsets. For BGZF, these
+/// are the usual `compressed block offset << 16 | offset within the block`.
+/// The zstd seek table already addresses decompressed offsets, so for zstd the
+/// virtual offset is the decompressed offset.
+#[derive(Debug)]
+pub struct BlockMap {
+    format: BlockFormat,
+    blocks: Vec<Block>,
+    len:    u64,
+}
+
+impl BlockMap {
+    /// Reads the block map of a file in the given format.
+    /// Returns `None` if the file is not block-compressed (e.g. a regular gzip file).
+    pub fn read<R: Read + Seek>(rdr: &mut R, format: BlockFormat) -> io::Result<Option<BlockMap>> {
+        let blocks = match format {
+            BlockFormat::Bgzf => read_bgzf_blocks(rdr)?,
+            BlockFormat::Zstd => read_zstd_seek_table(rdr)?,
+        };
+        Ok(blocks.map(|blocks| BlockMap {
+            format,
+            len: blocks.last().map_or(0, |b| b.uoffset + b.ulen),
+            blocks,
+        }))
+    }
+
+    /// Returns the block & the offset within the block of a decompressed offset.
+    fn locate(&self, offset: u64) -> (usize, u64) {
+        // empty blocks (like the BGZF EOF marker) are skipped over
+        let i = self
+            .blocks
+            .partition_point(|b| b.uoffset + b.ulen <= offset);
+        match self.blocks.get(i) {
+            Some(block) => (i, offset - block.uoffset),
+            None => (i, offset - self.len),
+        }
+    }
+
+    /// Converts a decompressed offset to the virtual offset recorded in an index.
+    pub fn virtual_offset(&self, offset: u64) -> u64 {
+        match self.format {
+            BlockFormat::Bgzf => {
+                let (i, within) = self.locate(offset);
+                let coffset = self.blocks.get(i).map_or_else(
+                    || self.blocks.last().map_or(0, |b| b.coffset + b.clen),
+                    |b| b.coffset,
+                );
+                (coffset << 16) | within
+            },
+            BlockFormat::Zstd => offset,
+        }
+    }
+
+    /// Converts a virtual offset recorded in an index back to a decompressed offset.
+    pub fn real_offset(&self, voffset: u64) -> io::Result<u64> {
+        match self.format {
+            BlockFormat::Bgzf => {
+                let (coffset, within) = (voffset >> 16, voffset & 0xffff);
+                match self.blocks.binary_search_by_key(&coffset, |b| b.coffset) {
+                    Ok(i) if within <= self.blocks[i].ulen => Ok(self.blocks[i].uoffset + within),
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid BGZF virtual offset {voffset} in index"),
+                    )),
+                }
+            },
+            BlockFormat::Zstd => Ok(voffset),
+        }
+    }
+}
+
+/// Scans the headers of a BGZF file, without decompressing it.
+fn read_bgzf_blocks<R: Read + Seek>(rdr: &mut R) -> io::Result<Option<Vec<Block>>> {
+    let compressed_len = rdr.seek(SeekFrom::End(0))?;
+    let mut blocks = Vec::new();
+    let mut header = [0_u8; BGZF_HEADER_LEN];
+    let mut isize_bytes = [0_u8; 4];
+    let (mut coffset, mut uoffset) = (0_u64, 0_u64);
+
+    while coffset < compressed_len {
+        rdr.seek(SeekFrom::Start(coffset))?;
+        let valid_header = match rdr.read_exact(&mut header) {
+            Ok(()) => {
+                header[..4] == [0x1f, 0x8b, 0x08, 0x04]
+                    && header[10..16] == [0x06, 0x00, b'B', b'C', 0x02, 0x00]
+            },
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => false,
+            Err(e) => return Err(e),
+        };
+        if !valid_header {
+            if blocks.is_empty() {
+                // not a BGZF file
+                return Ok(None);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid BGZF block at offset {coffset}"),
+            ));
+        }
+
+        let clen = u64::from(u16::from_le_bytes([header[16], header[17]])) + 1;
+        rdr.seek(SeekFrom::Start(coffset + clen - 4))?;
+        rdr.read_exact(&mut isize_bytes)?;
+        let ulen = u64::from(u32::from_le_bytes(isize_bytes));
+
+        blocks.push(Block {
+            coffset,
+            clen,
+            uoffset,
+            ulen,
+        });
+        coffset += clen;
+        uoffset += ulen;
+    }
+
+    Ok(if blocks.is_empty() {
+        None
+    } else {
+        Some(blocks)
+    })
+}
+
+/// Reads the seek table of a zstd seekable file.
+fn read_zstd_seek_table<R: Read + Seek>(rdr: &mut R) -> io::Result<Option<Vec<Block>>> {
+    let compressed_len = rdr.seek(SeekFrom::End(0))?;
+    if compressed_len < ZSTD_SEEK_TABLE_FOOTER_LEN + 8 {
+        return Ok(None);
+    }
+
+    let mut footer = [0_u8; ZSTD_SEEK_TABLE_FOOTER_LEN as usize];
+    rdr.seek(SeekFrom::Start(compressed_len - ZSTD_SEEK_TABLE_FOOTER_LEN))?;
+    rdr.read_exact(&mut footer)?;
+    if u32::from_le_bytes([footer[5], footer[6], footer[7], footer[8]]) != ZSTD_SEEKABLE_MAGIC {
+        // a regular zstd file
+        return Ok(None);
+    }
+
+    let invalid_table = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid zstd seek table".to_string(),
+        )
+    };
+    let num_frames = u64::from(u32::from_le_bytes([
+        footer[0], footer[1], footer[2], footer[3],
+    ]));
+    // the high bit of the descriptor flags a checksum in each entry
+    let entry_len: u64 = if footer[4] & 0x80 == 0 { 8 } else { 12 };
+    let table_len = num_frames * entry_len;
+    let table_start = compressed_len
+        .checked_sub(ZSTD_SEEK_TABLE_FOOTER_LEN + table_len + 8)
+        .ok_or_else(invalid_table)?;
+
+    let mut table = vec![0_u8; (table_len + 8) as usize];
+    rdr.seek(SeekFrom::Start(table_start))?;
+    rdr.read_exact(&mut table)?;
+    let read_u32 = |pos: usize| {
+        u32::from_le_bytes([table[pos], table[pos + 1], table[pos + 2], table[pos + 3]])
+    };
+    if read_u32(0) != ZSTD_SKIPPABLE_MAGIC
+        || u64::from(read_u32(4)) != table_len + ZSTD_SEEK_TABLE_FOOTER_LEN
+    {
+        return Err(invalid_table());
+    }
+
+    let mut blocks = Vec::with_capacity(num_frames as usize);
+    let (mut coffset, mut uoffset) = (0_u64, 0_u64);
+    for entry in table[8..].chunks_exact(entry_len as usize) {
+        let clen = u64::from(u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]));
+        let ulen = u64::from(u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]));
+        blocks.push(Block {
+            coffset,
+            clen,
+            uoffset,
+            ulen,
+        });
+        coffset += clen;
+        uoffset += ulen;
+    }
+    if coffset > table_start {
+        return Err(invalid_table());
+    }
+
+    Ok(Some(blocks))
+}
+
+/// BlockReader decompresses a block-compressed file, seeking in its
+/// decompressed stream by only decompressing the block that holds the target.
+pub struct BlockReader<R> {
+    inner:      R,
+    map:        Arc<BlockMap>,
+    block:      usize, // the current block
+    block_pos:  usize, // the current position within the current block
+    loaded:     Option<usize>,
+    buf:        Vec<u8>,
+    compressed: Vec<u8>,
+}
+
+impl<R: Read + Seek> BlockReader<R> {
+    pub fn new(inner: R, map: Arc<BlockMap>) -> BlockReader<R> {
+        BlockReader {
+            inner,
+            map,
+            block: 0,
+            block_pos: 0,
+            loaded: None,
+            buf: Vec::new(),
+            compressed: Vec::new(),
+        }
+    }
+
+    pub fn block_map(&self) -> Arc<BlockMap> {
+        Arc::clone(&self.map)
+    }
+
+    fn load_block(&mut self, i: usize) -> io::Result<()> {
+        if self.loaded == Some(i) {
+            return Ok(());
+        }
+        let block = self.map.blocks[i];
+        self.inner.seek(SeekFrom::Start(block.coffset))?;
+        self.compressed.resize(block.clen as usize, 0);
+        self.inner.read_exact(&mut self.compressed)?;
+
+        self.buf.clear();
+        match self.map.format {
+            #[cfg(feature = "compression")]
+            BlockFormat::Bgzf => {
+                flate2::read::GzDecoder::new(&self.compressed[..]).read_to_end(&mut self.buf)?;
+            },
+            #[cfg(feature = "compression")]
+            BlockFormat::Zstd => {
+                zstd::stream::read::Decoder::with_buffer(&self.compressed[..])?
+                    .read_to_end(&mut self.buf)?;
+            },
+            #[cfg(not(feature = "compression"))]
+            BlockFormat::Bgzf | BlockFormat::Zstd => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "block-compressed files require qsv to be built with the \"compression\" \
+                     feature",
+                ));
+            },
+        }
+        if self.buf.len() as u64 != block.ulen {
+            self.loaded = None;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "block at offset {} decompressed to {} bytes, expected {}",
+                    block.coffset,
+                    self.buf.len(),
+                    block.ulen
+                ),
+            ));
+        }
+        self.loaded = Some(i);
+        Ok(())
+    }
+
+    fn position(&self) -> u64 {
+        self.map
+            .blocks
+            .get(self.block)
+            .map_or(self.map.len, |b| b.uoffset)
+            + self.block_pos as u64
+    }
+}
+
+impl<R: Read + Seek> Read for BlockReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.block < self.map.blocks.len() {
+            self.load_block(self.block)?;
+            if self.block_pos < self.buf.len() {
+                let n = (&self.buf[self.block_pos..]).read(out)?;
+                self.block_pos += n;
+                return Ok(n);
+            }
+            self.block += 1;
+            self.block_pos = 0;
+        }
+        Ok(0)
+    }
+}
+
+impl<R: Read + Seek> Seek for BlockReader<R> {
+    /// Seeks in the decompressed stream.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.map.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position().checked_add_signed(delta),
+        };
+        let Some(target) = target else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        };
+        let (block, within) = self.map.locate(target);
+        self.block = block;
+        self.block_pos = within as usize;
+        Ok(target)
+    }
+}
+
+/// InputFile is the data file read with an index: either a plain file,
+/// or a block-compressed one.
+pub enum InputFile {
+    Plain(fs::File),
+    Blocks(BlockReader<fs::File>),
+}
+
+impl InputFile {
+    pub fn block_map(&self) -> Option<Arc<BlockMap>> {
+        match self {
+            InputFile::Plain(_) => None,
+            InputFile::Blocks(rdr) => Some(rdr.block_map()),
+        }
+    }
+}
+
+impl Read for InputFile {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        match self {
+            InputFile::Plain(f) => f.read(out),
+            InputFile::Blocks(rdr) => rdr.read(out),
+        }
+    }
+}
+
+impl Seek for InputFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            InputFile::Plain(f) => f.seek(pos),
+            InputFile::Blocks(rdr) => rdr.seek(pos),
+        }
+    }
+}
+
+/// Creates an index of a block-compressed CSV, recording the virtual offset
+/// of each record.
+pub fn create_index<R: Read + Seek, W: Write>(
+    rdr: &mut csv::Reader<BlockReader<R>>,
+    mut wtr: W,
+) -> CliResult<()> {
+    let map = rdr.get_ref().block_map();
+    let mut offsets = Vec::new();
+    RandomAccessSimple::create(rdr, &mut offsets)?;
+
+    // the index is a sequence of big-endian u64 record offsets, followed by the record count
+    let (records, count) = offsets.split_at(offsets.len() - 8);
+    for offset in records.chunks_exact(8) {
+        // safety: chunks_exact always returns 8 byte chunks
+        let offset = u64::from_be_bytes(offset.try_into().unwrap());
+        wtr.write_all(&map.virtual_offset(offset).to_be_bytes())?;
+    }
+    wtr.write_all(count)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn bgzf_block(data: &[u8]) -> Vec<u8> {
+        let mut deflated = Vec::new();
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(&mut deflated, flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap();
+
+        let bsize = (BGZF_HEADER_LEN + deflated.len() + 8 - 1) as u16;
+        let mut block = vec![0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 0x06, 0x00];
+        block.extend_from_slice(&[b'B', b'C', 0x02, 0x00]);
+        block.extend_from_slice(&bsize.to_le_bytes());
+        block.extend_from_slice(&deflated);
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+        block.extend_from_slice(&crc.sum().to_le_bytes());
+        block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        block
+    }
+
+    fn bgzf_file(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut file: Vec<u8> = chunks.iter().flat_map(|c| bgzf_block(c)).collect();
+        // the EOF marker block
+        file.extend(bgzf_block(b""));
+        file
+    }
+
+    fn zstd_seekable_file(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut file = Vec::new();
+        let mut table = Vec::new();
+        for chunk in chunks {
+            let frame = zstd::bulk::compress(chunk, 0).unwrap();
+            table.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            table.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            file.extend(frame);
+        }
+        file.extend_from_slice(&ZSTD_SKIPPABLE_MAGIC.to_le_bytes());
+        file.extend_from_slice(&(table.len() as u32 + 9).to_le_bytes());
+        file.extend(table);
+        file.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        file.push(0);
+        file.extend_from_slice(&ZSTD_SEEKABLE_MAGIC.to_le_bytes());
+        file
+    }
+
+    fn open(file: Vec<u8>, format: BlockFormat) -> BlockReader<Cursor<Vec<u8>>> {
+        let mut cursor = Cursor::new(file);
+        let map = BlockMap::read(&mut cursor, format).unwrap().unwrap();
+        BlockReader::new(cursor, Arc::new(map))
+    }
+
+    #[test]
+    fn test_bgzf_read_and_seek() {
+        let mut rdr = open(
+            bgzf_file(&[b"a,b\n1,2\n", b"3,4\n", b"5,6\n"]),
+            BlockFormat::Bgzf,
+        );
+        let mut data = String::new();
+        rdr.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "a,b\n1,2\n3,4\n5,6\n");
+
+        rdr.seek(SeekFrom::Start(10)).unwrap();
+        let mut data = String::new();
+        rdr.read_to_string(&mut data).unwrap();
+        assert_eq!(data, ",4\n5,6\n");
+    }
+
+    #[test]
+    fn test_bgzf_virtual_offsets() {
+        let file = bgzf_file(&[b"a,b\n1,2\n", b"3,4\n"]);
+        let second_block = bgzf_block(b"a,b\n1,2\n").len() as u64;
+        let rdr = open(file, BlockFormat::Bgzf);
+        let map = rdr.block_map();
+
+        assert_eq!(map.virtual_offset(4), 4);
+        assert_eq!(map.virtual_offset(9), (second_block << 16) | 1);
+        assert_eq!(map.real_offset((second_block << 16) | 1).unwrap(), 9);
+        assert!(map.real_offset((second_block + 1) << 16).is_err());
+    }
+
+    #[test]
+    fn test_zstd_seekable_read_and_seek() {
+        let mut rdr = open(
+            zstd_seekable_file(&[b"a,b\n1,2\n", b"3,4\n5,6\n"]),
+            BlockFormat::Zstd,
+        );
+        rdr.seek(SeekFrom::Start(12)).unwrap();
+        let mut data = String::new();
+        rdr.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "5,6\n");
+        assert_eq!(rdr.block_map().virtual_offset(12), 12);
+    }
+
+    #[test]
+    fn test_not_block_compressed() {
+        let mut gzipped = Vec::new();
+        let mut encoder =
+            flate2::write::GzEncoder::new(&mut gzipped, flate2::Compression::default());
+        encoder.write_all(b"a,b\n1,2\n").unwrap();
+        encoder.finish().unwrap();
+        assert!(BlockMap::read(&mut Cursor::new(gzipped), BlockFormat::Bgzf)
+            .unwrap()
+            .is_none());
+
+        let zstd = zstd::bulk::compress(b"a,b\n1,2\n", 0).unwrap();
+        assert!(BlockMap::read(&mut Cursor::new(zstd), BlockFormat::Zstd)
+            .unwrap()
+            .is_none());
+    }
+}
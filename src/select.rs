@@ -64,10 +64,9 @@ impl SelectColumns {
         Ok(Selection(map))
     }
 
-    // commented out because it's unused
-    // pub fn is_empty(&self) -> bool {
-    //     self.selectors.is_empty()
-    // }
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
 }
 
 impl fmt::Debug for SelectColumns {
@@ -78,6 +78,8 @@ pub struct SchemaArgs {
     pub flag_jobs:            Option<usize>,
     pub flag_no_headers:      bool,
     pub flag_delimiter:       Option<Delimiter>,
+    // set by the caller, as `schema` accepts several inputs
+    #[serde(skip_deserializing)]
     pub arg_input:            Option<String>,
     pub flag_memcheck:        bool,
 }
@@ -1593,6 +1595,129 @@ pub fn isutf8_file(path: &Path) -> Result<bool, CliError> {
     Ok(simdutf8::basic::from_utf8(&buffer).is_ok())
 }
 
+/// the number of bytes sampled to detect a character encoding
+#[cfg(feature = "encoding")]
+pub const ENCODING_SAMPLE_SIZE: usize = 64 * 1024;
+
+/// The character encoding detected by `detect_encoding`.
+#[cfg(feature = "encoding")]
+#[derive(Debug, Clone, Copy)]
+pub struct DetectedEncoding {
+    pub encoding:   &'static encoding_rs::Encoding,
+    /// 1.0 when the encoding is certain (BOM or valid UTF-8),
+    /// lower for statistical guesses
+    pub confidence: f64,
+    pub bom:        bool,
+}
+
+/// Detects the character encoding of `sample`, the start of a stream.
+/// `complete` is true if the sample is the entire stream.
+///
+/// A BOM is trusted first, then UTF-16 without a BOM is recognized by its
+/// NUL bytes, then valid UTF-8 (including plain ASCII). Otherwise, the legacy
+/// encoding is guessed statistically with chardetng. Samples with control
+/// characters are likely not text at all, so their guess has zero confidence.
+#[cfg(feature = "encoding")]
+#[allow(clippy::cast_precision_loss)]
+pub fn detect_encoding(sample: &[u8], complete: bool) -> DetectedEncoding {
+    let certain = |encoding, bom| DetectedEncoding {
+        encoding,
+        confidence: 1.0,
+        bom,
+    };
+
+    if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(sample) {
+        return certain(encoding, true);
+    }
+
+    // mostly-ASCII UTF-16 text has a NUL in every other byte
+    let units = sample.len() / 2;
+    if units >= 2 {
+        let even_nuls = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_nuls = sample
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count();
+        let (nuls, other_nuls, encoding) = if odd_nuls >= even_nuls {
+            (odd_nuls, even_nuls, encoding_rs::UTF_16LE)
+        } else {
+            (even_nuls, odd_nuls, encoding_rs::UTF_16BE)
+        };
+        if nuls * 2 > units && other_nuls * 10 < units {
+            return DetectedEncoding {
+                encoding,
+                confidence: (nuls as f64 / units as f64 * 100.0).round() / 100.0,
+                bom: false,
+            };
+        }
+    }
+
+    match simdutf8::compat::from_utf8(sample) {
+        Ok(_) => return certain(encoding_rs::UTF_8, false),
+        // the sample may end in the middle of a multi-byte character
+        Err(e) if !complete && e.error_len().is_none() => {
+            return certain(encoding_rs::UTF_8, false);
+        },
+        Err(_) => {},
+    }
+
+    let is_binary = sample
+        .iter()
+        .any(|&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c));
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(sample, complete);
+    let (encoding, outscored_others) = detector.guess_assess(None, false);
+    let had_errors = encoding
+        .decode_without_bom_handling_and_without_replacement(sample)
+        .is_none();
+    DetectedEncoding {
+        encoding,
+        confidence: match (is_binary, outscored_others, had_errors) {
+            (true, ..) => 0.0,
+            (false, true, false) => 0.8,
+            (false, false, false) => 0.5,
+            (false, _, true) => 0.2,
+        },
+        bom: false,
+    }
+}
+
+/// Reads a sample from `rdr` and detects its character encoding.
+/// Returns the detected encoding and a reader over the entire stream,
+/// including the sampled bytes.
+#[cfg(feature = "encoding")]
+pub fn sniff_encoding<R: Read>(
+    mut rdr: R,
+) -> std::io::Result<(
+    DetectedEncoding,
+    std::io::Chain<std::io::Cursor<Vec<u8>>, R>,
+)> {
+    let mut sample = Vec::with_capacity(ENCODING_SAMPLE_SIZE);
+    (&mut rdr)
+        .take(ENCODING_SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)?;
+    let detected = detect_encoding(&sample, sample.len() < ENCODING_SAMPLE_SIZE);
+    Ok((detected, std::io::Cursor::new(sample).chain(rdr)))
+}
+
+/// Wraps `rdr` in a streaming transcoder from `encoding` to UTF-8.
+/// A BOM takes precedence over `encoding` and is removed. Malformed sequences
+/// are replaced with the U+FFFD (�) replacement character.
+#[cfg(feature = "encoding")]
+pub fn transcode_reader<R: Read + Send + 'static>(
+    rdr: R,
+    encoding: &'static encoding_rs::Encoding,
+) -> Box<dyn Read + Send + 'static> {
+    Box::new(
+        encoding_rs_io::DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .bom_override(true)
+            .build(rdr),
+    )
+}
+
 /// Process the input files and return a vector of paths to the input files.
 ///
 /// If the input is empty, try to copy stdin to a file named stdin in the passed temp directory.
@@ -1960,6 +2085,52 @@ pub fn trim_bs_whitespace(bytes: &[u8]) -> &[u8] {
     &bytes[start..end]
 }
 
+/// derive a Polars schema from the stats of a CSV, as cached by `sqlp --cache-schema`
+/// in ".pschema.json" files
+#[cfg(feature = "polars")]
+pub fn polars_schema_from_stats(
+    csv_fields: &ByteRecord,
+    csv_stats: &[StatsData],
+) -> polars::prelude::Schema {
+    use polars::{datatypes::DataType, prelude::PlSmallStr};
+
+    let mut schema = polars::prelude::Schema::with_capacity(csv_stats.len());
+    for (idx, stat) in csv_stats.iter().enumerate() {
+        schema.insert(
+            PlSmallStr::from_str(simdutf8::basic::from_utf8(csv_fields.get(idx).unwrap()).unwrap()),
+            {
+                let datatype = &stat.r#type;
+                #[allow(clippy::match_same_arms)]
+                match datatype.as_str() {
+                    "String" => DataType::String,
+                    "Integer" => {
+                        let min = stat.min.as_ref().unwrap();
+                        let max = stat.max.as_ref().unwrap();
+                        if min.parse::<i32>().is_ok() && max.parse::<i32>().is_ok() {
+                            DataType::Int32
+                        } else {
+                            DataType::Int64
+                        }
+                    },
+                    "Float" => {
+                        let min = stat.min.as_ref().unwrap();
+                        let max = stat.max.as_ref().unwrap();
+                        if min.parse::<f32>().is_ok() && max.parse::<f32>().is_ok() {
+                            DataType::Float32
+                        } else {
+                            DataType::Float64
+                        }
+                    },
+                    "Boolean" => DataType::Boolean,
+                    "Date" => DataType::Date,
+                    _ => DataType::String,
+                }
+            },
+        );
+    }
+    schema
+}
+
 /// get stats records from stats.csv.data.jsonl file, or if its invalid, by running the stats
 /// command returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`)
 pub fn get_stats_records(
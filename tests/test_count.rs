@@ -285,6 +285,90 @@ fn count_comments() {
     assert_eq!(got, expected.to_string());
 }
 
+#[test]
+#[cfg(feature = "compression")]
+fn count_gzip() {
+    let wrk = Workdir::new("count_gzip");
+    let test_file = wrk.load_test_file("boston311-100.csv.gz");
+
+    let mut cmd = wrk.command("count");
+    cmd.arg(test_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "100";
+    assert_eq!(got, expected.to_string());
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn count_zstd() {
+    let wrk = Workdir::new("count_zstd");
+    let test_file = wrk.load_test_file("boston311-100.csv.zst");
+
+    let mut cmd = wrk.command("count");
+    cmd.arg(test_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "100";
+    assert_eq!(got, expected.to_string());
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn count_compressed_output_roundtrip() {
+    let wrk = Workdir::new("count_compressed_output_roundtrip");
+    let test_file = wrk.load_test_file("boston311-100.csv.zst");
+
+    // decode zstd input, and encode the output as gzip
+    let mut cmd = wrk.command("select");
+    cmd.arg("1-3")
+        .arg(test_file)
+        .args(["--output", "out.csv.gz"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("count");
+    cmd.arg("out.csv.gz");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "100";
+    assert_eq!(got, expected.to_string());
+}
+
+#[test]
+#[cfg(feature = "polars")]
+fn count_jsonl_input() {
+    let wrk = Workdir::new("count_jsonl_input");
+    let test_file = wrk.load_test_file("boston311-100.jsonl");
+
+    let mut cmd = wrk.command("count");
+    cmd.arg(test_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "100";
+    assert_eq!(got, expected.to_string());
+}
+
+#[test]
+#[cfg(feature = "polars")]
+fn count_parquet_arrow_input() {
+    let wrk = Workdir::new("count_parquet_arrow_input");
+    let test_file = wrk.load_test_file("boston311-100.csv");
+
+    for (format, output) in [("parquet", "boston.parquet"), ("arrow", "boston.arrow")] {
+        let mut cmd = wrk.command("sqlp");
+        cmd.arg(&test_file)
+            .arg("select * from _t_1")
+            .args(["--format", format, "--output", output]);
+        wrk.assert_success(&mut cmd);
+
+        let mut cmd = wrk.command("count");
+        cmd.arg(output);
+
+        let got: String = wrk.stdout(&mut cmd);
+        assert_eq!(got, "100".to_string(), "count of {output}");
+    }
+}
+
 /// This tests whether `qsv count` gets the right answer.
 ///
 /// It does some simple case analysis to handle whether we want to test counts
@@ -287,3 +287,88 @@ fn dedup_not_sorted2() {
     let got: String = wrk.output_stderr(&mut cmd);
     assert!(got.contains("Aborting! Input not sorted!"));
 }
+
+#[test]
+fn dedup_keep_max_dupes_output() {
+    let wrk = Workdir::new("dedup_keep_max_dupes_output");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "updated_at", "name"],
+            svec!["1", "2024-01-05", "a"],
+            svec!["2", "2024-01-01", "b"],
+            svec!["1", "2024-03-01", "c"],
+            svec!["1", "2024-02-01", "d"],
+            svec!["2", "", "e"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["-s", "id"])
+        .args(["--keep", "max:updated_at"])
+        .args(["--dupes-output", "dupes.csv"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "updated_at", "name"],
+        svec!["1", "2024-03-01", "c"],
+        svec!["2", "2024-01-01", "b"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_dupes: String = wrk.from_str(&wrk.path("dupes.csv"));
+    let expected_dupes = r#"dupe_rowno,dupe_group,dupe_group_count,kept_rowno,id,updated_at,name
+1,1,3,3,1,2024-01-05,a
+4,1,3,3,1,2024-02-01,d
+5,2,2,2,2,,e
+"#;
+    assert_eq!(got_dupes, expected_dupes);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n");
+}
+
+#[test]
+fn dedup_keep_most_complete_sorted() {
+    let wrk = Workdir::new("dedup_keep_most_complete_sorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "email", "phone"],
+            svec!["1", "", ""],
+            svec!["1", "a@example.com", "555-1234"],
+            svec!["1", "a@example.com", ""],
+            svec!["2", "b@example.com", ""],
+            svec!["2", "", "555-9876"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["-s", "id"])
+        .args(["--keep", "most-complete"])
+        .arg("--sorted")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "email", "phone"],
+        svec!["1", "a@example.com", "555-1234"],
+        svec!["2", "b@example.com", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_keep_invalid_policy() {
+    let wrk = Workdir::new("dedup_keep_invalid_policy");
+    wrk.create(
+        "in.csv",
+        vec![svec!["id", "name"], svec!["1", "a"], svec!["1", "b"]],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--keep", "newest"]).arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
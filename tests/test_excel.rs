@@ -1946,3 +1946,375 @@ fn excel_absolute_range2() {
     assert_eq!(got, expected);
     wrk.assert_success(&mut cmd);
 }
+
+fn read_exported(wrk: &Workdir, name: &str) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(wrk.path(name))
+        .unwrap()
+        .records()
+        .map(|record| record.unwrap().iter().map(String::from).collect())
+        .collect()
+}
+
+#[test]
+fn excel_all_sheets() {
+    let wrk = Workdir::new("excel_all_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-xlsx.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .arg("exported")
+        .arg("--include-tables")
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let manifest: Vec<Vec<String>> = read_exported(&wrk, "exported/manifest.csv");
+    let got: Vec<Vec<String>> = manifest.iter().map(|row| row[..3].to_vec()).collect();
+    let expected = vec![
+        svec!["sheet_name", "table_name", "file"],
+        svec!["Sheet1", "", "sheet1.csv"],
+        svec!["safe_header_name_test", "", "safe_header_name_test.csv"],
+        svec!["date_test", "", "date_test.csv"],
+        svec!["data types", "", "data_types.csv"],
+        svec!["cellerrors", "", "cellerrors.csv"],
+        svec!["Sheet2", "", "sheet2.csv"],
+        svec!["firstnonemptyrow", "", "firstnonemptyrow.csv"],
+        svec!["Sheet2", "Table1", "table1.csv"],
+    ];
+    assert_eq!(got, expected);
+    assert_eq!(manifest[1][3..], svec!["8", "4"]);
+
+    // every sheet is exported the same way as a single sheet export
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet").arg("Sheet1").arg(&xlsx_file);
+    let expected: String = wrk.stdout(&mut cmd);
+    let got = wrk.read_to_string("exported/sheet1.csv").unwrap();
+    assert_eq!(got.trim_end(), expected);
+
+    let got: Vec<Vec<String>> = read_exported(&wrk, "exported/table1.csv");
+    let expected = vec![
+        svec!["tabc1", "tabc2", "tabc3"],
+        svec!["a2", "false", "2.2"],
+        svec!["a3", "true", "3.3"],
+        svec!["a4", "true", "4.4"],
+        svec!["a5", "false", "5.56"],
+        svec!["a6", "true", "0.9999"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_all_sheets_xls() {
+    let wrk = Workdir::new("excel_all_sheets_xls");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets").arg("exported").arg(&xls_file);
+    wrk.assert_success(&mut cmd);
+
+    let manifest: Vec<Vec<String>> = read_exported(&wrk, "exported/manifest.csv");
+    let got: Vec<String> = manifest.iter().skip(1).map(|row| row[2].clone()).collect();
+    assert_eq!(
+        got,
+        svec![
+            "first.csv",
+            "flexibility_test.csv",
+            "middle.csv",
+            "sheet1.csv",
+            "trim_test.csv",
+            "date_test.csv",
+            "nodata.csv",
+            "last.csv"
+        ]
+    );
+    assert!(wrk.path("exported/nodata.csv").exists());
+
+    // tables are only supported in xlsx files
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .arg("exported2")
+        .arg("--include-tables")
+        .arg(&xls_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_merged_cells_header_rows() {
+    let wrk = Workdir::new("excel_merged_cells_header_rows");
+
+    let xlsx_file = wrk.load_test_file("excel-merged.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--merged-cells")
+        .args(["--header-rows", "2"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["Region", "Population_2020", "Population_2021"],
+        svec!["North", "10", "11"],
+        svec!["East", "30", "31"],
+        svec!["East", "32", "33"],
+    ];
+    assert_eq!(got, expected);
+
+    // without --merged-cells, only the top-left cell of a merged region has a value
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--header-rows", "2"])
+        .args(["--header-join", " "])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["Region", "Population 2020", "2021"],
+        svec!["North", "10", "11"],
+        svec!["East", "30", "31"],
+        svec!["", "32", "33"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--header-rows", "0"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[cfg(feature = "excel_xml")]
+fn excel_as_displayed() {
+    let wrk = Workdir::new("excel_as_displayed");
+
+    let xlsx_file = wrk.load_test_file("excel-formats.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--as-displayed")
+        .args(["--format-sidecar", "formats.csv"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["pct", "amount", "zip", "fixed", "date", "thousands", "name"],
+        svec![
+            "12.34%",
+            "$1,234.50",
+            "00501",
+            "2.50",
+            "15/01/2024",
+            "1,234.6K",
+            "alpha"
+        ],
+        svec![
+            "-5.00%",
+            "-$42.00",
+            "12345",
+            "3.00",
+            "16/01/2024",
+            "1.0K",
+            "beta"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    let got: Vec<Vec<String>> = read_exported(&wrk, "formats.csv");
+    let expected = vec![
+        svec!["column", "number_format", "format_type"],
+        svec!["pct", "0.00%", "percent"],
+        svec!["amount", "\"$\"#,##0.00", "currency"],
+        svec!["zip", "00000", "number"],
+        svec!["fixed", "0.00", "number"],
+        svec!["date", "dd/mm/yyyy", "date"],
+        svec!["thousands", "#,##0.0,\"K\"", "number"],
+        svec!["name", "General", "general"],
+    ];
+    assert_eq!(got, expected);
+
+    // without --as-displayed, the underlying values are exported
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(
+        got[1],
+        svec![
+            "0.1234",
+            "1234.5",
+            "501",
+            "2.5",
+            "2024-01-15",
+            "1234567",
+            "alpha"
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "excel_xml")]
+fn excel_write_update_cells() {
+    let wrk = Workdir::new("excel_write_update_cells");
+
+    let xlsx_file = wrk.load_test_file("excel-formats.xlsx");
+    wrk.create("data.csv", vec![svec!["pct", "amount"], svec!["0.5", "99"]]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "data.csv"])
+        .args(["--range", "Formats!A3"])
+        .arg("--data-only")
+        .args(["--output", "filled.xlsx"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    // the overwritten cells keep their number formats, the other cells are unchanged
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--as-displayed").arg("filled.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["pct", "amount", "zip", "fixed", "date", "thousands", "name"],
+        svec![
+            "12.34%",
+            "$1,234.50",
+            "00501",
+            "2.50",
+            "15/01/2024",
+            "1,234.6K",
+            "alpha"
+        ],
+        svec![
+            "50.00%",
+            "$99.00",
+            "12345",
+            "3.00",
+            "16/01/2024",
+            "1.0K",
+            "beta"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    // the CSV doesn't fit in the range
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "data.csv"])
+        .args(["--range", "A3:A4"])
+        .args(["--output", "filled2.xlsx"])
+        .arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[cfg(feature = "excel_xml")]
+fn excel_write_shared_formula() {
+    let wrk = Workdir::new("excel_write_shared_formula");
+
+    // H2 is the master cell of a formula shared with H3
+    let xlsx_file = wrk.load_test_file("excel-shared-formula.xlsx");
+    wrk.create("data.csv", vec![svec!["1"]]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "data.csv"])
+        .args(["--range", "Formats!H2"])
+        .arg("--data-only")
+        .args(["--output", "master.xlsx"])
+        .arg(&xlsx_file);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.contains("Cannot overwrite cell H2 as it has the shared formula of H2:H3"),
+        "{got}"
+    );
+    wrk.assert_err(&mut cmd);
+
+    // the other cells of the shared formula can be overwritten
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "data.csv"])
+        .args(["--range", "Formats!H3"])
+        .arg("--data-only")
+        .args(["--output", "dependent.xlsx"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("dependent.xlsx");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[2][7], "1");
+}
+
+#[test]
+#[cfg(feature = "excel_xml")]
+fn excel_write_append() {
+    let wrk = Workdir::new("excel_write_append");
+
+    let xlsx_file = wrk.load_test_file("excel-formats.xlsx");
+    wrk.create(
+        "new_rows.csv",
+        vec![
+            svec!["pct", "amount", "zip", "fixed", "date", "thousands", "name"],
+            svec!["0.25", "10", "7", "1", "45308", "2000", "gamma"],
+        ],
+    );
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "new_rows.csv"])
+        .arg("--append")
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    // the appended cells get the number formats of the last row
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--as-displayed").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["pct", "amount", "zip", "fixed", "date", "thousands", "name"],
+        svec![
+            "12.34%",
+            "$1,234.50",
+            "00501",
+            "2.50",
+            "15/01/2024",
+            "1,234.6K",
+            "alpha"
+        ],
+        svec![
+            "-5.00%",
+            "-$42.00",
+            "12345",
+            "3.00",
+            "16/01/2024",
+            "1.0K",
+            "beta"
+        ],
+        svec![
+            "25.00%",
+            "$10.00",
+            "00007",
+            "1.00",
+            "17/01/2024",
+            "2.0K",
+            "gamma"
+        ],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[cfg(all(unix, feature = "excel_xml"))]
+#[test]
+fn excel_write_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let wrk = Workdir::new("excel_write_keeps_permissions");
+
+    let xlsx_file = wrk.load_test_file("excel-formats.xlsx");
+    std::fs::set_permissions(&xlsx_file, std::fs::Permissions::from_mode(0o644)).unwrap();
+    wrk.create("data.csv", vec![svec!["0.5", "99"]]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--write", "data.csv"])
+        .args(["--range", "Formats!A3"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let mode = std::fs::metadata(&xlsx_file).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o644);
+}
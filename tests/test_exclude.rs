@@ -196,3 +196,36 @@ fn exclude_1497_empty_fields() {
 
     assert_eq!(got, expected);
 }
+
+#[test]
+#[cfg(feature = "compression")]
+fn exclude_block_compressed() {
+    let wrk = Workdir::new("exclude_block_compressed");
+    let plain = wrk.load_test_file("boston311-100.csv");
+    wrk.create(
+        "skip.csv",
+        vec![svec!["case_enquiry_id"], svec!["101004143000"]],
+    );
+
+    let mut cmd = wrk.command("exclude");
+    cmd.args(["case_enquiry_id", &plain, "case_enquiry_id", "skip.csv"]);
+    let expected: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(expected.len(), 100);
+
+    // BGZF & seekable zstd files are read block by block, as they can seek
+    for test_file in [
+        "boston311-100-bgzf.csv.gz",
+        "boston311-100-seekable.csv.zst",
+    ] {
+        let compressed = wrk.load_test_file(test_file);
+        let mut cmd = wrk.command("exclude");
+        cmd.args([
+            "case_enquiry_id",
+            &compressed,
+            "case_enquiry_id",
+            "skip.csv",
+        ]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        assert_eq!(got, expected);
+    }
+}
@@ -83,6 +83,28 @@ fn extdedupe_csvmode() {
     assert!(String::from_utf8_lossy(&output.stderr).contains("20\n"));
 }
 
+#[test]
+fn extdedup_csvmode_field_boundaries() {
+    let wrk = Workdir::new("extdedup_csvmode_field_boundaries");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["a", "b"],
+            svec!["ab", "c"],
+            svec!["a", "bc"],
+            svec!["ab", "c"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("in.csv").args(["--select", "a,b"]);
+
+    // ("ab", "c") & ("a", "bc") are different keys
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b"], svec!["ab", "c"], svec!["a", "bc"]];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn extdedupe_csvmode_dupesoutput() {
     let wrk = Workdir::new("extdedup-csvmode-dupesoutput").flexible(true);
@@ -150,3 +172,162 @@ fn extdedupe_csvmode_neighborhood() {
     // 81 duplicates should be removed
     assert!(String::from_utf8_lossy(&output.stderr).contains("81\n"));
 }
+
+#[test]
+fn extdedup_keep_last_dupes_output() {
+    let wrk = Workdir::new("extdedup_keep_last_dupes_output");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "updated_at", "name"],
+            svec!["1", "2024-01-05", "a"],
+            svec!["2", "2024-01-01", "b"],
+            svec!["1", "2024-03-01", "c"],
+            svec!["1", "2024-02-01", "d"],
+            svec!["2", "", "e"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("in.csv")
+        .args(["--select", "id"])
+        .args(["--keep", "last"])
+        .args(["--dupes-output", "dupes.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "updated_at", "name"],
+        svec!["1", "2024-02-01", "d"],
+        svec!["2", "", "e"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_dupes: String = wrk.from_str(&wrk.path("dupes.csv"));
+    let expected_dupes = r#"dupe_rowno,dupe_group,dupe_group_count,kept_rowno,id,updated_at,name
+1,1,3,4,1,2024-01-05,a
+2,2,2,5,2,2024-01-01,b
+3,1,3,4,1,2024-03-01,c
+"#;
+    assert_eq!(got_dupes, expected_dupes);
+}
+
+#[test]
+fn extdedup_keep_max() {
+    let wrk = Workdir::new("extdedup_keep_max");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "amount"],
+            svec!["1", "10"],
+            svec!["2", "5"],
+            svec!["1", "30"],
+            svec!["1", "20"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("in.csv")
+        .args(["--select", "id"])
+        .args(["--keep", "max:amount"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "amount"], svec!["2", "5"], svec!["1", "30"]];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "2\n");
+}
+
+#[test]
+fn extdedup_keep_stdin_error() {
+    let wrk = Workdir::new("extdedup_keep_stdin_error");
+    wrk.create("in.csv", vec![svec!["id"], svec!["1"], svec!["1"]]);
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("-")
+        .args(["--select", "id"])
+        .args(["--keep", "last"])
+        .stdin(std::fs::File::open(wrk.path("in.csv")).unwrap());
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("stdin is not supported"), "{got}");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn extdedup_state_across_runs() {
+    let wrk = Workdir::new("extdedup_state_across_runs");
+    wrk.create(
+        "batch1.csv",
+        vec![
+            svec!["id", "name"],
+            svec!["1", "a"],
+            svec!["2", "b"],
+            svec!["1", "a"],
+        ],
+    );
+    wrk.create(
+        "batch2.csv",
+        vec![
+            svec!["id", "name"],
+            svec!["2", "b"],
+            svec!["3", "c"],
+            svec!["1", "z"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch1.csv")
+        .args(["--select", "id"])
+        .args(["--state", "dedup.state"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "name"], svec!["1", "a"], svec!["2", "b"]];
+    assert_eq!(got, expected);
+
+    // rows already seen in batch1 are dupes
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch2.csv")
+        .args(["--select", "id"])
+        .args(["--state", "dedup.state"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "name"], svec!["3", "c"]];
+    assert_eq!(got, expected);
+
+    // a state built with different keys cannot be reused
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch2.csv")
+        .args(["--select", "name"])
+        .args(["--state", "dedup.state"]);
+    wrk.assert_err(&mut cmd);
+
+    // unless it is reset
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch2.csv")
+        .args(["--select", "id"])
+        .args(["--state", "dedup.state"])
+        .arg("--reset-state");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name"],
+        svec!["2", "b"],
+        svec!["3", "c"],
+        svec!["1", "z"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extdedup_state_linemode() {
+    let wrk = Workdir::new("extdedup_state_linemode");
+    wrk.create_from_string("batch1.txt", "alpha\nbeta\n");
+    wrk.create_from_string("batch2.txt", "beta\ngamma\nalpha\n");
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch1.txt").args(["--state", "dedup.state"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.arg("batch2.txt").args(["--state", "dedup.state"]);
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "gamma");
+}
@@ -0,0 +1,251 @@
+use crate::workdir::Workdir;
+
+fn layout() -> Vec<Vec<String>> {
+    vec![
+        svec!["name", "start", "length", "type", "decimals"],
+        svec!["id", "1", "4", "integer", ""],
+        svec!["name", "5", "10", "string", ""],
+        svec!["amount", "15", "7", "decimal", "2"],
+    ]
+}
+
+#[test]
+fn fixedwidth_import_csv_layout() {
+    let wrk = Workdir::new("fixedwidth_import_csv_layout");
+    wrk.create("layout.csv", layout());
+    wrk.create_from_string(
+        "data.txt",
+        "0001Alice     0012345\n0002Bob       0000050\r\n\n0003  Carol   -000100\n",
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "layout.csv"])
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "amount"],
+        svec!["1", "Alice", "123.45"],
+        svec!["2", "Bob", "0.50"],
+        svec!["3", "Carol", "-1.00"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_import_overpunch_no_trim() {
+    let wrk = Workdir::new("fixedwidth_import_overpunch_no_trim");
+    // no start column, so fields follow one another
+    wrk.create(
+        "layout.csv",
+        vec![
+            svec!["name", "width", "type", "decimals"],
+            svec!["code", "3", "string", ""],
+            svec!["balance", "5", "integer", "2"],
+        ],
+    );
+    wrk.create_from_string("data.txt", " AB0012J\n CD0010{\n");
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "layout.csv"])
+        .arg("--overpunch")
+        .args(["--trim", "none"])
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["code", "balance"],
+        svec![" AB", "-1.21"],
+        svec![" CD", "1.00"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_import_copybook() {
+    let wrk = Workdir::new("fixedwidth_import_copybook");
+    wrk.create_from_string(
+        "custrec.cpy",
+        r#"      * customer master record
+       01  CUST-REC.
+           05  CUST-ID         PIC 9(5).
+           05  CUST-NAME       PIC X(10).
+           05  FILLER          PIC XX.
+           05  BALANCE         PIC S9(5)V99.
+           05  STATUS          PIC X VALUE 'A'.
+               88  ACTIVE      VALUE 'A'.
+"#,
+    );
+    wrk.create_from_string(
+        "custrec.dat",
+        "00042John SmithXX000123DA\n00007Jane Doe  XX000050}I\n",
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "custrec.cpy"])
+        .arg("custrec.dat");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["CUST-ID", "CUST-NAME", "BALANCE", "STATUS"],
+        svec!["42", "John Smith", "12.34", "A"],
+        svec!["7", "Jane Doe", "-5.00", "I"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_import_copybook_unsupported() {
+    let wrk = Workdir::new("fixedwidth_import_copybook_unsupported");
+    wrk.create_from_string(
+        "rec.cpy",
+        "01 REC.\n   05 AMOUNTS PIC 9(5) OCCURS 3 TIMES.\n",
+    );
+    wrk.create_from_string("rec.dat", "000010000200003\n");
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "rec.cpy"])
+        .arg("rec.dat");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("OCCURS is not supported"), "{got}");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn fixedwidth_export() {
+    let wrk = Workdir::new("fixedwidth_export");
+    wrk.create("layout.csv", layout());
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "amount", "id"],
+            svec!["Alice", "123.45", "1"],
+            svec!["Bartholomew Jr", "-0.5", "2"],
+            svec!["", "", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("export")
+        .args(["--layout", "layout.csv"])
+        .arg("--overpunch")
+        .arg("data.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    // with --overpunch, every numeric field carries its sign in the last digit
+    let expected = format!(
+        "000AAlice     001234E\n000BBartholome000005}}\n000C{}",
+        " ".repeat(17)
+    );
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_export_strict() {
+    let wrk = Workdir::new("fixedwidth_export_strict");
+    wrk.create("layout.csv", layout());
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Bartholomew Jr", "1"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("export")
+        .args(["--layout", "layout.csv"])
+        .arg("--strict")
+        .arg("data.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.contains(
+            r#"Record 1, field "name": value "Bartholomew Jr" is longer than the field width of 10"#
+        ),
+        "{got}"
+    );
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn fixedwidth_record_length_roundtrip() {
+    let wrk = Workdir::new("fixedwidth_record_length_roundtrip");
+    wrk.create("layout.csv", layout());
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Alice", "123.45"],
+            svec!["2", "Bob", "-7.00"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("export")
+        .args(["--layout", "layout.csv"])
+        .args(["--record-length", "24"])
+        .args(["--pad", "."])
+        .arg("data.csv")
+        .args(["--output", "data.dat"]);
+    wrk.assert_success(&mut cmd);
+
+    let got = wrk.read_to_string("data.dat").unwrap();
+    assert_eq!(got, "0001Alice.....0012345   0002Bob.......-000700   ");
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "layout.csv"])
+        .args(["--record-length", "24"])
+        .arg("data.dat");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "amount"],
+        svec!["1", "Alice.....", "123.45"],
+        svec!["2", "Bob.......", "-7.00"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_export_decimal_point_zero_padded() {
+    let wrk = Workdir::new("fixedwidth_export_decimal_point_zero_padded");
+    // decimal fields without implied decimals keep their decimal point
+    wrk.create(
+        "layout.csv",
+        vec![
+            svec!["name", "start", "length", "type", "decimals"],
+            svec!["id", "1", "2", "integer", ""],
+            svec!["rate", "3", "7", "decimal", "0"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![svec!["id", "rate"], svec!["1", "12.25"], svec!["2", "-1.5"]],
+    );
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("export")
+        .args(["--layout", "layout.csv"])
+        .arg("data.csv")
+        .args(["--output", "data.dat"]);
+    wrk.assert_success(&mut cmd);
+
+    let got = wrk.read_to_string("data.dat").unwrap();
+    assert_eq!(got, "010012.25\n02-0001.5\n");
+
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("import")
+        .args(["--layout", "layout.csv"])
+        .arg("data.dat");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "rate"], svec!["1", "12.25"], svec!["2", "-1.5"]];
+    assert_eq!(got, expected);
+}
@@ -253,3 +253,25 @@ fn headers_intersect_infile() {
     let expected = vec![["a"], ["b"], ["c"], ["d"], ["e"], ["f"], ["g"]];
     assert_eq!(got, expected);
 }
+
+#[test]
+#[cfg(feature = "polars")]
+fn headers_jsonl_late_column() {
+    let wrk = Workdir::new("headers_jsonl_late_column");
+    // the "late" column only appears after the first batch of lines
+    let mut data = String::new();
+    for i in 0..300 {
+        data.push_str(&format!("{{\"id\":{i}}}\n"));
+    }
+    data.push_str("{\"id\":300,\"late\":\"x\"}\n");
+    std::fs::write(wrk.path("data.jsonl"), data).unwrap();
+
+    let mut cmd = wrk.command("headers");
+    cmd.arg("--just-names").arg("data.jsonl");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "\
+id
+late";
+    assert_eq!(got, expected.to_string());
+}
@@ -330,6 +330,64 @@ fn index_autoindex_threshold_not_reached() {
     assert!(!wrk.path("in.csv.idx").exists());
 }
 
+#[test]
+fn index_compressed_file() {
+    let wrk = Workdir::new("index_compressed_file");
+    let test_file = wrk.load_test_file("boston311-100.csv.gz");
+
+    // compressed files are not seekable, so they cannot be indexed
+    let mut cmd = wrk.command("index");
+    cmd.arg(test_file);
+    wrk.assert_err(&mut cmd);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got,
+        "usage error: Cannot index a gzip compressed file, unless it is block-compressed (BGZF or \
+         seekable zstd).\n"
+    );
+}
+
+#[cfg(feature = "compression")]
+fn index_block_compressed(name: &str, test_file: &str) {
+    let wrk = Workdir::new(name);
+    let compressed = wrk.load_test_file(test_file);
+    let plain = wrk.load_test_file("boston311-100.csv");
+
+    let mut cmd = wrk.command("index");
+    cmd.arg(&compressed);
+    wrk.assert_success(&mut cmd);
+    assert!(wrk.path(&format!("{test_file}.idx")).exists());
+
+    // count is answered by the index
+    let mut cmd = wrk.command("count");
+    cmd.arg(&compressed);
+    let got: usize = wrk.stdout(&mut cmd);
+    assert_eq!(got, 100);
+
+    // slice seeks to the row's virtual offset
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--start", "75", "--len", "3"]).arg(&compressed);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--start", "75", "--len", "3"]).arg(&plain);
+    let expected: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn index_bgzf() {
+    index_block_compressed("index_bgzf", "boston311-100-bgzf.csv.gz");
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn index_zstd_seekable() {
+    index_block_compressed("index_zstd_seekable", "boston311-100-seekable.csv.zst");
+}
+
 fn future_time(ft: FileTime) -> FileTime {
     let secs = ft.unix_seconds();
     FileTime::from_unix_time(secs + 10_000, 0)
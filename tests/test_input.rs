@@ -476,3 +476,193 @@ fn input_headertrim() {
     ];
     assert_eq!(got, expected);
 }
+
+// "José Müller", "Zürich", "Renée Façade", "Besançon"... in Windows-1252
+#[cfg(feature = "encoding")]
+const WINDOWS_1252_CSV: &[u8] = b"id,name,city\n1,Jos\xe9 M\xfcller,Z\xfcrich\n2,Ren\xe9e \
+    Fa\xe7ade,Besan\xe7on\n3,\xc6tla,\xd8rsted \x97 \x93quoted\x94\n";
+
+#[cfg(feature = "encoding")]
+fn utf8_expected() -> Vec<Vec<String>> {
+    vec![
+        svec!["id", "name", "city"],
+        svec!["1", "José Müller", "Zürich"],
+        svec!["2", "Renée Façade", "Besançon"],
+        svec!["3", "Ætla", "Ørsted — “quoted”"],
+    ]
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_label() {
+    let wrk = Workdir::new("input_from_encoding_label");
+    std::fs::write(wrk.path("in.csv"), WINDOWS_1252_CSV).unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--from-encoding", "windows-1252"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, utf8_expected());
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_auto_legacy() {
+    let wrk = Workdir::new("input_from_encoding_auto_legacy");
+    std::fs::write(wrk.path("in.csv"), WINDOWS_1252_CSV).unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--from-encoding", "auto"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, utf8_expected());
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_auto_utf16() {
+    let wrk = Workdir::new("input_from_encoding_auto_utf16");
+    let csv =
+        "id,name,city\n1,José Müller,Zürich\n2,Renée Façade,Besançon\n3,Ætla,Ørsted — “quoted”\n";
+
+    // with a BOM
+    let mut utf16le = vec![0xFF, 0xFE];
+    utf16le.extend(csv.encode_utf16().flat_map(u16::to_le_bytes));
+    std::fs::write(wrk.path("le.csv"), utf16le).unwrap();
+    // without a BOM
+    let utf16be: Vec<u8> = csv.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    std::fs::write(wrk.path("be.csv"), utf16be).unwrap();
+
+    for file in ["le.csv", "be.csv"] {
+        let mut cmd = wrk.command("input");
+        cmd.args(["--from-encoding", "auto"]).arg(file);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        assert_eq!(got, utf8_expected(), "{file}");
+    }
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_shift_jis() {
+    let wrk = Workdir::new("input_from_encoding_shift_jis");
+    // "名前,都市" / "山田太郎,東京都" in Shift-JIS
+    std::fs::write(
+        wrk.path("in.csv"),
+        b"\x96\xbc\x91\x4f,\x93\x73\x8e\x73\n\x8e\x52\x93\x63\x91\xbe\x98\x59,\x93\x8c\x8b\x9e\x93\x73\n",
+    )
+    .unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--from-encoding", "shift_jis"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["名前", "都市"], svec!["山田太郎", "東京都"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_strict_invalid() {
+    let wrk = Workdir::new("input_from_encoding_strict_invalid");
+    // 0x82 0x20 is not a valid Shift-JIS sequence
+    std::fs::write(wrk.path("in.csv"), b"a,b\n1,x\x82 y\n").unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--from-encoding", "shift_jis"])
+        .args(["--encoding-errors", "strict"])
+        .arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn input_from_encoding_unknown() {
+    let wrk = Workdir::new("input_from_encoding_unknown");
+    wrk.create("in.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--from-encoding", "klingon"]).arg("in.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.contains("Unknown --from-encoding: \"klingon\""),
+        "{got}"
+    );
+}
+
+#[test]
+fn input_multichar_delimiter() {
+    let wrk = Workdir::new("input_multichar_delimiter");
+    wrk.create_from_string(
+        "in.txt",
+        "id||name||quote\n1||Smith, John||he said \"hi\"\n2||\"Doe||Jane\"||\"a \"\"b\"\"\"\n",
+    );
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--delimiter", "||"]).arg("in.txt");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"id,name,quote
+1,"Smith, John","he said ""hi"""
+2,Doe||Jane,"a ""b""""#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn input_custom_terminator() {
+    let wrk = Workdir::new("input_custom_terminator");
+    wrk.create_from_string("in.txt", "id~|~name\x1e1~|~multi\nline\x1e2~|~ padded \x1e");
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--delimiter", "~|~"])
+        .args(["--terminator", r"\x1e"])
+        .arg("--trim-fields")
+        .arg("in.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name"],
+        svec!["1", "multi\nline"],
+        svec!["2", "padded"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn input_multichar_terminator_skip_lines() {
+    let wrk = Workdir::new("input_multichar_terminator_skip_lines");
+    wrk.create_from_string(
+        "in.txt",
+        "exported 2024-01-01;;\na,b;;\n1,line1\nline2;;\n2,x;y;;\n3,z;;\nend of file;;\n",
+    );
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--terminator", r";;\n"])
+        .args(["--skip-lines", "1"])
+        .args(["--skip-lastlines", "1"])
+        .arg("in.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["a", "b"],
+        svec!["1", "line1\nline2"],
+        svec!["2", "x;y"],
+        svec!["3", "z"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn input_multichar_delimiter_autoskip_error() {
+    let wrk = Workdir::new("input_multichar_delimiter_autoskip_error");
+    wrk.create_from_string("in.txt", "a||b\n1||2\n");
+
+    let mut cmd = wrk.command("input");
+    cmd.args(["--delimiter", "||"])
+        .arg("--auto-skip")
+        .arg("in.txt");
+
+    wrk.assert_err(&mut cmd);
+}
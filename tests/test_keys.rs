@@ -0,0 +1,174 @@
+use crate::workdir::Workdir;
+
+fn people() -> Vec<Vec<String>> {
+    vec![
+        svec!["id", "first", "last", "zip", "city", "state"],
+        svec!["1", "Ann", "Lee", "10001", "New York", "NY"],
+        svec!["2", "Bob", "Lee", "10001", "New York", "NY"],
+        svec!["3", "Ann", "Kim", "94105", "San Francisco", "CA"],
+        svec!["4", "Cid", "Kim", "94105", "San Francisco", "CA"],
+        svec!["5", "Bob", "Ray", "60601", "Chicago", "IL"],
+        svec!["6", "Dee", "Ray", "60601", "Chicago", "IL"],
+    ]
+}
+
+#[test]
+fn keys_unique_keys_and_fds() {
+    let wrk = Workdir::new("keys_unique_keys_and_fds");
+    wrk.create("in.csv", people());
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let mut expected = vec![
+        svec!["kind", "columns", "dependent", "confidence", "support"],
+        svec!["uniqueKey", "id", "", "1", "6"],
+        svec!["uniqueKey", "first,last", "", "1", "6"],
+        svec!["uniqueKey", "first,zip", "", "1", "6"],
+        svec!["uniqueKey", "first,city", "", "1", "6"],
+        svec!["uniqueKey", "first,state", "", "1", "6"],
+    ];
+    for determinant in ["last", "zip", "city", "state"] {
+        for dependent in ["last", "zip", "city", "state"] {
+            if determinant != dependent {
+                expected.push(svec![
+                    "functionalDependency",
+                    determinant,
+                    dependent,
+                    "1",
+                    "6"
+                ]);
+            }
+        }
+    }
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn keys_max_key_size() {
+    let wrk = Workdir::new("keys_max_key_size");
+    wrk.create("in.csv", people());
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv")
+        .args(["--max-key-size", "1"])
+        .arg("--no-fds");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["kind", "columns", "dependent", "confidence", "support"],
+        svec!["uniqueKey", "id", "", "1", "6"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn keys_approximate_fds() {
+    let wrk = Workdir::new("keys_approximate_fds");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["zip", "city"],
+            svec!["10001", "New York"],
+            svec!["10001", "New York"],
+            svec!["10001", "NYC"],
+            svec!["94105", "San Francisco"],
+            svec!["94105", "San Francisco"],
+        ],
+    );
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv").args(["--min-confidence", "0.8"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["kind", "columns", "dependent", "confidence", "support"],
+        svec!["functionalDependency", "zip", "city", "0.8", "5"],
+        svec!["functionalDependency", "city", "zip", "1", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    // zip -> city is below the default confidence threshold
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["kind", "columns", "dependent", "confidence", "support"],
+        svec!["functionalDependency", "city", "zip", "1", "4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn keys_allow_nulls_json() {
+    let wrk = Workdir::new("keys_allow_nulls_json");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["code", "id"],
+            svec!["x", "1"],
+            svec!["", "2"],
+            svec!["y", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv").arg("--no-fds").arg("--json");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        got_json,
+        serde_json::json!({
+            "rowcount": 3,
+            "primaryKey": "id",
+            "uniqueKey": ["id"],
+            "functionalDependencies": []
+        })
+    );
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv")
+        .arg("--no-fds")
+        .arg("--allow-nulls")
+        .arg("--json");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        got_json,
+        serde_json::json!({
+            "rowcount": 3,
+            "primaryKey": "id",
+            "uniqueKey": ["code", "id"],
+            "functionalDependencies": []
+        })
+    );
+}
+
+#[test]
+fn keys_stats_mode_force() {
+    let wrk = Workdir::new("keys_stats_mode_force");
+    wrk.create("in.csv", people());
+
+    let mut cmd = wrk.command("keys");
+    cmd.arg("in.csv")
+        .args(["--stats-mode", "force"])
+        .arg("--no-fds")
+        .arg("--json");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        got_json["uniqueKey"],
+        serde_json::json!([
+            "id",
+            ["first", "last"],
+            ["first", "zip"],
+            ["first", "city"],
+            ["first", "state"]
+        ])
+    );
+}
@@ -380,3 +380,230 @@ fn generate_schema_with_const_and_enum_constraints() {
 
     assert_eq!(output_schema_string, expected_schema);
 }
+
+#[test]
+#[file_serial]
+fn generate_schema_frictionless_and_validate() {
+    let wrk = Workdir::new("generate_schema_frictionless_and_validate").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    let csv = "id,name,const_col,enum_col
+1,r1,const,alpha
+2,r2,const,beta
+3,,const,alpha
+4,r4,const,beta
+";
+    wrk.create_from_string("tableschema_test.csv", csv);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("tableschema_test.csv")
+        .args(["--format", "frictionless"])
+        .args(["--enum-threshold", "3"]);
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String =
+        wrk.from_str(&wrk.path("tableschema_test.csv.tableschema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse table schema json");
+
+    let fields = output_schema_json["fields"].as_array().unwrap();
+    let names: Vec<&str> = fields.iter().map(|f| f["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["id", "name", "const_col", "enum_col"]);
+    assert_eq!(fields[0]["type"], "integer");
+    assert_eq!(fields[0]["constraints"]["required"], true);
+    assert_eq!(fields[0]["constraints"]["minimum"], 1);
+    assert_eq!(fields[0]["constraints"]["maximum"], 4);
+    assert_eq!(fields[1]["type"], "string");
+    assert!(fields[1]["constraints"].get("required").is_none());
+    assert_eq!(
+        fields[2]["constraints"]["enum"],
+        serde_json::json!(["const"])
+    );
+    assert_eq!(
+        fields[3]["constraints"]["enum"],
+        serde_json::json!(["alpha", "beta"])
+    );
+    assert_eq!(output_schema_json["missingValues"], serde_json::json!([""]));
+
+    // the input CSV should be valid against its own Table Schema
+    let mut cmd = wrk.command("validate");
+    cmd.arg("tableschema_test.csv")
+        .arg("tableschema_test.csv.tableschema.json");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_sql_ddl() {
+    let wrk = Workdir::new("generate_schema_sql_ddl").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    let csv = "id,name,score,color
+1,r1,1.5,red
+2,,2.5,blue
+3,r3,3.5,red
+4,r4,4.5,blue
+";
+    wrk.create_from_string("tbl.csv", csv);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("tbl.csv")
+        .args(["--format", "sql-postgres"])
+        .args(["--enum-threshold", "2"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("tbl.csv.postgres.sql"));
+    let expected = r#"CREATE TABLE "tbl" (
+    "id" INTEGER NOT NULL,
+    "name" TEXT,
+    "score" DOUBLE PRECISION NOT NULL,
+    "color" TEXT NOT NULL CHECK ("color" IN ('blue', 'red'))
+);
+"#;
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("tbl.csv")
+        .args(["--format", "sql-sqlite"])
+        .args(["--enum-threshold", "2"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("tbl.csv.sqlite.sql"));
+    let expected = r#"CREATE TABLE "tbl" (
+    "id" INTEGER NOT NULL,
+    "name" TEXT,
+    "score" REAL NOT NULL,
+    "color" TEXT NOT NULL CHECK ("color" IN ('blue', 'red'))
+);
+"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_avro() {
+    let wrk = Workdir::new("generate_schema_avro").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    let csv = "id,first name,score
+1,r1,1.5
+2,,2.5
+3,r3,3.5
+";
+    wrk.create_from_string("my-data.csv", csv);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("my-data.csv")
+        .args(["--format", "avro"])
+        .args(["--enum-threshold", "0"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("my-data.csv.avsc"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    let expected = serde_json::json!({
+        "type": "record",
+        "name": "my_data",
+        "fields": [
+            {"name": "id", "type": "int", "doc": "id column from my-data.csv"},
+            {
+                "name": "first_name",
+                "type": ["null", "string"],
+                "default": null,
+                "doc": "first name column from my-data.csv"
+            },
+            {"name": "score", "type": "double", "doc": "score column from my-data.csv"}
+        ]
+    });
+    assert_json_eq!(got_json, expected);
+}
+
+#[test]
+fn generate_schema_invalid_format() {
+    let wrk = Workdir::new("generate_schema_invalid_format").flexible(true);
+    wrk.create_from_string("data.csv", "a\n1\n");
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "xml"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_multiple_inputs() {
+    let wrk = Workdir::new("generate_schema_multiple_inputs").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    wrk.create_from_string(
+        "jan.csv",
+        "id,amount,status
+1,10,open
+2,20,closed
+",
+    );
+    wrk.create_from_string(
+        "feb.csv",
+        "id,amount,status,note
+3,1.5,open,late
+4,2.5,,early
+",
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("jan.csv")
+        .arg("feb.csv")
+        .args(["--enum-threshold", "0"]);
+    let stderr = wrk.output_stderr(&mut cmd);
+    assert!(stderr.contains("note: missing, optional in the unified schema"));
+    assert!(stderr.contains("amount: integer widened to number"));
+    assert!(stderr.contains("feb.csv: agrees with the unified schema"));
+
+    let got: String = wrk.from_str(&wrk.path("unified.schema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    let properties = &got_json["properties"];
+
+    assert_json_eq!(
+        properties["id"],
+        serde_json::json!({
+            "description": "id column from 2 files",
+            "type": ["integer"],
+            "minimum": 1,
+            "maximum": 4
+        })
+    );
+    assert_json_eq!(
+        properties["amount"],
+        serde_json::json!({
+            "description": "amount column from 2 files",
+            "type": ["number"],
+            "minimum": 1.5,
+            "maximum": 20
+        })
+    );
+    assert_eq!(
+        properties["status"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(
+        properties["note"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(
+        properties["note"]["description"],
+        serde_json::json!("note column from 1 of 2 files")
+    );
+    assert_eq!(
+        got_json["required"],
+        serde_json::json!(["id", "amount", "status"])
+    );
+}
+
+#[test]
+fn generate_schema_multiple_inputs_polars_unsupported() {
+    let wrk = Workdir::new("generate_schema_multiple_inputs_polars").flexible(true);
+    wrk.create_from_string("a.csv", "x\n1\n");
+    wrk.create_from_string("b.csv", "x\n2\n");
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("a.csv").arg("b.csv").args(["--format", "polars"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,197 @@
+use crate::workdir::Workdir;
+
+const OLD_SCHEMA: &str = r#"{
+    "$schema": "https://json-schema.org/draft-07/schema",
+    "type": "object",
+    "properties": {
+        "id": {"type": ["integer"], "minimum": 1, "maximum": 100},
+        "amount": {"type": ["number"]},
+        "status": {"type": ["string"], "enum": ["open", "closed"]},
+        "legacy": {"type": ["string", "null"]}
+    },
+    "required": ["id", "amount", "status", "legacy"]
+}"#;
+
+const NEW_SCHEMA: &str = r#"{
+    "$schema": "https://json-schema.org/draft-07/schema",
+    "type": "object",
+    "properties": {
+        "id": {"type": ["integer", "null"], "minimum": 1, "maximum": 200},
+        "amount": {"type": ["integer"]},
+        "status": {"type": ["string"], "enum": ["open", "pending"]},
+        "note": {"type": ["string", "null"]},
+        "region": {"type": ["string"]}
+    },
+    "required": ["id", "amount", "status", "note", "region"]
+}"#;
+
+#[test]
+fn schemadiff_breaking() {
+    let wrk = Workdir::new("schemadiff_breaking");
+    wrk.create_from_string("old.schema.json", OLD_SCHEMA);
+    wrk.create_from_string("new.schema.json", NEW_SCHEMA);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("old.schema.json").arg("new.schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["severity", "column", "change", "old", "new"],
+        svec![
+            "non-breaking",
+            "id",
+            "now nullable",
+            "not nullable",
+            "nullable"
+        ],
+        svec!["non-breaking", "id", "maximum loosened", "100", "200"],
+        svec!["breaking", "amount", "type narrowed", "number", "integer"],
+        svec!["breaking", "status", "enum values removed", "closed", ""],
+        svec!["non-breaking", "status", "enum values added", "", "pending"],
+        svec!["breaking", "legacy", "column removed", "string", ""],
+        svec![
+            "non-breaking",
+            "note",
+            "optional column added",
+            "",
+            "string"
+        ],
+        svec!["breaking", "region", "required column added", "", "string"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn schemadiff_types_only_json() {
+    let wrk = Workdir::new("schemadiff_types_only_json");
+    wrk.create_from_string("old.schema.json", OLD_SCHEMA);
+    wrk.create_from_string("new.schema.json", NEW_SCHEMA);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("old.schema.json")
+        .arg("new.schema.json")
+        .arg("--types-only")
+        .arg("--json");
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["breaking"], serde_json::json!(true));
+    assert_eq!(got_json["breaking_count"], serde_json::json!(3));
+    assert_eq!(got_json["non_breaking_count"], serde_json::json!(2));
+    assert_eq!(
+        got_json["changes"][0],
+        serde_json::json!({
+            "severity": "non-breaking",
+            "column": "id",
+            "change": "now nullable",
+            "old": "not nullable",
+            "new": "nullable"
+        })
+    );
+}
+
+#[test]
+fn schemadiff_csv_non_breaking() {
+    let wrk = Workdir::new("schemadiff_csv_non_breaking");
+    wrk.create_from_string("v1.csv", "id,amount\n1,10\n2,20\n");
+    wrk.create_from_string("v2.csv", "id,amount,note\n3,1.5,\n4,2.5,x\n");
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("v1.csv").arg("v2.csv").arg("--types-only");
+    wrk.assert_success(&mut cmd);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["severity", "column", "change", "old", "new"],
+        svec![
+            "non-breaking",
+            "amount",
+            "type widened",
+            "integer",
+            "number"
+        ],
+        svec![
+            "non-breaking",
+            "note",
+            "optional column added",
+            "",
+            "string"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("v1.csv")
+        .arg("v2.csv")
+        .arg("--types-only")
+        .arg("--strict");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn schemadiff_csv_column_added() {
+    let wrk = Workdir::new("schemadiff_csv_column_added");
+    wrk.create_from_string("v1.csv", "id,amount\n1,10\n2,20\n");
+    wrk.create_from_string("v2.csv", "id,amount,region\n3,30,east\n4,40,west\n");
+
+    // a CSV doesn't declare required fields, so an added column is non-breaking
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("v1.csv").arg("v2.csv").arg("--types-only");
+    wrk.assert_success(&mut cmd);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["severity", "column", "change", "old", "new"],
+        svec![
+            "non-breaking",
+            "region",
+            "optional column added",
+            "",
+            "string"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    // unless the old input is a schema with a required list
+    wrk.create_from_string(
+        "v1.schema.json",
+        r#"{
+    "type": "object",
+    "properties": {
+        "id": {"type": ["integer"]},
+        "amount": {"type": ["integer"]}
+    },
+    "required": ["id", "amount"]
+}"#,
+    );
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("v1.schema.json").arg("v2.csv").arg("--types-only");
+    wrk.assert_err(&mut cmd);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["severity", "column", "change", "old", "new"],
+        svec!["breaking", "region", "required column added", "", "string"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn schemadiff_no_changes() {
+    let wrk = Workdir::new("schemadiff_no_changes");
+    wrk.create_from_string("old.schema.json", OLD_SCHEMA);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("old.schema.json")
+        .arg("old.schema.json")
+        .arg("--strict");
+    wrk.assert_success(&mut cmd);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(
+        got,
+        vec![svec!["severity", "column", "change", "old", "new"]]
+    );
+}
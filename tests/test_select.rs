@@ -317,3 +317,38 @@ fn test_select_random_seeded_subset() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+#[cfg(feature = "polars")]
+fn test_select_parquet_input() {
+    let wrk = Workdir::new("test_select_parquet_input");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["letter", "number", "color"],
+            svec!["alpha", "13", "red"],
+            svec!["beta", "24", "blue"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv").arg("select * from data").args([
+        "--format",
+        "parquet",
+        "--output",
+        "data.parquet",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    // the parquet file is read as a CSV record stream
+    let mut cmd = wrk.command("select");
+    cmd.arg("color,letter").arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["color", "letter"],
+        svec!["red", "alpha"],
+        svec!["blue", "beta"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -262,6 +262,7 @@ Preamble Rows: 0
 Quote Char: none
 Flexible: false
 Is UTF8: true
+Encoding: UTF-8 (confidence: 1)
 Detected Mime Type: text/plain
 Detected Kind: Other
 Retrieved Size (bytes): 27
@@ -280,6 +281,54 @@ Fields:
     assert!(dos2unix(&got).trim_end().ends_with(expected_end));
 }
 
+#[test]
+#[cfg(feature = "encoding")]
+fn sniff_encoding_windows1252() {
+    let wrk = Workdir::new("sniff_encoding_windows1252");
+    // "José Müller", "Zürich", "Renée Façade", "Besançon" in Windows-1252
+    std::fs::write(
+        wrk.path("in.csv"),
+        b"id,name,city\n1,Jos\xe9 M\xfcller,Z\xfcrich\n2,Ren\xe9e Fa\xe7ade,Besan\xe7on\n3,\
+          \xc6tla,\xd8rsted \x97 \x93quoted\x94\n",
+    )
+    .unwrap();
+
+    let mut cmd = wrk.command("sniff");
+    cmd.arg("--json").arg("in.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got["encoding"], "windows-1252");
+    assert_eq!(got["encoding_confidence"], 0.8);
+    assert_eq!(got["is_utf8"], false);
+    assert_eq!(got["num_records"], 3);
+    assert_eq!(got["fields"], serde_json::json!(["id", "name", "city"]));
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn sniff_encoding_windows1252_sample() {
+    let wrk = Workdir::new("sniff_encoding_windows1252_sample");
+    // only the sampled rows are transcoded, but all the records are counted
+    let mut data = b"id,name,city\n".to_vec();
+    for i in 0..200 {
+        data.extend_from_slice(format!("{i},").as_bytes());
+        data.extend_from_slice(b"Jos\xe9 M\xfcller,Z\xfcrich\n");
+    }
+    std::fs::write(wrk.path("in.csv"), data).unwrap();
+
+    let mut cmd = wrk.command("sniff");
+    cmd.arg("--json").args(["--sample", "50"]).arg("in.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got["encoding"], "windows-1252");
+    assert_eq!(got["num_records"], 200);
+    assert_eq!(got["estimated"], false);
+    assert_eq!(got["sampled_records"], 50);
+    assert_eq!(got["fields"], serde_json::json!(["id", "name", "city"]));
+}
+
 #[test]
 fn qsv_sniff_pipe_delimiter_env() {
     let wrk = Workdir::new("qsv_sniff_pipe_delimiter_env");
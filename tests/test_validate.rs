@@ -374,3 +374,417 @@ fn validate_adur_public_toilets_dataset_with_json_schema_url() {
     assert_eq!(adur_errors(), validation_error_output);
     wrk.assert_err(&mut cmd);
 }
+
+#[test]
+fn validate_table_schema() {
+    let wrk = Workdir::new("validate_table_schema").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "active", "born", "parent"],
+            svec!["1", "Y", "31/12/1970", ""],
+            svec!["2", "N", "NA", "1"],
+            svec!["2", "Y", "01/01/2000", "1"],
+            svec!["3", "maybe", "2000-01-01", "9"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "fields": [
+    {"name": "id", "type": "integer"},
+    {"name": "active", "type": "boolean", "trueValues": ["Y"], "falseValues": ["N"]},
+    {"name": "born", "type": "date", "format": "%d/%m/%Y"},
+    {"name": "parent", "type": "integer"}
+  ],
+  "primaryKey": ["id"],
+  "foreignKeys": [
+    {"fields": ["parent"], "reference": {"resource": "", "fields": ["id"]}}
+  ],
+  "missingValues": ["", "NA"]
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let valid_output: String = wrk.from_str(&wrk.path("data.csv.valid"));
+    assert_eq!(
+        valid_output,
+        "id,active,born,parent\n1,Y,31/12/1970,\n2,N,NA,1\n"
+    );
+
+    let validation_errors: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert!(validation_errors.starts_with("row_number\tfield\terror\n"));
+    assert!(validation_errors
+        .contains("3\tid\tprimaryKey value \"2\" is not unique. It is also in row 2.\n"));
+    assert!(validation_errors.contains("4\tactive\t"));
+    assert!(validation_errors.contains("4\tborn\t\"2000-01-01\" does not match date pattern"));
+    assert!(validation_errors.contains("4\tparent\tforeign key value \"9\" not found in input."));
+}
+
+#[test]
+fn validate_table_schema_declared_missing_values() {
+    let wrk = Workdir::new("validate_table_schema_declared_missing_values").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![svec!["id", "parent"], svec!["1", "NA"], svec!["2", ""]],
+    );
+    // empty values are not nulls, as "missingValues" doesn't list ""
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "fields": [
+    {"name": "id", "type": "integer"},
+    {"name": "parent", "type": "integer"}
+  ],
+  "missingValues": ["NA"]
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let valid_output: String = wrk.from_str(&wrk.path("data.csv.valid"));
+    assert_eq!(valid_output, "id,parent\n1,NA\n");
+
+    let validation_errors: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert!(validation_errors.contains("2\t<RECORD>\tCan't cast into Integer. key: parent"));
+}
+
+#[test]
+fn validate_unique_and_foreign_keys() {
+    let wrk = Workdir::new("validate_unique_and_foreign_keys").flexible(true);
+    wrk.create(
+        "states.csv",
+        vec![
+            svec!["code", "name"],
+            svec!["NY", "New York"],
+            svec!["NJ", "New Jersey"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["first", "last", "state"],
+            svec!["John", "Doe", "NY"],
+            svec!["Jane", "Doe", "NJ"],
+            svec!["John", "Doe", "NJ"],
+            svec!["John", "Smith", "CA"],
+            svec!["Jim", "Beam", ""],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "first": {"type": "string"},
+    "last": {"type": "string"},
+    "state": {"type": ["string", "null"]}
+  },
+  "uniqueKey": [["first", "last"]],
+  "foreignKey": {"columns": "state", "reference": "states.csv", "referenceColumns": "code"}
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let validation_errors: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    let expected = "row_number\tfield\terror\n3\tfirst,last\tuniqueKey value \"John, Doe\" is not \
+                    unique. It is also in row 1.\n4\tstate\tforeign key value \"CA\" not found in \
+                    states.csv.\n";
+    assert_eq!(validation_errors, expected);
+
+    let invalid_output: String = wrk.from_str(&wrk.path("data.csv.invalid"));
+    assert_eq!(
+        invalid_output,
+        "first,last,state\nJohn,Doe,NJ\nJohn,Smith,CA\n"
+    );
+}
+
+#[test]
+fn validate_invalid_unique_key_keyword() {
+    let wrk = Workdir::new("validate_invalid_unique_key_keyword").flexible(true);
+    wrk.create("data.csv", vec![svec!["id"], svec!["1"]]);
+    wrk.create_from_string(
+        "schema.json",
+        r#"{"type": "object", "properties": {"id": {"type": "integer"}}, "uniqueKey": 42}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("'uniqueKey' must be a column name"));
+}
+
+#[test]
+fn validate_fix() {
+    let wrk = Workdir::new("validate_fix").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "amount", "active", "joined"],
+            svec!["1", "10.5", "true", "2024-01-31"],
+            svec!["2", "$1,234.00", "Yes", "03/04/2024"],
+            svec!["3", "  7 ", "no", "2024-02-01"],
+            svec!["4", "lots", "true", "2024-02-01"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "id": {"type": "integer"},
+    "amount": {"type": "number"},
+    "active": {"type": "boolean"},
+    "joined": {"type": "string", "format": "date"}
+  }
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--fix");
+    wrk.assert_err(&mut cmd);
+
+    let fixed_output: String = wrk.from_str(&wrk.path("data.csv.fixed"));
+    assert_eq!(
+        fixed_output,
+        "id,amount,active,joined\n1,10.5,true,2024-01-31\n2,1234.00,true,2024-03-04\n3,7,false,\
+         2024-02-01\n4,lots,true,2024-02-01\n"
+    );
+
+    let fix_log: String = wrk.from_str(&wrk.path("data.csv.fix-log.tsv"));
+    assert_eq!(
+        fix_log,
+        "row_number\tfield\toriginal\tfixed\n2\tamount\t$1,234.00\t1234.00\n2\tactive\tYes\ttrue\\
+         n2\tjoined\t03/04/2024\t2024-03-04\n3\tamount\t  7 \t7\n3\tactive\tno\tfalse\n"
+    );
+
+    let valid_output: String = wrk.from_str(&wrk.path("data.csv.valid"));
+    assert_eq!(
+        valid_output,
+        "id,amount,active,joined\n1,10.5,true,2024-01-31\n2,1234.00,true,2024-03-04\n3,7,false,\
+         2024-02-01\n"
+    );
+
+    let invalid_output: String = wrk.from_str(&wrk.path("data.csv.invalid"));
+    assert_eq!(
+        invalid_output,
+        "id,amount,active,joined\n4,lots,true,2024-02-01\n"
+    );
+}
+
+#[test]
+fn validate_fix_only_strips_currencies_and_separators() {
+    let wrk = Workdir::new("validate_fix_only_strips_currencies_and_separators").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["weight", "price", "shipped"],
+            svec!["5kg", "1", "2024-02-01"],
+            svec!["3", "2", "12345"],
+            svec!["7", "1 234 EUR", "2024-02-01"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "weight": {"type": "integer"},
+    "price": {"type": "number"},
+    "shipped": {"type": "string", "format": "date"}
+  }
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--fix");
+    wrk.assert_err(&mut cmd);
+
+    // a weight with a unit is not a number & 12345 is not a date
+    let fix_log: String = wrk.from_str(&wrk.path("data.csv.fix-log.tsv"));
+    assert_eq!(
+        fix_log,
+        "row_number\tfield\toriginal\tfixed\n3\tprice\t1 234 EUR\t1234\n"
+    );
+
+    let invalid_output: String = wrk.from_str(&wrk.path("data.csv.invalid"));
+    assert_eq!(
+        invalid_output,
+        "weight,price,shipped\n5kg,1,2024-02-01\n3,2,12345\n"
+    );
+}
+
+#[test]
+fn validate_report_json_and_annotate() {
+    let wrk = Workdir::new("validate_report_json_and_annotate").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "age"],
+            svec!["1", "Alice", "30"],
+            svec!["2", "B", "forty"],
+            svec!["3", "Carol", "25"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "id": {"type": "integer"},
+    "name": {"type": "string", "minLength": 2},
+    "age": {"type": "integer"}
+  },
+  "required": ["id", "name", "age"]
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report", "json"])
+        .arg("--annotate");
+    wrk.assert_err(&mut cmd);
+
+    let report: String = wrk.from_str(&wrk.path("data.csv.validation-report.json"));
+    let report_json: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report_json["valid"], false);
+    assert_eq!(report_json["record_count"], 3);
+    assert_eq!(report_json["invalid_count"], 1);
+    assert_eq!(
+        report_json["findings"],
+        serde_json::json!([
+            {
+                "row": 2,
+                "field": "age",
+                "keyword": "type",
+                "message": "Can't cast into Integer. key: age, value: forty",
+                "value": "forty"
+            },
+            {
+                "row": 2,
+                "field": "name",
+                "keyword": "minLength",
+                "message": "\"B\" is shorter than 2 characters",
+                "value": "B"
+            }
+        ])
+    );
+
+    let annotated: String = wrk.from_str(&wrk.path("data.csv.annotated"));
+    assert_eq!(
+        annotated,
+        "id,name,age,_errors\n1,Alice,30,\n2,B,forty,\"age: Can't cast into Integer. key: age, \
+         value: forty; name: \"\"B\"\" is shorter than 2 characters\"\n3,Carol,25,\n"
+    );
+}
+
+#[test]
+fn validate_report_junit_all_valid() {
+    let wrk = Workdir::new("validate_report_junit_all_valid").flexible(true);
+    wrk.create("data.csv", vec![svec!["id"], svec!["1"], svec!["2"]]);
+    wrk.create_from_string(
+        "schema.json",
+        r#"{"type": "object", "properties": {"id": {"type": "integer"}}}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report", "junit"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: String = wrk.from_str(&wrk.path("data.csv.validation-report.xml"));
+    assert_eq!(
+        report,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"qsv validate data.csv\" \
+         tests=\"0\" failures=\"0\">\n<testsuite name=\"qsv validate data.csv\" tests=\"0\" \
+         failures=\"0\">\n</testsuite>\n</testsuites>\n"
+    );
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn validate_custom_formats() {
+    let wrk = Workdir::new("validate_custom_formats").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["policy", "card", "isbn"],
+            svec!["POL-12345678", "4111111111111111", "978-0-306-40615-7"],
+            svec!["POL-1234", "4111111111111112", "0-306-40615-2"],
+            svec!["POL-87654321", "79927398713", "978-0-306-40615-6"],
+        ],
+    );
+    wrk.create_from_string(
+        "formats.toml",
+        r#"
+[policy_number]
+pattern = "^POL-[0-9]{8}$"
+
+[card_number]
+checksum = "luhn"
+
+[isbn]
+checksum = "isbn"
+"#,
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "policy": {"type": "string", "format": "policy_number"},
+    "card": {"type": "string", "format": "card_number"},
+    "isbn": {"type": "string", "format": "isbn"}
+  }
+}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--formats", "formats.toml"]);
+    wrk.assert_err(&mut cmd);
+
+    let validation_errors: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    let expected = "row_number\tfield\terror\n2\tpolicy\t\"POL-1234\" is not a \
+                    \"policy_number\"\n2\tcard\t\"4111111111111112\" is not a \
+                    \"card_number\"\n3\tisbn\t\"978-0-306-40615-6\" is not a \"isbn\"\n";
+    assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_custom_formats_invalid_file() {
+    let wrk = Workdir::new("validate_custom_formats_invalid_file").flexible(true);
+    wrk.create("data.csv", vec![svec!["id"], svec!["1"]]);
+    wrk.create_from_string("formats.json", r#"{"sku": {"checksum": "crc32"}}"#);
+    wrk.create_from_string(
+        "schema.json",
+        r#"{"type": "object", "properties": {"id": {"type": "string", "format": "sku"}}}"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--formats", "formats.json"]);
+    wrk.assert_err(&mut cmd);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Cannot parse formats file formats.json"));
+}
@@ -68,6 +68,8 @@ mod test_fetch;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_fill;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_fixedwidth;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_fixlengths;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_flatten;
@@ -76,6 +78,8 @@ mod test_fmt;
 #[cfg(all(feature = "foreach"))]
 mod test_foreach;
 mod test_frequency;
+#[cfg(all(feature = "to", feature = "feature_capable"))]
+mod test_from;
 #[cfg(all(feature = "feature_capable", feature = "geocode"))]
 mod test_geocode;
 mod test_headers;
@@ -89,6 +93,8 @@ mod test_joinp;
 mod test_json;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_jsonl;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_keys;
 #[cfg(feature = "luau")]
 mod test_luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
@@ -107,6 +113,8 @@ mod test_safenames;
 mod test_sample;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_schema;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_schemadiff;
 mod test_search;
 mod test_searchset;
 mod test_select;